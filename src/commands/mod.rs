@@ -3,7 +3,58 @@ pub mod project;
 pub mod system;
 pub mod user;
 
+use clap::Command;
+
 pub use pipeline::{PipelineAction, handle_pipeline_command};
 pub use project::{ProjectAction, handle_project_command};
 pub use system::SystemAction;
 pub use user::UserAction;
+
+/// Attaches the runtime-generated examples of each command group as `after_help`
+///
+/// The examples depend on the host (detected target, resolved URLs), so they
+/// cannot be static doc comments.
+pub fn with_help_examples(command: Command) -> Command {
+    command.mut_subcommands(|subcommand| {
+        let examples = match subcommand.get_name() {
+            "project" => ProjectAction::help_examples(),
+            "pipeline" => PipelineAction::help_examples(),
+            "system" => SystemAction::help_examples(),
+            "user" => UserAction::help_examples(),
+            _ => return subcommand,
+        };
+        subcommand.after_help(examples)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::SystemTarget;
+    use clap::Subcommand;
+
+    #[test]
+    fn test_system_help_includes_detected_target() {
+        let command = Command::new("ag")
+            .subcommand(SystemAction::augment_subcommands(Command::new("system")))
+            .subcommand(UserAction::augment_subcommands(Command::new("user")));
+        let mut command = with_help_examples(command);
+
+        let help = command
+            .find_subcommand_mut("system")
+            .unwrap()
+            .render_long_help()
+            .to_string();
+        assert!(help.contains("ag system status --checksums"));
+        if let Ok(target) = SystemTarget::detect() {
+            assert!(help.contains(&target.to_string()));
+        }
+
+        let help = command
+            .find_subcommand_mut("user")
+            .unwrap()
+            .render_help()
+            .to_string();
+        assert!(help.contains("ag user login"));
+    }
+}
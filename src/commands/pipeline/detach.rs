@@ -0,0 +1,270 @@
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{BinResult, fs::write_atomic};
+
+/// Processes of a pipeline started with `pipeline spawn --detach`, saved to its PID file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetachedPipeline {
+    pub name: String,
+    pub s3_pid: u32,
+    pub pipeline_pid: u32,
+    /// Address the S3 server listens on
+    pub s3_addr: String,
+    /// [`process_identity`] of the S3 server when it was detached
+    #[serde(default)]
+    pub s3_identity: Option<String>,
+    /// [`process_identity`] of the pipeline when it was detached
+    #[serde(default)]
+    pub pipeline_identity: Option<String>,
+}
+
+impl DetachedPipeline {
+    /// Writes the PID file, creating the pipelines directory if needed
+    pub fn save(&self, path: &Path) -> BinResult<()> {
+        write_atomic(path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    /// Whether the recorded pipeline process is still the one that was detached
+    pub fn is_pipeline_running(&self) -> bool {
+        is_same_process(self.pipeline_pid, self.pipeline_identity.as_deref())
+    }
+
+    /// Reads a PID file, `None` when the pipeline was not started detached
+    pub fn load(path: &Path) -> BinResult<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let content = std::fs::read_to_string(path)?;
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| format!("Invalid PID file {}: {}", path.display(), e).into())
+    }
+}
+
+/// Stops the processes recorded in the PID file at `path` and removes the file
+///
+/// The pipeline is stopped before its S3 server. Processes that already
+/// exited are skipped. When a recorded PID now belongs to another process
+/// (after a reboot or PID reuse), the file is stale and nothing is signalled.
+/// Returns the PIDs that were signalled, or `None` when there is no PID file.
+pub fn stop_detached(path: &Path) -> BinResult<Option<Vec<u32>>> {
+    let Some(detached) = DetachedPipeline::load(path)? else {
+        return Ok(None);
+    };
+
+    let processes = [
+        (detached.pipeline_pid, detached.pipeline_identity.as_deref()),
+        (detached.s3_pid, detached.s3_identity.as_deref()),
+    ];
+    if processes
+        .iter()
+        .any(|(pid, identity)| is_running(*pid) && !is_same_process(*pid, *identity))
+    {
+        std::fs::remove_file(path)?;
+        return Ok(Some(Vec::new()));
+    }
+
+    let mut stopped = Vec::new();
+    for (pid, _) in processes {
+        if is_running(pid) {
+            terminate(pid)?;
+            stopped.push(pid);
+        }
+    }
+    std::fs::remove_file(path)?;
+    Ok(Some(stopped))
+}
+
+/// Whether a process with this PID exists
+///
+/// PID 0 is never reported running: `kill` would address the whole process group.
+#[cfg(unix)]
+pub fn is_running(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+    Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Whether a process with this PID exists
+#[cfg(not(unix))]
+pub fn is_running(pid: u32) -> bool {
+    if pid == 0 {
+        return false;
+    }
+    Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH"])
+        .stderr(Stdio::null())
+        .output()
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).contains(&pid.to_string()))
+}
+
+/// Start time and command line of a running process, `None` when it is not running
+///
+/// Saved next to the PID so a later `pipeline stop --local` can tell the
+/// detached process from an unrelated one that reused its PID.
+#[cfg(unix)]
+pub fn process_identity(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    let output = Command::new("ps")
+        .args(["-o", "lstart=,args=", "-p", &pid.to_string()])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let identity = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (output.status.success() && !identity.is_empty()).then_some(identity)
+}
+
+/// Image name of a running process, `None` when it is not running
+#[cfg(not(unix))]
+pub fn process_identity(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    let output = Command::new("tasklist")
+        .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let line = stdout.lines().next()?;
+    line.contains(&format!("\"{}\"", pid))
+        .then(|| line.split(',').next().unwrap_or_default().to_string())
+}
+
+/// Whether `pid` still runs the process recorded as `identity`
+///
+/// A missing identity (PID files from older CLIs) never matches.
+fn is_same_process(pid: u32, identity: Option<&str>) -> bool {
+    identity.is_some() && process_identity(pid).as_deref() == identity
+}
+
+/// Asks a process to exit (SIGTERM on Unix)
+fn terminate(pid: u32) -> BinResult<()> {
+    if pid == 0 {
+        return Err("Refusing to signal PID 0".into());
+    }
+    #[cfg(unix)]
+    let status = Command::new("kill").arg(pid.to_string()).status()?;
+    #[cfg(not(unix))]
+    let status = Command::new("taskkill")
+        .args(["/PID", &pid.to_string()])
+        .status()?;
+    if !status.success() {
+        return Err(format!("Could not stop process {} ({})", pid, status).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pid_file_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("pipelines").join("demo.pid");
+        assert_eq!(DetachedPipeline::load(&path).unwrap(), None);
+
+        let detached = DetachedPipeline {
+            name: "demo".to_string(),
+            s3_pid: 100,
+            pipeline_pid: 101,
+            s3_addr: "127.0.0.1:9000".to_string(),
+            s3_identity: Some("Fri Oct 16 12:00:00 2026 s3fs".to_string()),
+            pipeline_identity: None,
+        };
+        detached.save(&path).unwrap();
+        assert_eq!(DetachedPipeline::load(&path).unwrap(), Some(detached));
+
+        std::fs::write(&path, "not json").unwrap();
+        assert!(DetachedPipeline::load(&path).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_detached_signals_processes() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("demo.pid");
+        let mut s3 = Command::new("sleep").arg("30").spawn().unwrap();
+        let mut pipeline = Command::new("sleep").arg("30").spawn().unwrap();
+        DetachedPipeline {
+            name: "demo".to_string(),
+            s3_pid: s3.id(),
+            pipeline_pid: pipeline.id(),
+            s3_addr: "127.0.0.1:9000".to_string(),
+            s3_identity: process_identity(s3.id()),
+            pipeline_identity: process_identity(pipeline.id()),
+        }
+        .save(&path)
+        .unwrap();
+
+        let stopped = stop_detached(&path).unwrap().unwrap();
+        assert_eq!(stopped, vec![pipeline.id(), s3.id()]);
+        assert_eq!(pipeline.wait().unwrap().signal(), Some(15));
+        assert_eq!(s3.wait().unwrap().signal(), Some(15));
+        assert!(!path.exists());
+
+        assert_eq!(stop_detached(&path).unwrap(), None);
+    }
+
+    #[test]
+    fn test_stop_detached_ignores_pid_zero() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("demo.pid");
+        DetachedPipeline {
+            name: "demo".to_string(),
+            s3_pid: 0,
+            pipeline_pid: 0,
+            s3_addr: "127.0.0.1:9000".to_string(),
+            s3_identity: None,
+            pipeline_identity: None,
+        }
+        .save(&path)
+        .unwrap();
+
+        assert!(!is_running(0));
+        assert!(terminate(0).is_err());
+        assert_eq!(stop_detached(&path).unwrap(), Some(Vec::new()));
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stop_detached_skips_reused_pid() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("demo.pid");
+        // An unrelated process now holds the recorded PID
+        let mut unrelated = Command::new("sleep").arg("30").spawn().unwrap();
+        DetachedPipeline {
+            name: "demo".to_string(),
+            s3_pid: unrelated.id(),
+            pipeline_pid: unrelated.id(),
+            s3_addr: "127.0.0.1:9000".to_string(),
+            s3_identity: Some("Thu Jan  1 00:00:00 2026 /old/s3fs".to_string()),
+            pipeline_identity: None,
+        }
+        .save(&path)
+        .unwrap();
+
+        assert_eq!(stop_detached(&path).unwrap(), Some(Vec::new()));
+        assert!(!path.exists());
+        assert!(is_running(unrelated.id()), "must not be signalled");
+
+        unrelated.kill().unwrap();
+        unrelated.wait().unwrap();
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use crate::utils::BinResult;
+
+/// A `--env KEY=VALUE` variable passed to the spawned processes
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for EnvVar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected KEY=VALUE, got '{}'", s))?;
+        let key = key.trim();
+        validate_key(key)?;
+
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Checks that `key` is a portable environment variable name
+fn validate_key(key: &str) -> Result<(), String> {
+    let valid = key
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(format!(
+            "Invalid variable name '{}': use letters, digits and underscores, not starting with a digit",
+            key
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a dotenv-style file
+///
+/// Blank lines and `#` comments are skipped, an `export ` prefix is allowed
+/// and values may be wrapped in single or double quotes.
+pub fn parse_env_file(content: &str) -> Result<Vec<EnvVar>, String> {
+    let mut vars = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+
+        let mut var: EnvVar = line
+            .parse()
+            .map_err(|e| format!("line {}: {}", index + 1, e))?;
+        let value = var.value.trim();
+        var.value = [('"', '"'), ('\'', '\'')]
+            .iter()
+            .find_map(|(open, close)| {
+                value
+                    .strip_prefix(*open)
+                    .and_then(|rest| rest.strip_suffix(*close))
+            })
+            .unwrap_or(value)
+            .to_string();
+        vars.push(var);
+    }
+
+    Ok(vars)
+}
+
+/// Merges the `--env-file` variables with `--env` flags, the flags taking precedence
+pub fn build_env(env_file: Option<&Path>, vars: &[EnvVar]) -> BinResult<BTreeMap<String, String>> {
+    let mut env = BTreeMap::new();
+
+    if let Some(path) = env_file {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Could not read {}: {}", path.display(), e))?;
+        let file_vars =
+            parse_env_file(&content).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+        for var in file_vars {
+            env.insert(var.key, var.value);
+        }
+    }
+    for var in vars {
+        env.insert(var.key.clone(), var.value.clone());
+    }
+
+    Ok(env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_env_flag() {
+        let var: EnvVar = "AWS_SECRET_ACCESS_KEY=a=b".parse().unwrap();
+        assert_eq!(var.key, "AWS_SECRET_ACCESS_KEY");
+        assert_eq!(var.value, "a=b");
+
+        let empty: EnvVar = "EMPTY=".parse().unwrap();
+        assert_eq!(empty.value, "");
+
+        assert!("NOVALUE".parse::<EnvVar>().is_err());
+        assert!("1ABC=x".parse::<EnvVar>().is_err());
+        assert!("MY-KEY=x".parse::<EnvVar>().is_err());
+        assert!("=x".parse::<EnvVar>().is_err());
+    }
+
+    #[test]
+    fn test_env_file_and_flags() {
+        let temp_dir = TempDir::new().unwrap();
+        let env_file = temp_dir.path().join(".env");
+        fs::write(
+            &env_file,
+            "# S3 credentials\n\
+             AWS_ACCESS_KEY_ID=key\n\
+             \n\
+             export AWS_SECRET_ACCESS_KEY=\"secret value\"\n\
+             \x20  REGION='eu-west-1'\n\
+             LOG_LEVEL=info\n",
+        )
+        .unwrap();
+        let flags = ["LOG_LEVEL=debug".parse().unwrap()];
+
+        let env = build_env(Some(&env_file), &flags).unwrap();
+
+        assert_eq!(
+            env,
+            BTreeMap::from([
+                ("AWS_ACCESS_KEY_ID".to_string(), "key".to_string()),
+                (
+                    "AWS_SECRET_ACCESS_KEY".to_string(),
+                    "secret value".to_string()
+                ),
+                ("LOG_LEVEL".to_string(), "debug".to_string()),
+                ("REGION".to_string(), "eu-west-1".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_env_file_errors_name_the_line() {
+        let err = parse_env_file("# ok\nA=1\nnot a variable\n").unwrap_err();
+        assert!(err.starts_with("line 3:"));
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    error::Error,
+    io::{self, Write},
+    str::FromStr,
+    time::Duration,
+};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use futures_util::TryStreamExt;
+use reqwest::{Client, StatusCode};
+
+use crate::utils::{
+    ApiError, AppConfig, AuthTokens,
+    net::{
+        api::{CliError, api_get_text},
+        client::response_request_id,
+    },
+    send_api_request,
+};
+
+/// Number of lines fetched when neither `--since` nor `--tail` is given
+pub const DEFAULT_TAIL: usize = 100;
+
+/// Start of the log window requested with `--since`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Since {
+    /// Relative to now, e.g. `10m`
+    Ago(Duration),
+    /// An absolute RFC 3339 time, e.g. `2024-01-01T00:00:00Z`
+    At(DateTime<Utc>),
+}
+
+impl Since {
+    /// Absolute start time, resolving relative durations against `now`
+    pub fn resolve(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Ago(duration) => {
+                now - chrono::Duration::from_std(*duration).unwrap_or(chrono::Duration::MAX)
+            }
+            Self::At(time) => *time,
+        }
+    }
+}
+
+impl FromStr for Since {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(duration) = parse_duration(s) {
+            return Ok(Self::Ago(duration));
+        }
+        DateTime::parse_from_rfc3339(s)
+            .map(|time| Self::At(time.with_timezone(&Utc)))
+            .map_err(|_| {
+                format!(
+                    "'{}' is neither a duration (e.g. 30s, 10m, 2h, 1d) nor an RFC 3339 time",
+                    s
+                )
+            })
+    }
+}
+
+/// Parses a duration made of a number and a unit: `s`, `m`, `h` or `d`
+fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    let unit_start = s.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = s.split_at(unit_start);
+    let value: u64 = value.parse().ok()?;
+    let secs = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return None,
+    };
+    value.checked_mul(secs).map(Duration::from_secs)
+}
+
+/// Which log lines to request
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogsQuery {
+    pub since: Option<Since>,
+    pub tail: Option<usize>,
+}
+
+impl LogsQuery {
+    /// Query parameters for the logs endpoint, defaulting to the last
+    /// [`DEFAULT_TAIL`] lines when no filter is set
+    fn params(&self, now: DateTime<Utc>) -> Vec<(&'static str, String)> {
+        let mut params = Vec::new();
+        if let Some(since) = &self.since {
+            params.push((
+                "since",
+                since
+                    .resolve(now)
+                    .to_rfc3339_opts(SecondsFormat::Secs, true),
+            ));
+        }
+        match (self.tail, &self.since) {
+            (Some(tail), _) => params.push(("tail", tail.to_string())),
+            (None, None) => params.push(("tail", DEFAULT_TAIL.to_string())),
+            (None, Some(_)) => {}
+        }
+        params
+    }
+}
+
+/// Fetches and prints the logs of a pipeline, optionally following new lines
+pub(super) async fn handle_logs(
+    config: &AppConfig,
+    client: &Client,
+    tokens: &AuthTokens,
+    name: &str,
+    follow: bool,
+    query: &LogsQuery,
+) -> Result<(), Box<dyn Error>> {
+    let url = logs_url(config, name);
+    let params = query.params(Utc::now());
+
+    let (status, error): (_, Box<dyn Error>) = if follow {
+        match follow_logs(client, &url, tokens.id_token(), &params).await {
+            Ok(()) => return Ok(()),
+            Err(e) => (e.status(), e.into()),
+        }
+    } else {
+        match fetch_logs(client, &url, tokens.id_token(), &params).await {
+            Ok(logs) => {
+                print!("{}", logs);
+                return Ok(());
+            }
+            Err(e) => (e.status(), e.into()),
+        }
+    };
+
+    match status {
+        Some(StatusCode::UNAUTHORIZED) => {
+            println!("Authentication failed. Please try to log in again.");
+            Ok(())
+        }
+        Some(StatusCode::NOT_FOUND) => {
+            println!("Pipeline '{}' not found.", name);
+            Ok(())
+        }
+        _ => Err(error),
+    }
+}
+
+/// URL of the logs endpoint for a pipeline
+fn logs_url(config: &AppConfig, name: &str) -> String {
+    config.api_url(&format!("pipelines/{}/logs", urlencoding::encode(name)))
+}
+
+/// Fetches the current pipeline logs in one request
+async fn fetch_logs(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+    params: &[(&str, String)],
+) -> Result<String, CliError> {
+    api_get_text(client, url, id_token, params).await
+}
+
+/// Streams pipeline logs to stdout as they are produced, until the server closes the stream
+///
+/// This goes through [`send_api_request`] rather than the [`api`](crate::utils::net::api)
+/// helpers: those buffer the whole body under a size cap and a timeout covering
+/// the full read, while a followed stream is unbounded and stays open for as
+/// long as the pipeline runs.
+async fn follow_logs(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+    params: &[(&str, String)],
+) -> Result<(), ApiError> {
+    let request = client
+        .get(url)
+        .bearer_auth(id_token)
+        .query(params)
+        .query(&[("follow", "true")]);
+    let response = send_api_request(request).await?;
+    let request_id = response_request_id(&response);
+    let mut stream = response.bytes_stream();
+    let mut stdout = io::stdout();
+
+    while let Some(chunk) = stream
+        .try_next()
+        .await
+        .map_err(|source| ApiError::Request {
+            request_id: request_id.clone(),
+            source,
+        })?
+    {
+        let _ = stdout.write_all(&chunk);
+        let _ = stdout.flush();
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::net::client::REQUEST_ID_HEADER;
+    use std::path::PathBuf;
+    use wiremock::matchers::{header, header_exists, method, path, query_param};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_fetch_logs() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/pipelines/my-pipeline/logs"))
+            .and(header("authorization", "Bearer id-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("line 1\nline 2\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        let url = logs_url(&config, "my-pipeline");
+
+        let logs = fetch_logs(&Client::new(), &url, "id-token", &[])
+            .await
+            .unwrap();
+        assert_eq!(logs, "line 1\nline 2\n");
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        let url = logs_url(&config, "missing");
+
+        let err = fetch_logs(&Client::new(), &url, "id-token", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_unauthorized_reports_request_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header_exists(REQUEST_ID_HEADER))
+            .respond_with(ResponseTemplate::new(401).insert_header(REQUEST_ID_HEADER, "srv-42"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        let url = logs_url(&config, "my-pipeline");
+
+        let err = fetch_logs(&Client::new(), &url, "id-token", &[])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, CliError::Auth { .. }));
+        assert_eq!(err.request_id(), "srv-42");
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2024-06-01T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn test_since_duration_params() {
+        let query = LogsQuery {
+            since: Some("10m".parse().unwrap()),
+            tail: None,
+        };
+        assert_eq!(query.since, Some(Since::Ago(Duration::from_secs(600))));
+        assert_eq!(
+            query.params(now()),
+            [("since", "2024-06-01T11:50:00Z".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_since_timestamp_params() {
+        let query = LogsQuery {
+            since: Some("2024-01-01T00:00:00Z".parse().unwrap()),
+            tail: Some(20),
+        };
+        assert_eq!(
+            query.params(now()),
+            [
+                ("since", "2024-01-01T00:00:00Z".to_string()),
+                ("tail", "20".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_tail_and_invalid_since() {
+        assert_eq!(
+            LogsQuery::default().params(now()),
+            [("tail", DEFAULT_TAIL.to_string())]
+        );
+        assert!("10 minutes".parse::<Since>().is_err());
+        assert!("m".parse::<Since>().is_err());
+        assert_eq!(parse_duration("2d"), Some(Duration::from_secs(2 * 86400)));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_logs_sends_filters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("since", "2024-06-01T11:50:00Z"))
+            .and(query_param("tail", "5"))
+            .respond_with(ResponseTemplate::new(200).set_body_string("recent\n"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        let url = logs_url(&config, "my-pipeline");
+        let query = LogsQuery {
+            since: Some(Since::Ago(Duration::from_secs(600))),
+            tail: Some(5),
+        };
+
+        let logs = fetch_logs(&Client::new(), &url, "id-token", &query.params(now()))
+            .await
+            .unwrap();
+        assert_eq!(logs, "recent\n");
+    }
+}
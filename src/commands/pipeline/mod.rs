@@ -1,4 +1,17 @@
+mod detach;
+mod env;
+mod logs;
+mod spawn;
+mod stop;
+mod wait;
+
+use std::path::PathBuf;
+
 use clap::Subcommand;
+use reqwest::Client;
+
+use crate::commands::user::authenticate;
+use crate::utils::{AppConfig, confirm};
 
 #[derive(Subcommand, Debug)]
 pub enum PipelineAction {
@@ -6,22 +19,205 @@ pub enum PipelineAction {
     Spawn {
         /// Name of the pipeline
         name: String,
+        /// Set an environment variable for the spawned processes, repeatable
+        #[arg(long = "env", value_name = "KEY=VALUE")]
+        env: Vec<env::EnvVar>,
+        /// Read environment variables from a dotenv-style file (--env takes precedence)
+        #[arg(long, value_name = "PATH")]
+        env_file: Option<PathBuf>,
+        /// Wait until the platform reports the pipeline running, failing if it does not
+        #[arg(long)]
+        wait: bool,
+        /// Seconds to wait with --wait (default 120)
+        #[arg(
+            long,
+            value_name = "SECS",
+            requires = "wait",
+            conflicts_with = "detach"
+        )]
+        timeout: Option<u64>,
+        /// Start the processes in the background and return, see `pipeline stop --local`
+        #[arg(long, conflicts_with = "wait")]
+        detach: bool,
     },
     /// Get information about a pipeline
     Info {
         /// Name of the pipeline
         name: String,
     },
+    /// Print the logs of a pipeline
+    Logs {
+        /// Name of the pipeline
+        name: String,
+        /// Keep streaming new log lines as they are produced
+        #[arg(long, short = 'f')]
+        follow: bool,
+        /// Only show lines newer than a duration ago (e.g. 10m, 2h) or an RFC 3339 time
+        #[arg(long, value_name = "TIME")]
+        since: Option<logs::Since>,
+        /// Only show the last N lines (default: 100 when --since is not given)
+        #[arg(long, value_name = "N")]
+        tail: Option<usize>,
+    },
+    /// Stop a running pipeline
+    Stop {
+        /// Name of the pipeline
+        name: String,
+        /// Stop the local processes started with `spawn --detach` instead of the platform pipeline
+        #[arg(long)]
+        local: bool,
+    },
+}
+
+impl PipelineAction {
+    /// Examples shown after `pipeline --help`
+    pub fn help_examples() -> String {
+        format!(
+            "Examples:\n  \
+             ag pipeline spawn my-pipeline --wait --timeout 60\n  \
+             ag pipeline spawn my-pipeline --env-file .env --env LOG_LEVEL=debug\n  \
+             ag pipeline spawn my-pipeline --detach     # then `pipeline stop my-pipeline --local`\n  \
+             ag pipeline logs my-pipeline --follow      # last {} lines, then new ones\n  \
+             ag pipeline logs my-pipeline --since 10m",
+            logs::DEFAULT_TAIL
+        )
+    }
 }
 
-pub async fn handle_pipeline_command(action: PipelineAction) {
+pub async fn handle_pipeline_command(action: PipelineAction, config: &AppConfig) {
     match action {
-        PipelineAction::Spawn { name } => {
+        PipelineAction::Spawn {
+            name,
+            env,
+            env_file,
+            wait,
+            timeout,
+            detach,
+        } => {
+            // Authenticate before starting anything so --wait can poll the platform
+            let waiter = if wait {
+                let Some(client) = http_client(config) else {
+                    std::process::exit(1);
+                };
+                let Some(tokens) = authenticate(config, &client).await else {
+                    std::process::exit(1);
+                };
+                let mut waiter = wait::StatusWaiter::new(config, client, tokens, &name);
+                if let Some(timeout) = timeout {
+                    waiter.timeout = std::time::Duration::from_secs(timeout);
+                }
+                Some(waiter)
+            } else {
+                None
+            };
+
             println!("Spawning pipeline: {}", name);
+            let result = match env::build_env(env_file.as_deref(), &env) {
+                Ok(env) => spawn::handle_spawn(config, &name, env, waiter.as_ref(), detach).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = result {
+                eprintln!("Error spawning pipeline '{}': {}", name, e);
+                std::process::exit(1);
+            }
         }
         PipelineAction::Info { name } => {
             println!("Getting info for pipeline: {}", name);
             // TODO: Implement pipeline info retrieval logic
         }
+        PipelineAction::Logs {
+            name,
+            follow,
+            since,
+            tail,
+        } => {
+            let Some(client) = http_client(config) else {
+                return;
+            };
+            let Some(tokens) = authenticate(config, &client).await else {
+                return;
+            };
+
+            let query = logs::LogsQuery { since, tail };
+            if let Err(e) = logs::handle_logs(config, &client, &tokens, &name, follow, &query).await
+            {
+                eprintln!("Error fetching logs for pipeline '{}': {}", name, e);
+            }
+        }
+        PipelineAction::Stop { name, local: true } => {
+            match detach::stop_detached(&config.layout().pipeline_pid_file(&name)) {
+                Ok(Some(stopped)) if stopped.is_empty() => {
+                    println!("Pipeline '{}' had already exited.", name)
+                }
+                Ok(Some(stopped)) => println!(
+                    "Stopped local pipeline '{}' ({} processes).",
+                    name,
+                    stopped.len()
+                ),
+                Ok(None) => println!("No pipeline '{}' is running in the background.", name),
+                Err(e) => {
+                    eprintln!("Error stopping local pipeline '{}': {}", name, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        PipelineAction::Stop { name, local: false } => {
+            match confirm(config, &format!("Stop pipeline '{}'?", name)) {
+                Ok(true) => {}
+                Ok(false) => {
+                    println!("Aborted, pipeline '{}' left running.", name);
+                    return;
+                }
+                Err(e) => {
+                    eprintln!("Error reading confirmation: {}", e);
+                    return;
+                }
+            }
+
+            let Some(client) = http_client(config) else {
+                return;
+            };
+            let Some(tokens) = authenticate(config, &client).await else {
+                return;
+            };
+
+            if let Err(e) = stop::handle_stop(config, &client, &tokens, &name).await {
+                eprintln!("Error stopping pipeline '{}': {}", name, e);
+            }
+        }
+    }
+}
+
+/// Builds the platform HTTP client, reporting invalid TLS settings
+fn http_client(config: &AppConfig) -> Option<Client> {
+    match config.http_client() {
+        Ok(client) => Some(client),
+        Err(e) => {
+            eprintln!("Error creating HTTP client: {}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clap::{Command, Subcommand};
+
+    #[test]
+    fn test_spawn_detach_conflicts_with_wait() {
+        let command = PipelineAction::augment_subcommands(Command::new("pipeline"));
+
+        let error = command
+            .clone()
+            .try_get_matches_from(["pipeline", "spawn", "demo", "--detach", "--wait"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
+
+        // --timeout only applies to --wait, so it is rejected with --detach too
+        let error = command
+            .try_get_matches_from(["pipeline", "spawn", "demo", "--detach", "--timeout", "5"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::ArgumentConflict);
     }
 }
@@ -0,0 +1,494 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use tokio::process::{Child, Command};
+
+use super::detach::{DetachedPipeline, process_identity};
+use super::wait::StatusWaiter;
+use crate::utils::{
+    AppConfig, BinResult,
+    app::events::{Event, EventEmitter},
+    bin::kind::BinaryKind,
+    bin::manager::spawn_binary_streaming_with_provider,
+};
+
+/// How long to wait for the S3 server to accept connections
+const S3_READY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Stage of `pipeline spawn`, reported in order as it progresses
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnStage {
+    StartingS3,
+    S3Ready { addr: String },
+    LaunchingPipeline,
+    PipelineRunning { pid: u32 },
+}
+
+impl SpawnStage {
+    /// Stable identifier used in `--events` output
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::StartingS3 => "starting_s3",
+            Self::S3Ready { .. } => "s3_ready",
+            Self::LaunchingPipeline => "launching_pipeline",
+            Self::PipelineRunning { .. } => "pipeline_running",
+        }
+    }
+}
+
+impl fmt::Display for SpawnStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::StartingS3 => write!(f, "Starting S3 server..."),
+            Self::S3Ready { addr } => write!(f, "S3 server ready at {}", addr),
+            Self::LaunchingPipeline => write!(f, "Launching pipeline..."),
+            Self::PipelineRunning { pid } => write!(f, "Pipeline running (pid {})", pid),
+        }
+    }
+}
+
+/// Error carrying the stage that was in progress when spawning failed
+#[derive(Debug, thiserror::Error)]
+#[error("Pipeline spawn failed at stage '{stage}': {message}")]
+pub struct SpawnError {
+    pub stage: &'static str,
+    pub message: String,
+}
+
+/// A child process started during spawn, which can be torn down on failure
+pub trait ChildProcess {
+    fn id(&self) -> Option<u32>;
+    fn kill(&mut self);
+}
+
+impl ChildProcess for Child {
+    fn id(&self) -> Option<u32> {
+        Child::id(self)
+    }
+
+    fn kill(&mut self) {
+        let _ = self.start_kill();
+    }
+}
+
+/// Starts the processes making up a running pipeline
+pub trait Spawner {
+    type Child: ChildProcess;
+
+    fn start_s3(&mut self, addr: &str) -> BinResult<Self::Child>;
+    async fn wait_s3_ready(&mut self, addr: &str) -> BinResult<()>;
+    fn launch_pipeline(&mut self, name: &str, s3_addr: &str) -> BinResult<Self::Child>;
+}
+
+/// Processes of a successfully spawned pipeline
+pub struct SpawnedPipeline<C> {
+    pub s3: C,
+    pub pipeline: C,
+}
+
+/// Reports a stage as a status line and, with `--events`, as a JSON line
+fn report(events: &EventEmitter, name: &str, stage: &SpawnStage) {
+    println!("{}", stage);
+    events.emit(Event::PipelineStage {
+        name: name.to_string(),
+        stage: stage.id().to_string(),
+        message: stage.to_string(),
+    });
+}
+
+/// Reports a failed stage and converts the error
+fn fail(
+    events: &EventEmitter,
+    name: &str,
+    stage: &SpawnStage,
+    error: Box<dyn std::error::Error>,
+) -> SpawnError {
+    let error = SpawnError {
+        stage: stage.id(),
+        message: error.to_string(),
+    };
+    events.emit(Event::PipelineFailed {
+        name: name.to_string(),
+        stage: error.stage.to_string(),
+        error: error.message.clone(),
+    });
+    error
+}
+
+/// Runs the spawn stages in order, killing already started processes if a later stage fails
+pub async fn spawn_pipeline<S: Spawner>(
+    spawner: &mut S,
+    name: &str,
+    s3_addr: &str,
+    events: &EventEmitter,
+) -> Result<SpawnedPipeline<S::Child>, SpawnError> {
+    let stage = SpawnStage::StartingS3;
+    report(events, name, &stage);
+    let mut s3 = spawner
+        .start_s3(s3_addr)
+        .map_err(|e| fail(events, name, &stage, e))?;
+
+    if let Err(e) = spawner.wait_s3_ready(s3_addr).await {
+        s3.kill();
+        return Err(fail(events, name, &stage, e));
+    }
+    report(
+        events,
+        name,
+        &SpawnStage::S3Ready {
+            addr: s3_addr.to_string(),
+        },
+    );
+
+    let stage = SpawnStage::LaunchingPipeline;
+    report(events, name, &stage);
+    let pipeline = match spawner.launch_pipeline(name, s3_addr) {
+        Ok(pipeline) => pipeline,
+        Err(e) => {
+            s3.kill();
+            return Err(fail(events, name, &stage, e));
+        }
+    };
+
+    report(
+        events,
+        name,
+        &SpawnStage::PipelineRunning {
+            pid: pipeline.id().unwrap_or_default(),
+        },
+    );
+
+    Ok(SpawnedPipeline { s3, pipeline })
+}
+
+/// Spawner running the installed `s3fs` and `agt` binaries
+///
+/// s3fs listens on a free local port and agt reaches it through `AWS_ENDPOINT_URL`.
+pub struct BinarySpawner {
+    bin_dir: PathBuf,
+    env: BTreeMap<String, String>,
+    detach: bool,
+}
+
+impl BinarySpawner {
+    pub fn new(bin_dir: &Path) -> Self {
+        Self {
+            bin_dir: bin_dir.to_path_buf(),
+            env: BTreeMap::new(),
+            detach: false,
+        }
+    }
+
+    /// Adds variables to the environment of both spawned processes
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Lets the processes outlive the CLI, detached from the terminal (`--detach`)
+    pub fn detached(mut self) -> Self {
+        self.detach = true;
+        self
+    }
+
+    /// Starts an installed binary with its output streamed, set up for attached or detached runs
+    fn spawn(
+        &self,
+        kind: BinaryKind,
+        args: &[&str],
+        configure: impl FnOnce(&mut Command),
+    ) -> BinResult<Child> {
+        spawn_binary_streaming_with_provider(kind.provider(), &self.bin_dir, args, |command| {
+            command.envs(&self.env).kill_on_drop(!self.detach);
+            configure(command);
+            if self.detach {
+                command
+                    .stdin(Stdio::null())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null());
+                // Keep Ctrl-C in the terminal from reaching the background processes
+                #[cfg(unix)]
+                command.process_group(0);
+            }
+        })
+    }
+}
+
+impl Spawner for BinarySpawner {
+    type Child = Child;
+
+    fn start_s3(&mut self, addr: &str) -> BinResult<Child> {
+        self.spawn(BinaryKind::S3fs, &["--addr", addr], |command| {
+            command.stdout(Stdio::null());
+        })
+    }
+
+    async fn wait_s3_ready(&mut self, addr: &str) -> BinResult<()> {
+        let started = Instant::now();
+        while started.elapsed() < S3_READY_TIMEOUT {
+            if tokio::net::TcpStream::connect(addr).await.is_ok() {
+                return Ok(());
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        Err(format!(
+            "S3 server did not accept connections on {} within {:?}",
+            addr, S3_READY_TIMEOUT
+        )
+        .into())
+    }
+
+    fn launch_pipeline(&mut self, name: &str, s3_addr: &str) -> BinResult<Child> {
+        self.spawn(BinaryKind::Agt, &["run", name], |command| {
+            command.env("AWS_ENDPOINT_URL", format!("http://{}", s3_addr));
+        })
+    }
+}
+
+/// Picks a free local port for the S3 server
+fn free_local_addr() -> BinResult<String> {
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    Ok(listener.local_addr()?.to_string())
+}
+
+/// Spawns the pipeline and waits for it to exit, stopping the S3 server afterwards
+///
+/// `env` is added to the environment of the spawned processes. With a
+/// `waiter` (`--wait`), the processes are stopped if the platform does not
+/// report the pipeline running in time. With `detach`, the processes are left
+/// running and their PIDs saved for `pipeline stop --local`; the two cannot
+/// be combined.
+pub(super) async fn handle_spawn(
+    config: &AppConfig,
+    name: &str,
+    env: BTreeMap<String, String>,
+    waiter: Option<&StatusWaiter>,
+    detach: bool,
+) -> BinResult<()> {
+    if detach && waiter.is_some() {
+        return Err("--wait cannot be used with --detach".into());
+    }
+    let pid_file = config.layout().pipeline_pid_file(name);
+    if detach
+        && let Some(running) = DetachedPipeline::load(&pid_file)?
+        && running.is_pipeline_running()
+    {
+        return Err(format!(
+            "Pipeline '{}' is already running in the background (pid {})",
+            name, running.pipeline_pid
+        )
+        .into());
+    }
+
+    let mut spawner = BinarySpawner::new(&config.layout().bin_dir()).with_env(env);
+    if detach {
+        spawner = spawner.detached();
+    }
+    let s3_addr = free_local_addr()?;
+
+    let SpawnedPipeline {
+        mut s3,
+        mut pipeline,
+    } = spawn_pipeline(&mut spawner, name, &s3_addr, &config.events).await?;
+
+    if detach {
+        // A child that already exited has no PID; never record 0, which `kill` treats as our group
+        let (Some(s3_pid), Some(pipeline_pid)) = (s3.id(), pipeline.id()) else {
+            ChildProcess::kill(&mut pipeline);
+            ChildProcess::kill(&mut s3);
+            return Err(format!(
+                "Pipeline '{}' exited right after starting, it was not detached",
+                name
+            )
+            .into());
+        };
+        let detached = DetachedPipeline {
+            name: name.to_string(),
+            s3_pid,
+            pipeline_pid,
+            s3_addr,
+            s3_identity: process_identity(s3_pid),
+            pipeline_identity: process_identity(pipeline_pid),
+        };
+        if let Err(e) = detached.save(&pid_file) {
+            ChildProcess::kill(&mut pipeline);
+            ChildProcess::kill(&mut s3);
+            return Err(e);
+        }
+        println!(
+            "Pipeline '{}' is running in the background, stop it with `pipeline stop {} --local`",
+            name, name
+        );
+        return Ok(());
+    }
+
+    let status = match waiter {
+        Some(waiter) => {
+            tokio::select! {
+                status = pipeline.wait() => status,
+                running = waiter.wait_until_running() => {
+                    if let Err(e) = running {
+                        ChildProcess::kill(&mut pipeline);
+                        ChildProcess::kill(&mut s3);
+                        return Err(e);
+                    }
+                    pipeline.wait().await
+                }
+            }
+        }
+        None => pipeline.wait().await,
+    };
+    ChildProcess::kill(&mut s3);
+
+    let status = status?;
+    if !status.success() {
+        return Err(format!("Pipeline '{}' exited with {}", name, status).into());
+    }
+    println!("Pipeline '{}' finished.", name);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[derive(Clone, Default)]
+    struct StubChild {
+        pid: u32,
+        killed: Arc<AtomicBool>,
+    }
+
+    impl ChildProcess for StubChild {
+        fn id(&self) -> Option<u32> {
+            Some(self.pid)
+        }
+
+        fn kill(&mut self) {
+            self.killed.store(true, Ordering::SeqCst);
+        }
+    }
+
+    #[derive(Default)]
+    struct StubSpawner {
+        fail_at: Option<&'static str>,
+        s3: StubChild,
+    }
+
+    impl Spawner for StubSpawner {
+        type Child = StubChild;
+
+        fn start_s3(&mut self, _addr: &str) -> BinResult<StubChild> {
+            if self.fail_at == Some("start_s3") {
+                return Err("s3fs missing".into());
+            }
+            Ok(self.s3.clone())
+        }
+
+        async fn wait_s3_ready(&mut self, _addr: &str) -> BinResult<()> {
+            if self.fail_at == Some("wait_s3_ready") {
+                return Err("timed out".into());
+            }
+            Ok(())
+        }
+
+        fn launch_pipeline(&mut self, _name: &str, _s3_addr: &str) -> BinResult<StubChild> {
+            if self.fail_at == Some("launch_pipeline") {
+                return Err("agt crashed".into());
+            }
+            Ok(StubChild {
+                pid: 4242,
+                ..Default::default()
+            })
+        }
+    }
+
+    fn stages(events: &EventEmitter) -> Vec<String> {
+        events
+            .recorded()
+            .into_iter()
+            .map(|event| match event {
+                Event::PipelineStage { stage, .. } => stage,
+                Event::PipelineFailed { stage, .. } => format!("failed:{}", stage),
+                other => panic!("unexpected event {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_spawn_stage_sequence() {
+        let events = EventEmitter::recording();
+        let mut spawner = StubSpawner::default();
+
+        let spawned = spawn_pipeline(&mut spawner, "demo", "127.0.0.1:9000", &events)
+            .await
+            .unwrap();
+
+        assert_eq!(spawned.pipeline.id(), Some(4242));
+        assert_eq!(
+            stages(&events),
+            [
+                "starting_s3",
+                "s3_ready",
+                "launching_pipeline",
+                "pipeline_running"
+            ]
+        );
+        assert!(matches!(
+            &events.recorded()[3],
+            Event::PipelineStage { message, .. } if message == "Pipeline running (pid 4242)"
+        ));
+        assert!(!spawner.s3.killed.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn test_launch_failure_stops_s3_server() {
+        let events = EventEmitter::recording();
+        let mut spawner = StubSpawner {
+            fail_at: Some("launch_pipeline"),
+            ..Default::default()
+        };
+
+        let err = spawn_pipeline(&mut spawner, "demo", "127.0.0.1:9000", &events)
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(err.stage, "launching_pipeline");
+        assert!(err.to_string().contains("agt crashed"));
+        assert!(spawner.s3.killed.load(Ordering::SeqCst));
+        assert_eq!(
+            stages(&events),
+            [
+                "starting_s3",
+                "s3_ready",
+                "launching_pipeline",
+                "failed:launching_pipeline"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_s3_not_ready_stops_s3_server() {
+        let events = EventEmitter::recording();
+        let mut spawner = StubSpawner {
+            fail_at: Some("wait_s3_ready"),
+            ..Default::default()
+        };
+
+        let err = spawn_pipeline(&mut spawner, "demo", "127.0.0.1:9000", &events)
+            .await
+            .err()
+            .unwrap();
+
+        assert_eq!(err.stage, "starting_s3");
+        assert!(spawner.s3.killed.load(Ordering::SeqCst));
+        assert_eq!(stages(&events), ["starting_s3", "failed:starting_s3"]);
+    }
+}
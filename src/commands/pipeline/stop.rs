@@ -0,0 +1,105 @@
+use std::error::Error;
+
+use reqwest::{Client, StatusCode};
+
+use crate::utils::{AppConfig, AuthTokens, CliError, api_post};
+
+/// Result of a stop request
+#[derive(Debug, PartialEq, Eq)]
+enum StopOutcome {
+    Stopped,
+    AlreadyStopped,
+    NotFound,
+}
+
+/// Asks the platform to stop a running pipeline
+pub(super) async fn handle_stop(
+    config: &AppConfig,
+    client: &Client,
+    tokens: &AuthTokens,
+    name: &str,
+) -> Result<(), Box<dyn Error>> {
+    let url = stop_url(config, name);
+
+    match stop_pipeline(client, &url, tokens.id_token()).await {
+        Ok(StopOutcome::Stopped) => println!("Pipeline '{}' stopped.", name),
+        Ok(StopOutcome::AlreadyStopped) => println!("Pipeline '{}' is already stopped.", name),
+        Ok(StopOutcome::NotFound) => println!("Pipeline '{}' not found.", name),
+        Err(e) if e.status() == Some(StatusCode::UNAUTHORIZED) => {
+            println!("Authentication failed. Please try to log in again.");
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    Ok(())
+}
+
+/// URL of the stop endpoint for a pipeline
+fn stop_url(config: &AppConfig, name: &str) -> String {
+    config.api_url(&format!("pipelines/{}/stop", urlencoding::encode(name)))
+}
+
+/// Sends the stop request, mapping "already stopped" and "not found" responses
+async fn stop_pipeline(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+) -> Result<StopOutcome, CliError> {
+    match api_post::<()>(client, url, id_token, None).await {
+        Ok(()) => Ok(StopOutcome::Stopped),
+        Err(e) if e.status() == Some(StatusCode::CONFLICT) => Ok(StopOutcome::AlreadyStopped),
+        Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => Ok(StopOutcome::NotFound),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn stop_with_response(status: u16) -> Result<StopOutcome, CliError> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/pipelines/my-pipeline/stop"))
+            .and(header("authorization", "Bearer id-token"))
+            .respond_with(ResponseTemplate::new(status))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        stop_pipeline(
+            &Client::new(),
+            &stop_url(&config, "my-pipeline"),
+            "id-token",
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_stop_pipeline_success() {
+        assert_eq!(stop_with_response(204).await.unwrap(), StopOutcome::Stopped);
+    }
+
+    #[tokio::test]
+    async fn test_stop_pipeline_already_stopped() {
+        assert_eq!(
+            stop_with_response(409).await.unwrap(),
+            StopOutcome::AlreadyStopped
+        );
+        assert_eq!(
+            stop_with_response(404).await.unwrap(),
+            StopOutcome::NotFound
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stop_pipeline_unauthorized() {
+        let err = stop_with_response(401).await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+    }
+}
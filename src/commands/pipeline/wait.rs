@@ -0,0 +1,172 @@
+use std::error::Error;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde::Deserialize;
+use tokio::time::Instant;
+
+use crate::utils::{AppConfig, AuthTokens, api_get};
+
+/// How long `pipeline spawn --wait` waits for the pipeline to run unless `--timeout` is given
+pub const DEFAULT_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Delay between two status requests
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// A pipeline as reported by the platform
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Pipeline {
+    pub name: String,
+    pub status: String,
+}
+
+impl Pipeline {
+    /// Whether the pipeline reached a running state
+    fn is_running(&self) -> bool {
+        matches!(self.status.to_lowercase().as_str(), "running" | "ready")
+    }
+
+    /// Whether the pipeline ended without running
+    fn has_failed(&self) -> bool {
+        matches!(
+            self.status.to_lowercase().as_str(),
+            "failed" | "error" | "stopped"
+        )
+    }
+}
+
+/// Polls a pipeline's status for `pipeline spawn --wait`
+pub struct StatusWaiter {
+    pub client: Client,
+    pub tokens: AuthTokens,
+    pub url: String,
+    pub interval: Duration,
+    pub timeout: Duration,
+}
+
+impl StatusWaiter {
+    pub fn new(config: &AppConfig, client: Client, tokens: AuthTokens, name: &str) -> Self {
+        Self {
+            client,
+            tokens,
+            url: config.api_url(&format!("pipelines/{}", urlencoding::encode(name))),
+            interval: POLL_INTERVAL,
+            timeout: DEFAULT_WAIT_TIMEOUT,
+        }
+    }
+
+    /// Polls until the pipeline is running, printing each status change
+    ///
+    /// Fails when the pipeline reaches a failed state or `timeout` elapses.
+    pub async fn wait_until_running(&self) -> Result<Pipeline, Box<dyn Error>> {
+        let deadline = Instant::now() + self.timeout;
+        let mut last_status = None;
+
+        loop {
+            let pipeline: Pipeline =
+                api_get(&self.client, &self.url, self.tokens.id_token()).await?;
+            if last_status.as_ref() != Some(&pipeline.status) {
+                println!("Pipeline status: {}", pipeline.status);
+                last_status = Some(pipeline.status.clone());
+            }
+
+            if pipeline.is_running() {
+                return Ok(pipeline);
+            }
+            if pipeline.has_failed() {
+                return Err(format!(
+                    "Pipeline '{}' ended in state '{}'",
+                    pipeline.name, pipeline.status
+                )
+                .into());
+            }
+            if Instant::now() + self.interval > deadline {
+                return Err(format!(
+                    "Pipeline '{}' was not running after {}s (last status: {})",
+                    pipeline.name,
+                    self.timeout.as_secs(),
+                    pipeline.status
+                )
+                .into());
+            }
+            tokio::time::sleep(self.interval).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn tokens() -> AuthTokens {
+        serde_json::from_value(json!({ "access_token": "a", "id_token": "id" })).unwrap()
+    }
+
+    async fn waiter(server: &MockServer, timeout: Duration) -> StatusWaiter {
+        let config = AppConfig::new("/tmp/.agnostic".into()).with_api_base_url(&server.uri());
+        StatusWaiter {
+            interval: Duration::from_millis(10),
+            timeout,
+            ..StatusWaiter::new(&config, Client::new(), tokens(), "demo")
+        }
+    }
+
+    fn status(value: &str) -> ResponseTemplate {
+        ResponseTemplate::new(200).set_body_json(json!({ "name": "demo", "status": value }))
+    }
+
+    #[tokio::test]
+    async fn test_wait_until_running_after_pending() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/pipelines/demo"))
+            .respond_with(status("pending"))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/pipelines/demo"))
+            .respond_with(status("running"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let pipeline = waiter(&server, Duration::from_secs(5))
+            .await
+            .wait_until_running()
+            .await
+            .unwrap();
+        assert_eq!(pipeline.status, "running");
+    }
+
+    #[tokio::test]
+    async fn test_wait_fails_on_failed_state_and_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(status("failed"))
+            .mount(&server)
+            .await;
+        let err = waiter(&server, Duration::from_secs(5))
+            .await
+            .wait_until_running()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ended in state 'failed'"));
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(status("pending"))
+            .mount(&server)
+            .await;
+        let err = waiter(&server, Duration::from_millis(50))
+            .await
+            .wait_until_running()
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("was not running"));
+    }
+}
@@ -0,0 +1,228 @@
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::process::Stdio;
+
+use reqwest::Client;
+
+use crate::utils::{
+    fs::{ExtractFilter, remove_path},
+    net::dl_unzip_stripping,
+};
+
+/// A GitHub repository and ref, derived from a template's archive URL
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitSource {
+    pub repo: String,
+    pub reference: String,
+}
+
+impl GitSource {
+    /// Parses `https://github.com/<owner>/<repo>/archive/[refs/heads/|refs/tags/]<ref>.zip`
+    pub fn from_archive_url(url: &str) -> Option<Self> {
+        let path = url.strip_prefix("https://github.com/")?;
+        let (repo_path, archive) = path.split_once("/archive/")?;
+        let reference = archive.strip_suffix(".zip")?;
+        let reference = reference
+            .strip_prefix("refs/heads/")
+            .or_else(|| reference.strip_prefix("refs/tags/"))
+            .unwrap_or(reference);
+        if repo_path.split('/').count() != 2 || reference.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            repo: format!("https://github.com/{}.git", repo_path),
+            reference: reference.to_string(),
+        })
+    }
+}
+
+/// Picks the repository to clone when the zip download is skipped or failed
+///
+/// Fails with the reason when the template is not a GitHub archive or `git`
+/// is not installed.
+pub fn git_fallback(url: &str, git_available: bool) -> Result<GitSource, String> {
+    let source = GitSource::from_archive_url(url)
+        .ok_or_else(|| format!("{} is not a GitHub archive URL", url))?;
+    if !git_available {
+        return Err("git was not found, install it or check your PATH".to_string());
+    }
+    Ok(source)
+}
+
+/// Whether a working `git` executable is on `PATH`
+fn is_git_available() -> bool {
+    std::process::Command::new("git")
+        .arg("--version")
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|status| status.success())
+}
+
+/// Downloads the template archive into `dest`, falling back to `git clone`
+///
+/// With `use_git`, the zip download is skipped and the template is cloned
+/// directly. A clone matches an archive with its root folder stripped, so
+/// git is only used when `strip_components` is unset or 1.
+pub async fn fetch_template(
+    client: &Client,
+    url: &str,
+    dest: &Path,
+    strip_components: Option<usize>,
+    filter: &ExtractFilter,
+    use_git: bool,
+) -> Result<(), Box<dyn Error>> {
+    let git_source = || {
+        if strip_components.is_some_and(|count| count != 1) {
+            return Err("--strip-components only applies to zip downloads".to_string());
+        }
+        git_fallback(url, is_git_available())
+    };
+    if use_git {
+        let source = git_source()?;
+        return clone_template(&source, dest, filter).await;
+    }
+
+    let Err(zip_error) = dl_unzip_stripping(client, url, dest, strip_components, filter).await
+    else {
+        return Ok(());
+    };
+    match git_source() {
+        Ok(source) => {
+            eprintln!(
+                "Template download failed ({}), falling back to git clone of {}",
+                zip_error, source.repo
+            );
+            if dest.exists() {
+                remove_path(dest)?;
+            }
+            clone_template(&source, dest, filter).await
+        }
+        Err(reason) => Err(format!("{} (no git fallback: {})", zip_error, reason).into()),
+    }
+}
+
+/// Shallow-clones `source` into `dest` and drops the `.git` directory, like an extracted archive
+async fn clone_template(
+    source: &GitSource,
+    dest: &Path,
+    filter: &ExtractFilter,
+) -> Result<(), Box<dyn Error>> {
+    let status = tokio::process::Command::new("git")
+        .args(["clone", "--quiet", "--depth", "1", "--branch"])
+        .arg(&source.reference)
+        .arg(&source.repo)
+        .arg(dest)
+        .stdin(Stdio::null())
+        .status()
+        .await
+        .map_err(|e| format!("Failed to run git: {}", e))?;
+    if !status.success() {
+        return Err(format!(
+            "git clone of {} at '{}' failed ({})",
+            source.repo, source.reference, status
+        )
+        .into());
+    }
+
+    remove_path(dest.join(".git"))?;
+    prune_unmatched(dest, dest, filter)?;
+    Ok(())
+}
+
+/// Removes the files `filter` would not have extracted, and directories left empty
+///
+/// Returns whether `dir` ended up empty.
+fn prune_unmatched(
+    root: &Path,
+    dir: &Path,
+    filter: &ExtractFilter,
+) -> Result<bool, Box<dyn Error>> {
+    let mut empty = true;
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            if prune_unmatched(root, &path, filter)? {
+                fs::remove_dir(&path)?;
+            } else {
+                empty = false;
+            }
+        } else if filter.matches(path.strip_prefix(root)?) {
+            empty = false;
+        } else {
+            fs::remove_file(&path)?;
+        }
+    }
+    Ok(empty)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_git_source_from_archive_url() {
+        assert_eq!(
+            GitSource::from_archive_url(
+                "https://github.com/agnosticeng/init/archive/refs/heads/main.zip"
+            ),
+            Some(GitSource {
+                repo: "https://github.com/agnosticeng/init.git".to_string(),
+                reference: "main".to_string(),
+            })
+        );
+        assert_eq!(
+            GitSource::from_archive_url("https://github.com/a/b/archive/refs/tags/v1.2.zip")
+                .unwrap()
+                .reference,
+            "v1.2"
+        );
+        assert_eq!(
+            GitSource::from_archive_url("https://github.com/a/b/archive/feature/x.zip")
+                .unwrap()
+                .reference,
+            "feature/x"
+        );
+        assert_eq!(
+            GitSource::from_archive_url("https://example.com/a/b/archive/main.zip"),
+            None
+        );
+        assert_eq!(
+            GitSource::from_archive_url("https://github.com/a/archive/main.zip"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_git_fallback_selection() {
+        let url = "https://github.com/agnosticeng/init/archive/refs/heads/main.zip";
+        assert_eq!(git_fallback(url, true).unwrap().reference, "main");
+
+        let err = git_fallback(url, false).unwrap_err();
+        assert!(err.contains("git was not found"));
+
+        let err = git_fallback("https://mirror.example.com/init.zip", true).unwrap_err();
+        assert!(err.contains("not a GitHub archive URL"));
+    }
+
+    #[test]
+    fn test_prune_unmatched_keeps_filtered_files() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        fs::create_dir_all(root.join("queries/daily")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("README.md"), "readme").unwrap();
+        fs::write(root.join("queries/daily/a.sql"), "select 1").unwrap();
+        fs::write(root.join("docs/guide.md"), "guide").unwrap();
+
+        let filter = ExtractFilter::new(&["queries/**".to_string()], &[]).unwrap();
+        prune_unmatched(root, root, &filter).unwrap();
+
+        assert!(root.join("queries/daily/a.sql").exists());
+        assert!(!root.join("README.md").exists());
+        assert!(!root.join("docs").exists());
+    }
+}
@@ -0,0 +1,230 @@
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+use crate::commands::user::authenticate;
+use crate::utils::{AppConfig, CliError, api_get};
+
+/// A project as registered on the platform
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct RemoteProject {
+    pub name: String,
+    #[serde(default)]
+    pub id: Option<String>,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+/// The project directory initialized by `project init`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalProject {
+    pub name: String,
+    pub path: PathBuf,
+    pub exists: bool,
+}
+
+impl LocalProject {
+    /// The project at `path`, named after its directory
+    pub fn at(path: &Path) -> Self {
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into_owned(),
+        );
+        Self {
+            name,
+            path: path.to_path_buf(),
+            exists: path.is_dir(),
+        }
+    }
+}
+
+/// A difference between the local project and the platform's view of it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Drift {
+    /// The project is not registered on the platform
+    NotRegistered,
+    /// The platform knows the project but there is no local directory
+    MissingLocally,
+    /// The local directory and the registered project have different names
+    NameMismatch { local: String, remote: String },
+}
+
+impl fmt::Display for Drift {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotRegistered => write!(f, "not registered on the platform"),
+            Self::MissingLocally => write!(f, "registered on the platform but not found locally"),
+            Self::NameMismatch { local, remote } => write!(
+                f,
+                "local name '{}' differs from remote name '{}'",
+                local, remote
+            ),
+        }
+    }
+}
+
+/// Lists how the local project and the registered one (if any) disagree
+pub fn detect_drift(local: &LocalProject, remote: Option<&RemoteProject>) -> Vec<Drift> {
+    let Some(remote) = remote else {
+        return vec![Drift::NotRegistered];
+    };
+
+    let mut drift = Vec::new();
+    if !local.exists {
+        drift.push(Drift::MissingLocally);
+    } else if local.name != remote.name {
+        drift.push(Drift::NameMismatch {
+            local: local.name.clone(),
+            remote: remote.name.clone(),
+        });
+    }
+    drift
+}
+
+/// Fetches the registered project, `None` when the platform answers 404
+async fn fetch_remote(
+    config: &AppConfig,
+    client: &Client,
+    id_token: &str,
+    name: &str,
+) -> Result<Option<RemoteProject>, CliError> {
+    let url = config.api_url(&format!("projects/{}", urlencoding::encode(name)));
+    match api_get(client, &url, id_token).await {
+        Ok(project) => Ok(Some(project)),
+        Err(e) if e.status() == Some(StatusCode::NOT_FOUND) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Prints the local project and, with `remote`, its registration and any drift
+pub async fn handle_info(
+    config: &AppConfig,
+    name: &str,
+    remote: bool,
+) -> Result<(), Box<dyn Error>> {
+    let local = LocalProject::at(Path::new(name));
+
+    println!("Project: {}", local.name);
+    println!(
+        "   Local: {} ({})",
+        local.path.display(),
+        if local.exists { "present" } else { "missing" }
+    );
+    if !remote {
+        return Ok(());
+    }
+
+    let client = config.http_client()?;
+    let Some(tokens) = authenticate(config, &client).await else {
+        return Ok(());
+    };
+    let remote = fetch_remote(config, &client, tokens.id_token(), &local.name).await?;
+    match &remote {
+        Some(project) => println!(
+            "   Remote: {}{}",
+            project.name,
+            project
+                .id
+                .as_ref()
+                .map(|id| format!(" (id {})", id))
+                .unwrap_or_default()
+        ),
+        None => println!("   Remote: not registered"),
+    }
+
+    let drift = detect_drift(&local, remote.as_ref());
+    if drift.is_empty() {
+        println!("   In sync with the platform");
+    }
+    for difference in drift {
+        println!("   Drift: {}", difference);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::AuthTokens;
+    use serde_json::json;
+    use tempfile::TempDir;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    const SAMPLE_REMOTE: &str = r#"{
+        "name": "demo",
+        "id": "prj_123",
+        "created_at": "2025-01-01T00:00:00Z",
+        "owner": "ada"
+    }"#;
+
+    #[test]
+    fn test_detect_drift() {
+        let temp_dir = TempDir::new().unwrap();
+        let remote: RemoteProject = serde_json::from_str(SAMPLE_REMOTE).unwrap();
+        assert_eq!(remote.id.as_deref(), Some("prj_123"));
+
+        let local = LocalProject::at(&temp_dir.path().join("demo"));
+        assert!(!local.exists);
+        assert_eq!(local.name, "demo");
+        assert_eq!(
+            detect_drift(&local, Some(&remote)),
+            vec![Drift::MissingLocally]
+        );
+        assert_eq!(detect_drift(&local, None), vec![Drift::NotRegistered]);
+
+        std::fs::create_dir(temp_dir.path().join("demo")).unwrap();
+        let local = LocalProject::at(&temp_dir.path().join("demo"));
+        assert!(detect_drift(&local, Some(&remote)).is_empty());
+
+        let renamed = RemoteProject {
+            name: "demo-v2".to_string(),
+            ..remote
+        };
+        assert_eq!(
+            detect_drift(&local, Some(&renamed)),
+            vec![Drift::NameMismatch {
+                local: "demo".to_string(),
+                remote: "demo-v2".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetch_remote_handles_not_found() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/projects/demo"))
+            .and(header("authorization", "Bearer id"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_raw(SAMPLE_REMOTE, "application/json"),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/api/projects/unknown"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let config = AppConfig::new("/tmp/.agnostic".into()).with_api_base_url(&server.uri());
+        let tokens: AuthTokens =
+            serde_json::from_value(json!({ "access_token": "a", "id_token": "id" })).unwrap();
+        let client = Client::new();
+
+        let project = fetch_remote(&config, &client, tokens.id_token(), "demo")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(project.name, "demo");
+        assert!(
+            fetch_remote(&config, &client, tokens.id_token(), "unknown")
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+}
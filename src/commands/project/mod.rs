@@ -1,23 +1,117 @@
-use crate::utils::net::dl_unzip;
+mod git;
+mod info;
+mod name;
+mod substitute;
+mod template;
+
+use crate::utils::{AppConfig, confirm, fs::ExtractFilter};
 use clap::Subcommand;
+use reqwest::Client;
+
+#[allow(unused_imports)]
+pub use template::{DEFAULT_TEMPLATE_URL, TEMPLATE_INDEX_URL, Template, TemplateIndex};
 
 #[derive(Subcommand, Debug)]
 pub enum ProjectAction {
     /// Initialize a new project
     Init {
         /// Name of the project
-        name: String,
+        #[arg(required_unless_present = "list_templates")]
+        name: Option<String>,
+        /// Starter template to initialize from (see `--list-templates`)
+        #[arg(long)]
+        template: Option<String>,
+        /// List available starter templates and exit
+        #[arg(long)]
+        list_templates: bool,
+        /// Only extract template files matching this glob (e.g. 'queries/**'), repeatable
+        #[arg(long, value_name = "GLOB")]
+        include: Vec<String>,
+        /// Skip template files matching this glob, repeatable
+        #[arg(long, value_name = "GLOB")]
+        exclude: Vec<String>,
+        /// Template variable substituted as {{KEY}} when the template enables it, repeatable
+        #[arg(long = "set", value_name = "KEY=VALUE")]
+        vars: Vec<substitute::TemplateVar>,
+        /// Clone the template with `git` instead of downloading its zip archive
+        ///
+        /// Without it, `git clone` is still tried when the zip download fails.
+        #[arg(long)]
+        use_git: bool,
+        /// Leading path components removed from template archive entries (0 keeps them all)
+        ///
+        /// Without it, the archive's root folder is stripped when every entry is nested in one.
+        #[arg(long, value_name = "N")]
+        strip_components: Option<usize>,
     },
     /// Get information about a project
     Info {
         /// Name of the project
         name: String,
+        /// Also fetch the project from the platform and report differences with the local one
+        #[arg(long)]
+        remote: bool,
     },
 }
 
-pub async fn handle_project_command(action: ProjectAction) {
+impl ProjectAction {
+    /// Examples shown after `project --help`
+    pub fn help_examples() -> String {
+        format!(
+            "Examples:\n  \
+             ag project init my-project                 # from {}\n  \
+             ag project init --list-templates\n  \
+             ag project init my-project --set author=ada\n  \
+             ag project init my-project --use-git       # clone instead of downloading the zip\n  \
+             ag project info my-project --remote        # compare with the platform",
+            template::DEFAULT_TEMPLATE_URL
+        )
+    }
+}
+
+pub async fn handle_project_command(action: ProjectAction, config: &AppConfig) {
+    let client = match config.download_http_client() {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("Error creating HTTP client: {}", e);
+            return;
+        }
+    };
+
     match action {
-        ProjectAction::Init { name } => {
+        ProjectAction::Init {
+            name,
+            template,
+            list_templates,
+            include,
+            exclude,
+            vars,
+            use_git,
+            strip_components,
+        } => {
+            if list_templates {
+                match TemplateIndex::fetch(&client, TEMPLATE_INDEX_URL).await {
+                    Ok(index) => index.print(),
+                    Err(e) => eprintln!("Error fetching template index: {}", e),
+                }
+                return;
+            }
+
+            let Some(name) = name else {
+                return;
+            };
+            let Some(name) = checked_project_name(config, name) else {
+                return;
+            };
+
+            let filter = match ExtractFilter::new(&include, &exclude) {
+                Ok(filter) => filter,
+                Err(e) => {
+                    eprintln!("Error: invalid --include/--exclude pattern: {}", e);
+                    return;
+                }
+            };
+
             println!("Initializing project: {}", name);
 
             if std::path::Path::new(&name).exists() {
@@ -25,19 +119,67 @@ pub async fn handle_project_command(action: ProjectAction) {
                 return;
             }
 
-            match dl_unzip(
-                "https://github.com/agnosticeng/init/archive/refs/heads/main.zip",
-                &name,
-            )
-            .await
-            {
+            let template_url = match template {
+                Some(template) => match resolve_template_url(&client, &template).await {
+                    Ok(url) => url,
+                    Err(e) => {
+                        eprintln!("Error initializing project '{}': {}", name, e);
+                        return;
+                    }
+                },
+                None => DEFAULT_TEMPLATE_URL.to_string(),
+            };
+
+            let dest = std::path::Path::new(&name);
+            let fetched = git::fetch_template(
+                &client,
+                &template_url,
+                dest,
+                strip_components,
+                &filter,
+                use_git,
+            );
+            let result = match fetched.await {
+                Ok(()) => substitute::apply_template_variables(dest, &vars),
+                Err(e) => Err(e),
+            };
+            match result {
                 Ok(()) => println!("Successfully initialized project '{}'", name),
                 Err(e) => eprintln!("Error initializing project '{}': {}", name, e),
             }
         }
-        ProjectAction::Info { name } => {
-            println!("Getting info for project: {}", name);
-            // TODO: Implement project info retrieval logic
+        ProjectAction::Info { name, remote } => {
+            if let Err(e) = info::handle_info(config, &name, remote).await {
+                eprintln!("Error getting info for project '{}': {}", name, e);
+            }
+        }
+    }
+}
+
+/// Validates the name given to `project init`, offering a slug of an invalid one
+fn checked_project_name(config: &AppConfig, name: String) -> Option<String> {
+    let Err(error) = name::validate_project_name(&name) else {
+        return Some(name);
+    };
+    eprintln!("Error: {}", error);
+
+    let slug = name::slugify(&name)?;
+    match confirm(config, &format!("Use '{}' instead?", slug)) {
+        Ok(true) => Some(slug),
+        Ok(false) => None,
+        Err(e) => {
+            eprintln!("Error reading confirmation: {}", e);
+            None
         }
     }
 }
+
+/// Resolves a template name against the published template index
+async fn resolve_template_url(
+    client: &Client,
+    name: &str,
+) -> Result<String, Box<dyn std::error::Error>> {
+    let index = TemplateIndex::fetch(client, TEMPLATE_INDEX_URL).await?;
+    let url = index.resolve_url(name)?;
+    Ok(url.to_string())
+}
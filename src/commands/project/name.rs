@@ -0,0 +1,110 @@
+/// Longest accepted project name
+pub const MAX_NAME_LEN: usize = 64;
+
+/// What a project name may contain, appended to every validation error
+const NAME_RULES: &str = "project names may only contain letters, digits, '-' and '_', \
+     must start with a letter or digit and be at most 64 characters long";
+
+/// Checks that `name` is safe to use as a directory in the current directory
+///
+/// Fails with a message giving the reason and the naming rules.
+pub fn validate_project_name(name: &str) -> Result<(), String> {
+    let reason = if name.is_empty() {
+        Some("it is empty".to_string())
+    } else if name.contains(['/', '\\']) {
+        Some("it contains a path separator".to_string())
+    } else if name.contains("..") {
+        Some("it contains '..'".to_string())
+    } else if let Some(c) = name
+        .chars()
+        .find(|c| !c.is_ascii_alphanumeric() && !matches!(c, '-' | '_'))
+    {
+        Some(format!("'{}' is not allowed", c))
+    } else if !name.starts_with(|c: char| c.is_ascii_alphanumeric()) {
+        Some("it must start with a letter or digit".to_string())
+    } else if name.len() > MAX_NAME_LEN {
+        Some(format!("it is {} characters long", name.len()))
+    } else {
+        None
+    };
+
+    match reason {
+        Some(reason) => Err(format!(
+            "Invalid project name '{}': {}; {}",
+            name, reason, NAME_RULES
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A valid name derived from `name`, e.g. `My Project!` becomes `My-Project`
+///
+/// Runs of disallowed characters become a single `-`. `None` when nothing
+/// usable is left.
+pub fn slugify(name: &str) -> Option<String> {
+    let mut slug = String::new();
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() || c == '_' {
+            slug.push(c);
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    let slug = slug.trim_start_matches('_');
+    let slug = slug[..slug.len().min(MAX_NAME_LEN)].trim_end_matches(['-', '_']);
+
+    validate_project_name(slug)
+        .is_ok()
+        .then(|| slug.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_project_names() {
+        for name in ["demo", "my-project", "data_2024", "A1", &"a".repeat(64)] {
+            assert_eq!(validate_project_name(name), Ok(()), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_names_with_paths_are_rejected() {
+        for name in ["team/demo", "/tmp/demo", "..\\demo", "demo/"] {
+            let err = validate_project_name(name).unwrap_err();
+            assert!(err.contains("path separator"), "{}", err);
+            assert!(err.contains("may only contain letters, digits"));
+        }
+
+        for name in ["..", "demo..old"] {
+            let err = validate_project_name(name).unwrap_err();
+            assert!(err.contains("'..'"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_other_invalid_names() {
+        assert!(validate_project_name("").unwrap_err().contains("empty"));
+        assert!(
+            validate_project_name("my project")
+                .unwrap_err()
+                .contains("' '")
+        );
+        assert!(
+            validate_project_name("-demo")
+                .unwrap_err()
+                .contains("start with")
+        );
+        assert!(validate_project_name(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("My Project!").as_deref(), Some("My-Project"));
+        assert_eq!(slugify("../evil").as_deref(), Some("evil"));
+        assert_eq!(slugify("_private data").as_deref(), Some("private-data"));
+        assert_eq!(slugify(&"x".repeat(80)).map(|s| s.len()), Some(64));
+        assert_eq!(slugify("../.."), None);
+    }
+}
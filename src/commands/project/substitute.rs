@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use serde::Deserialize;
+
+/// Manifest at the root of a template enabling optional init-time behaviour
+pub const TEMPLATE_MANIFEST: &str = "template.toml";
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplateManifest {
+    #[serde(default)]
+    template: TemplateSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TemplateSection {
+    /// Replace `{{variable}}` placeholders in text files after extraction
+    #[serde(default)]
+    substitute: bool,
+}
+
+/// A `--set key=value` template variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl FromStr for TemplateVar {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Expected KEY=VALUE, got '{}'", s))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!(
+                "Invalid variable name '{}': use letters, digits and underscores",
+                key
+            ));
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+/// Whether the template extracted in `project_dir` opts into variable substitution
+pub fn substitution_enabled(project_dir: &Path) -> Result<bool, Box<dyn Error>> {
+    let manifest_path = project_dir.join(TEMPLATE_MANIFEST);
+    if !manifest_path.is_file() {
+        return Ok(false);
+    }
+
+    let manifest: TemplateManifest = toml::from_str(&fs::read_to_string(&manifest_path)?)
+        .map_err(|e| format!("Invalid {}: {}", manifest_path.display(), e))?;
+    Ok(manifest.template.substitute)
+}
+
+/// Substitutes template variables in a freshly initialized project, if its template opts in
+///
+/// `project_name` is always defined (the project directory name); `--set`
+/// variables are added on top and may override it.
+pub fn apply_template_variables(
+    project_dir: &Path,
+    extra: &[TemplateVar],
+) -> Result<(), Box<dyn Error>> {
+    if !substitution_enabled(project_dir)? {
+        return Ok(());
+    }
+
+    let project_name = project_dir
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut vars = HashMap::from([("project_name".to_string(), project_name)]);
+    for var in extra {
+        vars.insert(var.key.clone(), var.value.clone());
+    }
+
+    let rewritten = substitute_variables(project_dir, &vars)?;
+    println!("Substituted template variables in {} files", rewritten);
+    Ok(())
+}
+
+/// Replaces `{{key}}` placeholders in every text file under `dir`
+///
+/// Binary files (invalid UTF-8 or containing NUL bytes) and symlinks are left
+/// untouched, as are placeholders naming unknown variables. Returns the number
+/// of files rewritten.
+pub fn substitute_variables(
+    dir: &Path,
+    vars: &HashMap<String, String>,
+) -> Result<usize, Box<dyn Error>> {
+    let mut rewritten = 0;
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+
+        if file_type.is_dir() {
+            rewritten += substitute_variables(&path, vars)?;
+        } else if file_type.is_file() {
+            let bytes = fs::read(&path)?;
+            let Ok(text) = String::from_utf8(bytes) else {
+                continue;
+            };
+            if text.contains('\0') {
+                continue;
+            }
+
+            let replaced = replace_placeholders(&text, vars);
+            if replaced != text {
+                fs::write(&path, replaced)?;
+                rewritten += 1;
+            }
+        }
+    }
+
+    Ok(rewritten)
+}
+
+/// Replaces known `{{ key }}` placeholders in a string
+fn replace_placeholders(text: &str, vars: &HashMap<String, String>) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after_open = &rest[start + 2..];
+
+        match after_open.find("}}") {
+            Some(end) => match vars.get(after_open[..end].trim()) {
+                Some(value) => {
+                    output.push_str(value);
+                    rest = &after_open[end + 2..];
+                }
+                None => {
+                    output.push_str("{{");
+                    rest = after_open;
+                }
+            },
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fs::{ExtractFilter, extract_zip_with_root_stripping};
+    use std::io::Write;
+    use tempfile::TempDir;
+
+    fn vars() -> HashMap<String, String> {
+        HashMap::from([
+            ("project_name".to_string(), "demo".to_string()),
+            ("author".to_string(), "ada".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_replace_placeholders() {
+        assert_eq!(
+            replace_placeholders("name = \"{{project_name}}\" by {{ author }}", &vars()),
+            "name = \"demo\" by ada"
+        );
+        assert_eq!(
+            replace_placeholders("{{unknown}} {{project_name}} {{", &vars()),
+            "{{unknown}} demo {{"
+        );
+    }
+
+    #[test]
+    fn test_parse_template_var() {
+        let var: TemplateVar = "team_slug=acme=corp".parse().unwrap();
+        assert_eq!(var.key, "team_slug");
+        assert_eq!(var.value, "acme=corp");
+        assert!("novalue".parse::<TemplateVar>().is_err());
+        assert!("bad key=x".parse::<TemplateVar>().is_err());
+    }
+
+    #[test]
+    fn test_substitute_extracted_template() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("template.zip");
+
+        let mut writer = zip::ZipWriter::new(fs::File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        let files: [(&str, &[u8]); 4] = [
+            (
+                "init-main/template.toml",
+                b"[template]\nsubstitute = true\n",
+            ),
+            ("init-main/README.md", b"# {{project_name}}\n"),
+            ("init-main/queries/owner.sql", b"-- author: {{ author }}\n"),
+            ("init-main/logo.bin", b"\0{{project_name}}"),
+        ];
+        for (name, content) in files {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(content).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let project = temp_dir.path().join("demo");
+        extract_zip_with_root_stripping(&zip_path, &project, &ExtractFilter::default()).unwrap();
+
+        assert!(substitution_enabled(&project).unwrap());
+        assert_eq!(substitute_variables(&project, &vars()).unwrap(), 2);
+
+        assert_eq!(
+            fs::read_to_string(project.join("README.md")).unwrap(),
+            "# demo\n"
+        );
+        assert_eq!(
+            fs::read_to_string(project.join("queries/owner.sql")).unwrap(),
+            "-- author: ada\n"
+        );
+        assert_eq!(
+            fs::read(project.join("logo.bin")).unwrap(),
+            b"\0{{project_name}}"
+        );
+    }
+
+    #[test]
+    fn test_substitution_is_opt_in() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(!substitution_enabled(temp_dir.path()).unwrap());
+
+        fs::write(temp_dir.path().join(TEMPLATE_MANIFEST), "[template]\n").unwrap();
+        assert!(!substitution_enabled(temp_dir.path()).unwrap());
+    }
+}
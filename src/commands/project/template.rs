@@ -0,0 +1,151 @@
+use std::error::Error;
+
+use reqwest::Client;
+use serde::Deserialize;
+
+/// Starter template used when no `--template` is given
+pub const DEFAULT_TEMPLATE_URL: &str =
+    "https://github.com/agnosticeng/init/archive/refs/heads/main.zip";
+
+/// Curated index of starter templates published in the `agnosticeng/init` repository
+pub const TEMPLATE_INDEX_URL: &str =
+    "https://raw.githubusercontent.com/agnosticeng/init/main/templates.json";
+
+/// A starter template entry from the template index
+#[derive(Debug, Clone, Deserialize)]
+pub struct Template {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub url: String,
+    #[serde(default)]
+    pub default: bool,
+}
+
+/// Index of available starter templates
+#[derive(Debug, Deserialize)]
+pub struct TemplateIndex {
+    pub templates: Vec<Template>,
+}
+
+impl TemplateIndex {
+    /// Fetches and parses the template index from the given URL
+    pub async fn fetch(client: &Client, url: &str) -> Result<Self, Box<dyn Error>> {
+        let response = client.get(url).send().await?;
+
+        if !response.status().is_success() {
+            return Err(
+                format!("Failed to fetch template index: HTTP {}", response.status()).into(),
+            );
+        }
+
+        let index = response.json().await?;
+        Ok(index)
+    }
+
+    /// Looks up a template by name (case-insensitive)
+    pub fn find(&self, name: &str) -> Option<&Template> {
+        self.templates
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Resolves a template name to its archive URL
+    pub fn resolve_url(&self, name: &str) -> Result<&str, String> {
+        self.find(name).map(|t| t.url.as_str()).ok_or_else(|| {
+            let names: Vec<&str> = self.templates.iter().map(|t| t.name.as_str()).collect();
+            format!(
+                "Unknown template '{}'. Available templates: {}",
+                name,
+                names.join(", ")
+            )
+        })
+    }
+
+    /// Prints the templates, marking the default one
+    pub fn print(&self) {
+        println!("Available Templates");
+        println!("===================");
+        println!();
+
+        if self.templates.is_empty() {
+            println!("No templates available.");
+            return;
+        }
+
+        for template in &self.templates {
+            let marker = if template.default { " (default)" } else { "" };
+            println!("  {}{}", template.name, marker);
+            if !template.description.is_empty() {
+                println!("    {}", template.description);
+            }
+            println!("    URL: {}", template.url);
+            println!();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_INDEX: &str = r#"{
+        "templates": [
+            {
+                "name": "default",
+                "description": "Minimal starter project",
+                "url": "https://github.com/agnosticeng/init/archive/refs/heads/main.zip",
+                "default": true
+            },
+            {
+                "name": "evm-indexer",
+                "description": "EVM chain indexing pipeline",
+                "url": "https://github.com/agnosticeng/init/archive/refs/heads/evm-indexer.zip"
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_template_index() {
+        let index: TemplateIndex = serde_json::from_str(SAMPLE_INDEX).unwrap();
+        assert_eq!(index.templates.len(), 2);
+        assert!(index.templates[0].default);
+        assert!(!index.templates[1].default);
+        assert_eq!(
+            index.templates[1].description,
+            "EVM chain indexing pipeline"
+        );
+    }
+
+    #[test]
+    fn test_parse_template_index_with_unknown_fields() {
+        let index: TemplateIndex = serde_json::from_str(
+            r#"{
+                "version": 2,
+                "templates": [
+                    {"name": "default", "url": "https://example.com/a.zip", "tags": ["starter"]}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(index.templates.len(), 1);
+        assert_eq!(index.templates[0].name, "default");
+    }
+
+    #[test]
+    fn test_resolve_template_url() {
+        let index: TemplateIndex = serde_json::from_str(SAMPLE_INDEX).unwrap();
+
+        let url = index.resolve_url("evm-indexer").unwrap();
+        assert_eq!(
+            url,
+            "https://github.com/agnosticeng/init/archive/refs/heads/evm-indexer.zip"
+        );
+
+        let url = index.resolve_url("EVM-Indexer").unwrap();
+        assert!(url.ends_with("evm-indexer.zip"));
+
+        let err = index.resolve_url("unknown").unwrap_err();
+        assert!(err.contains("default, evm-indexer"));
+    }
+}
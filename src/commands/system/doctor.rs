@@ -0,0 +1,351 @@
+use std::path::{Path, PathBuf};
+
+use crate::utils::{
+    AppConfig, AuthTokens, BinResult, BinaryInfoProvider, InstallOptions,
+    app::auth::AuthTokenError,
+    bin::manager::{
+        DEFAULT_BINARY_MODE, VerifyOutcome, get_provider_binary_path, install_binary,
+        is_executable, set_binary_mode, verify_binary,
+    },
+    fs::{check_dir_path, ensure_dir_exists, remove_path_guarded},
+    registry,
+};
+
+use super::{CONNECTIVITY_TIMEOUT, Connectivity, check_connectivity};
+
+/// Result of a single doctor check
+#[derive(Debug, Clone, PartialEq)]
+pub enum CheckStatus {
+    Ok,
+    Fail(String),
+}
+
+/// Corrective action attached to a failed check, applied with `--fix`
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    CreateDir(PathBuf),
+    ReinstallBinary(&'static str),
+    MakeExecutable(PathBuf),
+    RemoveCorruptAuth(PathBuf),
+}
+
+/// A named diagnostic and its outcome
+#[derive(Debug, Clone)]
+pub struct Check {
+    pub name: String,
+    pub status: CheckStatus,
+    /// How to repair a failure, when it can be repaired automatically
+    pub fix: Option<Fix>,
+}
+
+impl Check {
+    fn ok(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Ok,
+            fix: None,
+        }
+    }
+
+    fn fail(name: impl Into<String>, message: impl Into<String>, fix: Option<Fix>) -> Self {
+        Self {
+            name: name.into(),
+            status: CheckStatus::Fail(message.into()),
+            fix,
+        }
+    }
+
+    pub fn is_ok(&self) -> bool {
+        self.status == CheckStatus::Ok
+    }
+}
+
+/// Runs every diagnostic against the current environment
+pub async fn run_checks(config: &AppConfig) -> Vec<Check> {
+    let mut checks = vec![check_home()];
+
+    for (subdir, path) in config.layout().required_dirs() {
+        checks.push(check_subdir(subdir, path));
+    }
+
+    let bin_dir = config.layout().bin_dir();
+    for provider in registry().providers() {
+        checks.push(check_binary(provider, &bin_dir).await);
+    }
+
+    checks.push(check_auth(config));
+
+    if !config.offline {
+        checks.push(check_network(config).await);
+    }
+
+    checks
+}
+
+fn check_home() -> Check {
+    match std::env::var("HOME") {
+        Ok(_) => Check::ok("HOME is set"),
+        Err(_) => Check::fail("HOME is set", "HOME environment variable not set", None),
+    }
+}
+
+fn check_subdir(subdir: &str, path: PathBuf) -> Check {
+    let name = format!("{} directory", subdir);
+
+    if let Err(e) = check_dir_path(&path) {
+        return Check::fail(name, e.to_string(), None);
+    }
+    if !path.is_dir() {
+        return Check::fail(
+            name,
+            format!("{} is missing", path.display()),
+            Some(Fix::CreateDir(path)),
+        );
+    }
+
+    Check::ok(name)
+}
+
+async fn check_binary(provider: &dyn BinaryInfoProvider, bin_dir: &Path) -> Check {
+    let name = format!("{} binary", provider.name());
+    let path = get_provider_binary_path(provider, bin_dir);
+
+    if !path.exists() {
+        return Check::fail(
+            name,
+            format!("{} is missing", path.display()),
+            Some(Fix::ReinstallBinary(provider.name())),
+        );
+    }
+    if !is_executable(&path).unwrap_or(false) {
+        return Check::fail(
+            name,
+            format!("{} is not executable", path.display()),
+            Some(Fix::MakeExecutable(path)),
+        );
+    }
+
+    match verify_binary(provider, bin_dir).await {
+        VerifyOutcome::Ok { .. } => Check::ok(name),
+        VerifyOutcome::Missing => Check::fail(
+            name,
+            "binary is not installed",
+            Some(Fix::ReinstallBinary(provider.name())),
+        ),
+        VerifyOutcome::VersionCommandFailed { error } => Check::fail(
+            name,
+            format!("binary does not run: {}", error),
+            Some(Fix::ReinstallBinary(provider.name())),
+        ),
+        VerifyOutcome::HashMismatch { .. } => Check::fail(
+            name,
+            "checksum does not match the published digest",
+            Some(Fix::ReinstallBinary(provider.name())),
+        ),
+    }
+}
+
+fn check_auth(config: &AppConfig) -> Check {
+    let name = "auth tokens";
+    match AuthTokens::load_from_config(config) {
+        Ok(_) => Check::ok(name),
+        Err(AuthTokenError::CorruptTokenFile { path, source }) => Check::fail(
+            name,
+            format!("{} is corrupt ({})", path.display(), source),
+            Some(Fix::RemoveCorruptAuth(path)),
+        ),
+        Err(e) => Check::fail(name, e.to_string(), None),
+    }
+}
+
+async fn check_network(config: &AppConfig) -> Check {
+    let name = "platform reachable";
+    let client = match config.http_client() {
+        Ok(client) => client,
+        Err(e) => return Check::fail(name, e.to_string(), None),
+    };
+
+    match check_connectivity(&client, &config.api_base_url, CONNECTIVITY_TIMEOUT).await {
+        Connectivity::Reachable { .. } => Check::ok(name),
+        Connectivity::Unreachable { error } => Check::fail(
+            name,
+            format!("{} is unreachable: {}", config.api_base_url, error),
+            None,
+        ),
+    }
+}
+
+/// Applies a fix, returning a description of what was done
+pub async fn apply_fix(config: &AppConfig, fix: &Fix) -> BinResult<String> {
+    match fix {
+        Fix::CreateDir(path) => {
+            ensure_dir_exists(path)?;
+            Ok(format!("Created {}", path.display()))
+        }
+        Fix::MakeExecutable(path) => {
+            set_binary_mode(path, DEFAULT_BINARY_MODE)?;
+            Ok(format!(
+                "Set mode {:o} on {}",
+                DEFAULT_BINARY_MODE,
+                path.display()
+            ))
+        }
+        Fix::ReinstallBinary(name) => {
+            let provider = registry()
+                .get_provider(name)
+                .ok_or_else(|| format!("Unknown binary provider: {}", name))?;
+            let options = InstallOptions {
+                force_download: true,
+                events: config.events.clone(),
+                http: config.download_client.clone(),
+                install_log: Some(config.layout().install_log()),
+                ..Default::default()
+            };
+            let path = install_binary(provider, config.layout().bin_dir(), &options).await?;
+            Ok(format!("Reinstalled {} at {}", name, path.display()))
+        }
+        Fix::RemoveCorruptAuth(path) => {
+            remove_path_guarded(path, &config.agnostic_dir)?;
+            Ok(format!(
+                "Removed {}, run `user login` to sign in again",
+                path.display()
+            ))
+        }
+    }
+}
+
+/// Prints the checks and, with `fix`, repairs what it can
+///
+/// Returns whether every check passes (after fixes).
+pub async fn handle_doctor(config: &AppConfig, fix: bool) -> bool {
+    println!("System Doctor");
+    println!("=============");
+    println!();
+
+    let mut remaining = 0;
+
+    for check in run_checks(config).await {
+        if check.is_ok() {
+            println!("  [OK] {}", check.name);
+            continue;
+        }
+        if let CheckStatus::Fail(message) = &check.status {
+            println!("  [FAIL] {} - {}", check.name, message);
+        }
+
+        match (&check.fix, fix) {
+            (Some(action), true) => match apply_fix(config, action).await {
+                Ok(done) => println!("    Fixed: {}", done),
+                Err(e) => {
+                    println!("    Fix failed: {}", e);
+                    remaining += 1;
+                }
+            },
+            (Some(_), false) => {
+                println!("    Fixable, run `system doctor --fix`");
+                remaining += 1;
+            }
+            (None, _) => {
+                println!("    Cannot be fixed automatically");
+                remaining += 1;
+            }
+        }
+    }
+
+    println!();
+    if remaining > 0 {
+        println!("{} problem(s) remaining", remaining);
+        false
+    } else {
+        println!("No problems found");
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn offline_config(temp_dir: &TempDir) -> AppConfig {
+        AppConfig::new(temp_dir.path().to_path_buf()).with_offline()
+    }
+
+    fn find<'a>(checks: &'a [Check], name: &str) -> &'a Check {
+        checks.iter().find(|c| c.name == name).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_missing_subdir_is_repaired() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = offline_config(&temp_dir);
+        fs::create_dir(temp_dir.path().join("bin")).unwrap();
+
+        let checks = run_checks(&config).await;
+        assert!(find(&checks, "bin directory").is_ok());
+
+        let user = find(&checks, "user directory");
+        let fix = user.fix.as_ref().unwrap();
+        assert_eq!(fix, &Fix::CreateDir(temp_dir.path().join("user")));
+
+        let done = apply_fix(&config, fix).await.unwrap();
+        assert!(done.starts_with("Created"));
+        assert!(temp_dir.path().join("user").is_dir());
+
+        let checks = run_checks(&config).await;
+        assert!(find(&checks, "user directory").is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_non_executable_binary_is_repaired() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = offline_config(&temp_dir);
+        let agt_path = temp_dir.path().join("bin/agt");
+        fs::create_dir_all(agt_path.parent().unwrap()).unwrap();
+        fs::write(&agt_path, "#!/bin/sh\necho agt v0.0.23\n").unwrap();
+
+        let checks = run_checks(&config).await;
+        let agt = find(&checks, "agt binary");
+        assert!(matches!(&agt.status, CheckStatus::Fail(m) if m.contains("not executable")));
+        let fix = agt.fix.as_ref().unwrap();
+        assert_eq!(fix, &Fix::MakeExecutable(agt_path.clone()));
+
+        apply_fix(&config, fix).await.unwrap();
+        assert!(is_executable(&agt_path).unwrap());
+
+        let checks = run_checks(&config).await;
+        assert!(find(&checks, "agt binary").is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_corrupt_auth_is_removed() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = offline_config(&temp_dir);
+        let auth_path = temp_dir.path().join("user/auth.json");
+        fs::create_dir_all(auth_path.parent().unwrap()).unwrap();
+        fs::write(&auth_path, "{not json").unwrap();
+
+        let checks = run_checks(&config).await;
+        let fix = find(&checks, "auth tokens").fix.clone().unwrap();
+        assert_eq!(fix, Fix::RemoveCorruptAuth(auth_path.clone()));
+
+        let done = apply_fix(&config, &fix).await.unwrap();
+        assert!(done.contains("user login"));
+        assert!(!auth_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_missing_binary_is_fixable() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = offline_config(&temp_dir);
+
+        let checks = run_checks(&config).await;
+        assert_eq!(
+            find(&checks, "s3fs binary").fix,
+            Some(Fix::ReinstallBinary("s3fs"))
+        );
+        assert!(checks.iter().all(|c| c.name != "platform reachable"));
+    }
+}
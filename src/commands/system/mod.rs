@@ -0,0 +1,1208 @@
+mod doctor;
+mod paths;
+mod update_check;
+mod versions;
+
+use std::{
+    fs,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use clap::Subcommand;
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+
+use crate::utils::{
+    AppConfig, BinResult, BinaryManager, InstallOptions, SystemTarget, VersionOverride,
+    app::output::print_json,
+    bin::manager::{
+        BinaryInfo, BinaryInfoProvider, VerifyOutcome, get_binary_version_at, is_executable,
+        parse_binary_mode, verify_binary,
+    },
+    bin::manifest::BinManifest,
+    get_binaries_status, get_binary_version_by_name, registry,
+};
+
+/// System-related subcommands
+#[derive(Subcommand, Debug)]
+pub enum SystemAction {
+    /// Show overall system status
+    Status {
+        /// Compute and show the SHA-256 of each installed binary
+        #[arg(long)]
+        checksums: bool,
+        /// Print the binary status as JSON
+        #[arg(long)]
+        json: bool,
+        /// Also check GitHub for a newer release of the CLI
+        #[arg(long)]
+        check_update: bool,
+    },
+    /// Check GitHub for a newer release of the CLI
+    SelfUpdateCheck,
+    /// Re-check the integrity of installed binaries
+    Verify,
+    /// List the managed binaries, their targets and download URLs for this host
+    List,
+    /// Print the CLI version and the version of every managed binary
+    Versions {
+        /// Print the versions as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Print where the CLI keeps its files (root, bin dir, auth tokens, ...)
+    Paths {
+        /// Report this directory as the bin directory, as with `system install --bin-dir`
+        #[arg(long)]
+        bin_dir: Option<PathBuf>,
+        /// Print the paths as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Diagnose common problems with the local setup
+    Doctor {
+        /// Repair fixable problems (missing directories, broken binaries, corrupt auth file)
+        #[arg(long)]
+        fix: bool,
+    },
+    /// Install managed binaries
+    Install {
+        /// Only install this binary (e.g. clickhouse)
+        #[arg(long)]
+        name: Option<String>,
+        /// Install binaries for another platform (e.g. linux-x86_64)
+        #[arg(long)]
+        target: Option<SystemTarget>,
+        /// Directory to install binaries into (defaults to ~/.agnostic/bin)
+        #[arg(long)]
+        bin_dir: Option<PathBuf>,
+        /// Install the --name binary at this file or directory instead of the bin dir
+        #[arg(
+            long,
+            value_name = "PATH",
+            requires = "name",
+            conflicts_with = "bin_dir"
+        )]
+        to: Option<PathBuf>,
+        /// Re-download binaries even if they are already installed
+        #[arg(long)]
+        force: bool,
+        /// Octal permission mode for installed binaries (default 755)
+        #[arg(long, value_name = "MODE", value_parser = parse_binary_mode)]
+        chmod: Option<u32>,
+        /// Maximum number of binaries downloaded at once (default: all)
+        #[arg(long, value_name = "N")]
+        download_concurrency: Option<NonZeroUsize>,
+        /// Pick the download concurrency from a quick bandwidth probe (--download-concurrency wins)
+        #[arg(long)]
+        auto_concurrency: bool,
+        /// Install a specific version of a binary for this run (e.g. agt=0.0.24), repeatable
+        #[arg(long = "bin-version", value_name = "NAME=VERSION")]
+        bin_versions: Vec<VersionOverride>,
+        /// Print the URL the --name binary would be downloaded from and exit
+        #[arg(long, requires = "name")]
+        print_url: bool,
+        /// Don't run installed binaries to verify them (for sandboxes that forbid it)
+        #[arg(long, env = "AGNOSTIC_SKIP_VERIFY")]
+        skip_verify: bool,
+    },
+}
+
+impl SystemAction {
+    /// Examples shown after `system --help`, resolved for the current host
+    pub fn help_examples() -> String {
+        let mut lines = vec![
+            "Examples:".to_string(),
+            "  ag system status --checksums".to_string(),
+            "  ag system paths --json".to_string(),
+            "  ag system install --name clickhouse --force".to_string(),
+            "  ag system install --name clickhouse --to /usr/local/bin".to_string(),
+            "  ag system install --name agt --print-url".to_string(),
+        ];
+
+        let host = SystemTarget::detect();
+        if let Some(other) = SystemTarget::all()
+            .iter()
+            .find(|target| host.as_ref().ok() != Some(*target))
+        {
+            lines.push(format!(
+                "  ag system install --target {} --bin-dir ./bin",
+                other
+            ));
+        }
+        lines.push(String::new());
+
+        match host {
+            Ok(target) => {
+                lines.push(format!("Detected target: {}", target));
+                lines.push("Binary URLs for this host:".to_string());
+                for provider in registry().providers() {
+                    let url = provider
+                        .get_download_url(&target)
+                        .unwrap_or_else(|e| e.to_string());
+                    lines.push(format!("  {:<12} {}", provider.name(), url));
+                }
+            }
+            Err(e) => lines.push(format!("Detected target: {}", e)),
+        }
+
+        lines.join("\n")
+    }
+
+    pub async fn handle(self, config: &AppConfig) {
+        match self {
+            Self::Status {
+                checksums,
+                json,
+                check_update,
+            } => {
+                if json {
+                    let report = StatusReport {
+                        agnostic_dir: config.agnostic_dir.clone(),
+                        disk: disk_space_or_warn(&config.agnostic_dir),
+                        binaries: binaries_status(&config.layout().bin_dir(), checksums),
+                        update_notice: if check_update {
+                            update_check::check_for_update(config, update_check::CLI_RELEASES_URL)
+                                .await
+                        } else {
+                            None
+                        },
+                    };
+                    if let Err(e) = print_json(config, &report) {
+                        eprintln!("Error printing status: {}", e);
+                    }
+                } else {
+                    show_system_status(config, checksums, check_update).await
+                }
+            }
+            Self::SelfUpdateCheck => update_check::handle_self_update_check(config).await,
+            Self::List => show_provider_list(),
+            Self::Versions { json } => versions::handle_versions(config, json).await,
+            Self::Paths { bin_dir, json } => paths::handle_paths(config, bin_dir.as_deref(), json),
+            Self::Verify => {
+                if !verify_binaries(config).await {
+                    std::process::exit(1);
+                }
+            }
+            Self::Doctor { fix } => {
+                if !doctor::handle_doctor(config, fix).await {
+                    std::process::exit(1);
+                }
+            }
+            Self::Install {
+                name,
+                target,
+                bin_dir,
+                to,
+                force,
+                chmod,
+                download_concurrency,
+                auto_concurrency,
+                bin_versions,
+                print_url,
+                skip_verify,
+            } => {
+                let bin_dir = bin_dir.unwrap_or_else(|| config.layout().bin_dir());
+                let options = InstallOptions {
+                    force_download: force,
+                    target,
+                    mode: chmod,
+                    events: config.events.clone(),
+                    concurrency: download_concurrency,
+                    auto_concurrency,
+                    versions: bin_versions,
+                    http: config.download_client.clone(),
+                    install_log: Some(config.layout().install_log()),
+                    skip_verify,
+                    ..Default::default()
+                };
+                if print_url {
+                    match name.as_deref().map(|name| download_url(name, &options)) {
+                        Some(Ok(url)) => println!("{}", url),
+                        Some(Err(e)) => {
+                            eprintln!("Error resolving the download URL: {}", e);
+                            std::process::exit(1);
+                        }
+                        None => unreachable!("--print-url requires --name"),
+                    }
+                    return;
+                }
+                let result = match (name.as_deref(), to) {
+                    (Some(name), Some(to)) => {
+                        install_binary_to_path(name, &to, &bin_dir, &options).await
+                    }
+                    (name, _) => install_binaries(name, &bin_dir, &options).await,
+                };
+                if let Err(e) = result {
+                    eprintln!("Error installing binaries: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+}
+
+/// What `system list` shows about one provider
+#[derive(Debug, Clone, PartialEq)]
+struct ProviderListing {
+    name: &'static str,
+    local_name: &'static str,
+    /// Targets the provider publishes a build for
+    targets: Vec<String>,
+    /// Candidate download URLs for the host, primary first (empty on an unsupported host)
+    urls: Vec<String>,
+}
+
+/// Describes each provider, resolving download URLs for `host`
+fn list_providers<'a>(
+    providers: impl Iterator<Item = &'a dyn BinaryInfoProvider>,
+    host: Option<&SystemTarget>,
+) -> Vec<ProviderListing> {
+    providers
+        .map(|provider| ProviderListing {
+            name: provider.name(),
+            local_name: provider.local_name(),
+            targets: SystemTarget::all()
+                .iter()
+                .filter(|t| provider.get_download_url(t).is_ok())
+                .map(|t| t.to_string())
+                .collect(),
+            urls: host
+                .and_then(|t| provider.get_download_urls(t).ok())
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// Print the managed binaries without touching the network
+fn show_provider_list() {
+    let host = SystemTarget::detect();
+
+    println!("Managed Binaries");
+    println!("================");
+    match &host {
+        Ok(target) => println!("Host target: {}", target),
+        Err(e) => println!("Host target: {}", e),
+    }
+    println!();
+
+    for listing in list_providers(registry().providers(), host.as_ref().ok()) {
+        println!("{}", listing.name);
+        println!("   File: {}", listing.local_name);
+        println!("   Targets: {}", listing.targets.join(", "));
+        match listing.urls.split_first() {
+            Some((primary, mirrors)) => {
+                println!("   URL: {}", primary);
+                for mirror in mirrors {
+                    println!("   Mirror: {}", mirror);
+                }
+            }
+            None => println!("   URL: unavailable for this host"),
+        }
+        println!();
+    }
+}
+
+/// Verify every managed binary, returning whether all of them passed
+async fn verify_binaries(config: &AppConfig) -> bool {
+    let bin_dir = config.layout().bin_dir();
+    let mut failures = 0;
+
+    println!("Binary Verification");
+    println!("===================");
+    println!();
+
+    for provider in registry().providers() {
+        let outcome = verify_binary(provider, &bin_dir).await;
+        match &outcome {
+            VerifyOutcome::Ok {
+                version,
+                digest_checked: true,
+            } => println!(
+                "  [OK] {} - {} (checksum verified)",
+                provider.name(),
+                version
+            ),
+            VerifyOutcome::Ok {
+                version,
+                digest_checked: false,
+            } => println!(
+                "  [OK] {} - {} (no published digest, checksum not checked)",
+                provider.name(),
+                version
+            ),
+            VerifyOutcome::Missing => println!("  [MISSING] {}", provider.name()),
+            VerifyOutcome::VersionCommandFailed { error } => {
+                println!("  [VERSION FAILED] {} - {}", provider.name(), error)
+            }
+            VerifyOutcome::HashMismatch { expected, actual } => {
+                println!("  [HASH MISMATCH] {}", provider.name());
+                println!("    Expected: {}", expected);
+                println!("    Actual:   {}", actual);
+            }
+        }
+
+        if !outcome.is_ok() {
+            failures += 1;
+        }
+    }
+
+    println!();
+    if failures > 0 {
+        println!("{} binaries failed verification", failures);
+        false
+    } else {
+        println!("All binaries verified");
+        true
+    }
+}
+
+/// URL `system install --name` would download from first, for `--print-url`
+///
+/// Honours `--target` and `--bin-version` like an actual install.
+fn download_url(name: &str, options: &InstallOptions) -> BinResult<String> {
+    let provider = registry()
+        .get_provider(name)
+        .ok_or_else(|| format!("Unknown binary provider: {}", name))?;
+    let target = options.resolve_target()?;
+    options
+        .download_urls(provider, &target)?
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("No download URL for {} binary", provider.name()).into())
+}
+
+/// Install one named binary, or all managed binaries
+async fn install_binaries(
+    name: Option<&str>,
+    bin_dir: &PathBuf,
+    options: &InstallOptions,
+) -> BinResult<()> {
+    let manager = BinaryManager::new(bin_dir).with_options(options.clone());
+    match name {
+        Some(name) => {
+            let provider = manager.provider(name)?;
+            let path = manager.ensure(name).await?;
+            println!("{} is installed at {}", provider.name(), path.display());
+        }
+        None => {
+            manager.ensure_all().await?;
+            println!("All binaries are installed in {}", bin_dir.display());
+        }
+    }
+
+    Ok(())
+}
+
+/// Install one binary at `path`, outside the managed bin directory
+async fn install_binary_to_path(
+    name: &str,
+    path: &Path,
+    bin_dir: &Path,
+    options: &InstallOptions,
+) -> BinResult<()> {
+    let manager = BinaryManager::new(bin_dir).with_options(options.clone());
+    let installed = manager.install_to(name, path).await?;
+    println!(
+        "{} is installed at {}",
+        manager.provider(name)?.name(),
+        installed.display()
+    );
+    Ok(())
+}
+
+/// Display overall system status
+async fn show_system_status(config: &AppConfig, checksums: bool, check_update: bool) {
+    println!("System Status");
+    println!("=============");
+    println!("   CLI version: {}", env!("CARGO_PKG_VERSION"));
+    if check_update
+        && let Some(notice) =
+            update_check::check_for_update(config, update_check::CLI_RELEASES_URL).await
+    {
+        println!("   {}", notice);
+    }
+    println!();
+
+    // Working directory info
+    println!("Working Directory");
+    println!("   Location: {}", config.agnostic_dir.display());
+    println!("   Exists: {}", config.agnostic_dir.exists());
+
+    if let Ok(metadata) = fs::metadata(&config.agnostic_dir) {
+        println!("   Created: {}", format_created_time(metadata.created()));
+        println!(
+            "   Modified: {}",
+            format_system_time(metadata.modified().ok())
+        );
+    }
+    if let Some(disk) = disk_space_or_warn(&config.agnostic_dir) {
+        println!(
+            "   Disk space: {} available of {}",
+            format_file_size(disk.available_bytes),
+            format_file_size(disk.total_bytes)
+        );
+    }
+    println!();
+
+    // Subdirectories
+    println!("Subdirectories");
+    for (subdir, path) in config.layout().required_dirs() {
+        let exists = path.exists();
+        let status = if exists { "[EXISTS]" } else { "[MISSING]" };
+
+        println!("   {} {} - {}", status, subdir, path.display());
+
+        if exists && let Ok(entries) = fs::read_dir(&path) {
+            let count = entries.count();
+            println!("      Items: {}", count);
+        }
+    }
+    println!();
+
+    // Binary status summary
+    println!("Binary Dependencies");
+    show_binaries_status(config, checksums).await;
+
+    // Connectivity to the platform
+    println!("Connectivity");
+    println!("   Platform: {}", config.api_base_url);
+    if config.offline {
+        println!("   [SKIPPED] offline mode");
+    } else {
+        match config.http_client() {
+            Ok(client) => {
+                let outcome =
+                    check_connectivity(&client, &config.api_base_url, CONNECTIVITY_TIMEOUT).await;
+                print_connectivity(&outcome);
+            }
+            Err(e) => println!("   [UNREACHABLE] {}", e),
+        }
+    }
+    println!();
+
+    // System information
+    println!("System Information");
+    println!("   OS: {}", std::env::consts::OS);
+    println!("   Architecture: {}", std::env::consts::ARCH);
+    println!("   Family: {}", std::env::consts::FAMILY);
+
+    if let Ok(home) = std::env::var("HOME") {
+        println!("   Home: {}", home);
+    }
+
+    if let Ok(user) = std::env::var("USER") {
+        println!("   User: {}", user);
+    }
+}
+
+/// What `system status --json` prints
+#[derive(Debug, Serialize)]
+struct StatusReport {
+    agnostic_dir: PathBuf,
+    disk: Option<DiskSpace>,
+    binaries: Vec<BinaryInfo>,
+    /// Newer CLI release notice, with `--check-update`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    update_notice: Option<String>,
+}
+
+/// Size of the filesystem holding a directory
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct DiskSpace {
+    pub total_bytes: u64,
+    pub available_bytes: u64,
+}
+
+impl DiskSpace {
+    /// Space of the filesystem `path` lives on
+    ///
+    /// A missing `path` is measured on its closest existing ancestor, where it
+    /// would be created.
+    pub fn of(path: &Path) -> std::io::Result<Self> {
+        let existing = path
+            .ancestors()
+            .find(|ancestor| ancestor.exists())
+            .unwrap_or(path);
+        Ok(Self {
+            total_bytes: fs2::total_space(existing)?,
+            available_bytes: fs2::available_space(existing)?,
+        })
+    }
+}
+
+/// [`DiskSpace::of`] the agnostic dir, reporting a failure on stderr
+fn disk_space_or_warn(path: &Path) -> Option<DiskSpace> {
+    DiskSpace::of(path)
+        .inspect_err(|e| eprintln!("Could not read disk space of {}: {}", path.display(), e))
+        .ok()
+}
+
+/// Status of all managed binaries, hashing the installed ones with `checksums`
+///
+/// A binary that cannot be read keeps `sha256` unset; the failure is reported
+/// on stderr.
+fn binaries_status(bin_dir: &Path, checksums: bool) -> Vec<BinaryInfo> {
+    let binaries = get_binaries_status(bin_dir);
+    if !checksums {
+        return binaries;
+    }
+
+    binaries
+        .into_iter()
+        .map(|binary| {
+            binary.clone().with_checksum().unwrap_or_else(|e| {
+                eprintln!("Could not hash {}: {}", binary.path.display(), e);
+                binary
+            })
+        })
+        .collect()
+}
+
+/// Looks up the version of every ready binary concurrently, in the order of `binaries`
+///
+/// Entries for binaries that are not ready are `None`, as are failed lookups.
+async fn binary_versions(binaries: &[BinaryInfo], bin_dir: &Path) -> Vec<Option<String>> {
+    let lookups = binaries.iter().map(|binary| async move {
+        if !binary.is_ready() {
+            return None;
+        }
+        get_binary_version_by_name(&binary.name, bin_dir).await.ok()
+    });
+    futures_util::future::join_all(lookups).await
+}
+
+/// A binary on `PATH` with the same name as a managed one
+#[derive(Debug, Clone, PartialEq)]
+struct PathShadow {
+    name: &'static str,
+    path: PathBuf,
+    /// Version reported by the binary on `PATH`, when it could be determined
+    version: Option<String>,
+}
+
+/// First executable named `file_name` in the directories of `path_var`
+fn find_on_path(file_name: &str, path_var: &std::ffi::OsStr) -> Option<PathBuf> {
+    std::env::split_paths(path_var)
+        .map(|dir| dir.join(file_name))
+        .find(|candidate| candidate.is_file() && is_executable(candidate).unwrap_or(false))
+}
+
+/// Managed binaries whose name resolves to another executable on `PATH`
+///
+/// A `PATH` entry pointing into `bin_dir` itself is not reported.
+async fn path_shadows(bin_dir: &Path, path_var: &std::ffi::OsStr) -> Vec<PathShadow> {
+    let mut shadows = Vec::new();
+
+    for provider in registry().providers() {
+        let Some(path) = find_on_path(provider.local_name(), path_var) else {
+            continue;
+        };
+        let managed = bin_dir.join(provider.local_name());
+        let same_file = match (path.canonicalize(), managed.canonicalize()) {
+            (Ok(found), Ok(managed)) => found == managed,
+            _ => false,
+        };
+        if same_file {
+            continue;
+        }
+
+        let version = get_binary_version_at(provider, &path).await.ok();
+        shadows.push(PathShadow {
+            name: provider.name(),
+            path,
+            version,
+        });
+    }
+
+    shadows
+}
+
+/// Display the status of all managed binaries
+async fn show_binaries_status(config: &AppConfig, checksums: bool) {
+    let bin_dir = config.layout().bin_dir();
+    let binaries = binaries_status(&bin_dir, checksums);
+    let versions = binary_versions(&binaries, &bin_dir).await;
+
+    if binaries.is_empty() {
+        println!("No managed binaries found.");
+        return;
+    }
+
+    for (binary, version) in binaries.iter().zip(versions) {
+        let status_icon = if binary.is_ready() {
+            "[READY]"
+        } else {
+            "[MISSING]"
+        };
+        let size_info = match binary.size {
+            Some(size) => format_file_size(size),
+            None => "N/A".to_string(),
+        };
+
+        println!("  {} {}", status_icon, binary.name);
+        println!("    Path: {}", binary.path.display());
+        println!("    Exists: {}", if binary.exists { "Yes" } else { "No" });
+        println!(
+            "    Executable: {}",
+            if binary.executable { "Yes" } else { "No" }
+        );
+        println!("    Size: {}", size_info);
+        if let Some(modified) = binary.modified {
+            println!(
+                "    Modified: {}",
+                format_modified(modified, std::time::SystemTime::now())
+            );
+        }
+        if let Some(sha256) = &binary.sha256 {
+            println!("    SHA-256: {}", sha256);
+        }
+
+        // Show version info for ready binaries
+        if binary.is_ready() {
+            println!("    Version: {}", version.as_deref().unwrap_or("Unknown"));
+        }
+
+        println!();
+    }
+
+    // Summary
+    let ready_count = binaries.iter().filter(|b| b.is_ready()).count();
+    let total_count = binaries.len();
+
+    if ready_count != total_count {
+        println!(
+            "Warning: {} of {} binaries are ready",
+            ready_count, total_count
+        );
+    }
+
+    for warning in outdated_binary_warnings(&bin_dir) {
+        println!("{}", warning);
+    }
+
+    let path_var = std::env::var_os("PATH").unwrap_or_default();
+    for shadow in path_shadows(&bin_dir, &path_var).await {
+        println!(
+            "Note: another {} is on your PATH at {} ({}); commands run by the CLI use {}",
+            shadow.name,
+            shadow.path.display(),
+            shadow.version.as_deref().unwrap_or("version unknown"),
+            bin_dir.display()
+        );
+    }
+}
+
+/// Warnings for binaries placed by an older CLI version, per the bin manifest
+fn outdated_binary_warnings(bin_dir: &Path) -> Vec<String> {
+    let manifest = match BinManifest::load(bin_dir) {
+        Ok(manifest) => manifest,
+        Err(e) => return vec![format!("Warning: {}", e)],
+    };
+
+    let current = env!("CARGO_PKG_VERSION");
+    manifest
+        .installed_by_older_cli(current)
+        .into_iter()
+        .map(|(name, version)| {
+            format!(
+                "Warning: {} was installed by CLI {} (current: {}), run `system install --name {} --force` to refresh it",
+                name, version, current, name
+            )
+        })
+        .collect()
+}
+
+/// Format file size in human-readable format
+fn format_file_size(size: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB", "TB"];
+    let mut size = size as f64;
+    let mut unit_index = 0;
+
+    while size >= 1024.0 && unit_index < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit_index += 1;
+    }
+
+    if unit_index == 0 {
+        format!("{} {}", size as u64, UNITS[unit_index])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit_index])
+    }
+}
+
+/// Format system time for display
+fn format_system_time(time: Option<std::time::SystemTime>) -> String {
+    match time {
+        Some(t) => {
+            if let Ok(duration) = t.duration_since(std::time::UNIX_EPOCH) {
+                let secs = duration.as_secs();
+                let datetime = chrono::DateTime::from_timestamp(secs as i64, 0);
+                match datetime {
+                    Some(dt) => dt.format("%Y-%m-%d %H:%M:%S UTC").to_string(),
+                    None => "Unknown".to_string(),
+                }
+            } else {
+                "Unknown".to_string()
+            }
+        }
+        None => "Unknown".to_string(),
+    }
+}
+
+/// Format a modification time followed by its age relative to `now`
+fn format_modified(modified: std::time::SystemTime, now: std::time::SystemTime) -> String {
+    let age = now.duration_since(modified).unwrap_or_default();
+    format!(
+        "{} ({})",
+        format_system_time(Some(modified)),
+        format_age(age)
+    )
+}
+
+/// Describe an age as "N units ago" using the largest whole unit
+fn format_age(age: Duration) -> String {
+    const UNITS: [(u64, &str); 4] = [
+        (365 * 24 * 60 * 60, "year"),
+        (24 * 60 * 60, "day"),
+        (60 * 60, "hour"),
+        (60, "minute"),
+    ];
+
+    let secs = age.as_secs();
+    for (unit_secs, unit) in UNITS {
+        let count = secs / unit_secs;
+        if count > 0 {
+            let plural = if count == 1 { "" } else { "s" };
+            return format!("{} {}{} ago", count, unit, plural);
+        }
+    }
+    "just now".to_string()
+}
+
+/// Format a creation time, telling apart filesystems that do not record birth time
+fn format_created_time(created: std::io::Result<std::time::SystemTime>) -> String {
+    match created {
+        Err(e) if e.kind() == std::io::ErrorKind::Unsupported => {
+            "(unsupported on this filesystem)".to_string()
+        }
+        created => format_system_time(created.ok()),
+    }
+}
+
+/// Upper bound for the connectivity check so `system status` never hangs
+const CONNECTIVITY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Result of probing the platform base URL
+#[derive(Debug)]
+enum Connectivity {
+    Reachable {
+        status: StatusCode,
+        latency: Duration,
+    },
+    Unreachable {
+        error: String,
+    },
+}
+
+/// Issues a HEAD request to `url` and measures the round-trip latency
+///
+/// Any HTTP response counts as reachable; only transport failures and
+/// timeouts are reported as unreachable.
+async fn check_connectivity(client: &Client, url: &str, timeout: Duration) -> Connectivity {
+    let started = Instant::now();
+    match client.head(url).timeout(timeout).send().await {
+        Ok(response) => Connectivity::Reachable {
+            status: response.status(),
+            latency: started.elapsed(),
+        },
+        Err(e) if e.is_timeout() => Connectivity::Unreachable {
+            error: format!("timed out after {:?}", timeout),
+        },
+        Err(e) => Connectivity::Unreachable {
+            error: e.to_string(),
+        },
+    }
+}
+
+fn print_connectivity(outcome: &Connectivity) {
+    match outcome {
+        Connectivity::Reachable { status, latency } => println!(
+            "   [REACHABLE] {} ms (HTTP {})",
+            latency.as_millis(),
+            status.as_u16()
+        ),
+        Connectivity::Unreachable { error } => println!("   [UNREACHABLE] {}", error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_format_created_time() {
+        let unsupported = std::io::Error::new(std::io::ErrorKind::Unsupported, "no birth time");
+        assert_eq!(
+            format_created_time(Err(unsupported)),
+            "(unsupported on this filesystem)"
+        );
+
+        let other = std::io::Error::other("stat failed");
+        assert_eq!(format_created_time(Err(other)), "Unknown");
+        assert_eq!(
+            format_created_time(Ok(std::time::UNIX_EPOCH)),
+            "1970-01-01 00:00:00 UTC"
+        );
+    }
+
+    #[test]
+    fn test_list_providers_for_detected_target() {
+        let host = SystemTarget::detect().unwrap_or(SystemTarget::LinuxX86_64);
+        let listings = list_providers(registry().providers(), Some(&host));
+
+        let names: Vec<_> = listings.iter().map(|l| l.name).collect();
+        assert_eq!(names, ["s3fs", "ClickHouse", "agt"]);
+        for listing in &listings {
+            assert!(!listing.local_name.is_empty());
+            assert_eq!(listing.targets.len(), SystemTarget::all().len());
+            assert!(
+                listing
+                    .urls
+                    .first()
+                    .is_some_and(|url| url.starts_with("https://"))
+            );
+        }
+
+        assert!(
+            list_providers(registry().providers(), None)
+                .iter()
+                .all(|l| l.urls.is_empty())
+        );
+    }
+
+    /// A provider that publishes no build for Intel macOS
+    struct NoIntelMacProvider;
+
+    impl BinaryInfoProvider for NoIntelMacProvider {
+        fn name(&self) -> &'static str {
+            "nointel"
+        }
+
+        fn local_name(&self) -> &'static str {
+            "nointel"
+        }
+
+        fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+            match target {
+                SystemTarget::MacOsX86_64 => Err("no build for macos-x86_64".into()),
+                _ => Ok(format!("https://example.com/nointel/{}", target)),
+            }
+        }
+
+        fn version_args(&self) -> &[&str] {
+            &["--version"]
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            Some(output.trim().to_string())
+        }
+    }
+
+    #[test]
+    fn test_list_providers_omits_unsupported_targets() {
+        let provider: &dyn BinaryInfoProvider = &NoIntelMacProvider;
+
+        let listings = list_providers(std::iter::once(provider), Some(&SystemTarget::MacOsX86_64));
+        assert_eq!(listings.len(), 1);
+        assert!(
+            !listings[0]
+                .targets
+                .contains(&SystemTarget::MacOsX86_64.to_string())
+        );
+        assert_eq!(listings[0].targets.len(), SystemTarget::all().len() - 1);
+        assert!(listings[0].urls.is_empty());
+
+        let listings = list_providers(std::iter::once(provider), Some(&SystemTarget::LinuxX86_64));
+        assert_eq!(
+            listings[0].urls,
+            ["https://example.com/nointel/linux-x86_64"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_reachable() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(20)))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let outcome = check_connectivity(&Client::new(), &server.uri(), CONNECTIVITY_TIMEOUT).await;
+        match outcome {
+            Connectivity::Reachable { status, latency } => {
+                assert_eq!(status, StatusCode::OK);
+                assert!(latency >= Duration::from_millis(20));
+            }
+            other => panic!("expected reachable, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_unreachable() {
+        // Bind then drop a listener to get a port nothing is listening on
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        drop(listener);
+
+        let outcome = check_connectivity(&Client::new(), &url, CONNECTIVITY_TIMEOUT).await;
+        assert!(matches!(outcome, Connectivity::Unreachable { .. }));
+        print_connectivity(&outcome);
+    }
+
+    #[tokio::test]
+    async fn test_check_connectivity_timeout() {
+        let server = MockServer::start().await;
+        Mock::given(method("HEAD"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_secs(5)))
+            .mount(&server)
+            .await;
+
+        let outcome =
+            check_connectivity(&Client::new(), &server.uri(), Duration::from_millis(100)).await;
+        match outcome {
+            Connectivity::Unreachable { error } => assert!(error.contains("timed out")),
+            other => panic!("expected timeout, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_help_examples_show_host() {
+        let help = SystemAction::help_examples();
+        assert!(help.starts_with("Examples:"));
+        match SystemTarget::detect() {
+            Ok(target) => {
+                assert!(help.contains(&format!("Detected target: {}", target)));
+                assert!(
+                    help.contains(
+                        &registry()
+                            .providers()
+                            .next()
+                            .unwrap()
+                            .get_download_url(&target)
+                            .unwrap()
+                    )
+                );
+            }
+            Err(e) => assert!(help.contains(&e.to_string())),
+        }
+    }
+
+    #[test]
+    fn test_outdated_binary_warnings() {
+        use crate::utils::bin::manifest::ManifestEntry;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        assert!(outdated_binary_warnings(temp_dir.path()).is_empty());
+
+        let mut manifest = BinManifest::default();
+        for (name, version) in [("agt", "0.0.0"), ("s3fs", env!("CARGO_PKG_VERSION"))] {
+            manifest.binaries.insert(
+                name.to_string(),
+                ManifestEntry {
+                    installed_by_cli_version: version.to_string(),
+                    url: format!("https://example.com/{}", name),
+                    target: None,
+                    version: None,
+                    validators: Default::default(),
+                },
+            );
+        }
+        manifest.save(temp_dir.path()).unwrap();
+
+        let warnings = outdated_binary_warnings(temp_dir.path());
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("agt was installed by CLI 0.0.0"));
+        assert!(warnings[0].contains("system install --name agt --force"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_binary_versions_for_all_providers() {
+        use crate::utils::bin::manager::{DEFAULT_BINARY_MODE, write_with_mode};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        for provider in registry().providers() {
+            let script = format!("#!/bin/sh\necho {} 1.2.3\n", provider.local_name());
+            write_with_mode(
+                temp_dir.path().join(provider.local_name()),
+                script.as_bytes(),
+                DEFAULT_BINARY_MODE,
+            )
+            .unwrap();
+        }
+        let mut binaries = binaries_status(temp_dir.path(), false);
+        binaries.push(BinaryInfo::from_path(
+            "missing".to_string(),
+            temp_dir.path().join("missing"),
+        ));
+
+        let versions = binary_versions(&binaries, temp_dir.path()).await;
+
+        assert_eq!(versions.len(), binaries.len());
+        assert!(versions[..binaries.len() - 1].iter().all(Option::is_some));
+        assert_eq!(versions.last(), Some(&None));
+        let agt = binaries.iter().position(|b| b.name == "agt").unwrap();
+        assert_eq!(versions[agt].as_deref(), Some("agt 1.2.3"));
+    }
+
+    #[test]
+    fn test_binary_age_from_backdated_mtime() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("agt");
+        let file = fs::File::create(&path).unwrap();
+        let now = std::time::SystemTime::now();
+        file.set_modified(now - Duration::from_secs(3 * 24 * 60 * 60 + 90))
+            .unwrap();
+
+        let binary = BinaryInfo::from_path("agt".to_string(), path);
+        let modified = binary.modified.unwrap();
+        assert!(format_modified(modified, now).ends_with(" UTC (3 days ago)"));
+
+        let json = serde_json::to_value(&binary).unwrap();
+        let secs = modified
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert_eq!(json["modified_unix_secs"], secs);
+
+        assert_eq!(format_age(Duration::from_secs(30)), "just now");
+        assert_eq!(format_age(Duration::from_secs(60)), "1 minute ago");
+        assert_eq!(format_age(Duration::from_secs(5 * 60 * 60)), "5 hours ago");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_path_shadows() {
+        use crate::utils::bin::manager::{DEFAULT_BINARY_MODE, write_with_mode};
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let bin_dir = temp_dir.path().join("bin");
+        let system_dir = temp_dir.path().join("usr-bin");
+        write_with_mode(
+            system_dir.join("agt"),
+            b"#!/bin/sh\necho agt v0.0.1\n",
+            DEFAULT_BINARY_MODE,
+        )
+        .unwrap();
+        write_with_mode(bin_dir.join("agt"), b"#!/bin/sh\n", DEFAULT_BINARY_MODE).unwrap();
+        fs::write(system_dir.join("clickhouse"), b"not executable").unwrap();
+
+        let path_var = std::env::join_paths([&system_dir, &bin_dir]).unwrap();
+        let shadows = path_shadows(&bin_dir, &path_var).await;
+        assert_eq!(
+            shadows,
+            [PathShadow {
+                name: "agt",
+                path: system_dir.join("agt"),
+                version: Some("agt v0.0.1".to_string()),
+            }]
+        );
+
+        // The managed directory itself on PATH is not shadowing
+        let path_var = std::env::join_paths([&bin_dir]).unwrap();
+        assert!(path_shadows(&bin_dir, &path_var).await.is_empty());
+    }
+
+    #[test]
+    fn test_binaries_status_checksums() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::write(temp_dir.path().join("agt"), b"abc").unwrap();
+
+        let binaries = binaries_status(temp_dir.path(), false);
+        assert!(binaries.iter().all(|b| b.sha256.is_none()));
+
+        let binaries = binaries_status(temp_dir.path(), true);
+        let agt = binaries.iter().find(|b| b.name == "agt").unwrap();
+        assert_eq!(
+            agt.sha256.as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert!(
+            binaries
+                .iter()
+                .filter(|b| b.name != "agt")
+                .all(|b| b.sha256.is_none())
+        );
+
+        let json = serde_json::to_value(&binaries).unwrap();
+        let agt = json
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|b| b["name"] == "agt")
+            .unwrap();
+        assert_eq!(
+            agt["sha256"],
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_print_url_follows_target_and_version() {
+        let target = SystemTarget::LinuxX86_64;
+        let options = InstallOptions {
+            target: Some(target.clone()),
+            ..Default::default()
+        };
+        for provider in registry().providers() {
+            assert_eq!(
+                download_url(provider.local_name(), &options).unwrap(),
+                provider.get_download_url(&target).unwrap()
+            );
+        }
+
+        let pinned = InstallOptions {
+            versions: vec!["agt=0.0.24".parse().unwrap()],
+            ..options
+        };
+        assert_eq!(
+            download_url("agt", &pinned).unwrap(),
+            registry()
+                .get_provider("agt")
+                .unwrap()
+                .get_versioned_download_url(&target, "0.0.24")
+                .unwrap()
+        );
+        assert!(download_url("unknown", &pinned).is_err());
+    }
+
+    #[test]
+    fn test_disk_space_of_temp_dir() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+
+        let disk = DiskSpace::of(temp_dir.path()).unwrap();
+        assert!(disk.total_bytes > 0);
+        assert!(disk.available_bytes > 0);
+        assert!(disk.available_bytes <= disk.total_bytes);
+
+        // Not created yet: measured where it would be created
+        let missing = DiskSpace::of(&temp_dir.path().join("not/yet")).unwrap();
+        assert_eq!(missing.total_bytes, disk.total_bytes);
+    }
+
+    #[test]
+    fn test_format_file_size() {
+        assert_eq!(format_file_size(0), "0 B");
+        assert_eq!(format_file_size(512), "512 B");
+        assert_eq!(format_file_size(1024), "1.0 KB");
+        assert_eq!(format_file_size(1536), "1.5 KB");
+        assert_eq!(format_file_size(1024 * 1024), "1.0 MB");
+        assert_eq!(format_file_size(6423168), "6.1 MB");
+    }
+}
@@ -0,0 +1,97 @@
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use crate::utils::{
+    AppConfig, app::auth::auth_tokens_path, app::output::print_json, bin::manifest::BinManifest,
+};
+
+/// Resolved locations of the files and directories the CLI uses
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PathsReport {
+    pub root: PathBuf,
+    pub bin_dir: PathBuf,
+    pub bin_manifest: PathBuf,
+    pub auth: PathBuf,
+    pub profile_cache: PathBuf,
+    pub logs_dir: PathBuf,
+    pub install_log: PathBuf,
+}
+
+impl PathsReport {
+    /// Paths for `config`, with the bin directory replaced by `bin_dir` when given
+    pub fn resolve(config: &AppConfig, bin_dir: Option<&Path>) -> Self {
+        let layout = config.layout();
+        let bin_dir = bin_dir.map_or_else(|| layout.bin_dir(), Path::to_path_buf);
+        Self {
+            root: layout.root().to_path_buf(),
+            bin_manifest: BinManifest::path(&bin_dir),
+            bin_dir,
+            auth: auth_tokens_path(config),
+            profile_cache: crate::commands::user::profile_cache_path(config),
+            logs_dir: layout.logs_dir(),
+            install_log: layout.install_log(),
+        }
+    }
+
+    /// `(label, path)` pairs in display order
+    fn entries(&self) -> [(&'static str, &Path); 7] {
+        [
+            ("root", &self.root),
+            ("bin_dir", &self.bin_dir),
+            ("bin_manifest", &self.bin_manifest),
+            ("auth", &self.auth),
+            ("profile_cache", &self.profile_cache),
+            ("logs_dir", &self.logs_dir),
+            ("install_log", &self.install_log),
+        ]
+    }
+}
+
+/// Prints the resolved paths, one `label path` pair per line or as JSON
+pub fn handle_paths(config: &AppConfig, bin_dir: Option<&Path>, json: bool) {
+    let report = PathsReport::resolve(config, bin_dir);
+
+    if json {
+        if let Err(e) = print_json(config, &report) {
+            eprintln!("Error printing paths: {}", e);
+        }
+        return;
+    }
+
+    for (label, path) in report.entries() {
+        println!("{:<14} {}", label, path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paths_follow_overrides() {
+        let config = AppConfig::new(PathBuf::from("/srv/agnostic"));
+
+        let report = PathsReport::resolve(&config, None);
+        assert_eq!(report.bin_dir, Path::new("/srv/agnostic/bin"));
+        assert_eq!(report.auth, Path::new("/srv/agnostic/user/auth.json"));
+        assert_eq!(
+            report.profile_cache,
+            Path::new("/srv/agnostic/user/profile.json")
+        );
+        assert_eq!(report.logs_dir, Path::new("/srv/agnostic/logs"));
+        assert_eq!(
+            report.install_log,
+            Path::new("/srv/agnostic/logs/install.log")
+        );
+
+        let report = PathsReport::resolve(&config, Some(Path::new("/opt/tools")));
+        assert_eq!(report.root, Path::new("/srv/agnostic"));
+        assert_eq!(report.bin_dir, Path::new("/opt/tools"));
+        assert_eq!(report.bin_manifest, Path::new("/opt/tools/manifest.json"));
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["bin_dir"], "/opt/tools");
+        assert_eq!(json["auth"], "/srv/agnostic/user/auth.json");
+    }
+}
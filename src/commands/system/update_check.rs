@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use reqwest::{Client, header::ACCEPT};
+use serde::Deserialize;
+
+use crate::utils::{AppConfig, bin::manifest::compare_versions};
+
+/// Latest release of the CLI itself on GitHub
+pub const CLI_RELEASES_URL: &str = "https://api.github.com/repos/agnosticeng/cli/releases/latest";
+
+/// The check is a courtesy; never hold up `system status` for long
+const UPDATE_CHECK_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Deserialize)]
+struct Release {
+    tag_name: String,
+}
+
+/// Version of the latest release at `url`, without the leading `v`
+///
+/// Any failure (network, rate limit, unexpected body) yields `None`.
+pub async fn latest_cli_version(client: &Client, url: &str) -> Option<String> {
+    let response = client
+        .get(url)
+        .header(ACCEPT, "application/vnd.github+json")
+        .timeout(UPDATE_CHECK_TIMEOUT)
+        .send()
+        .await
+        .ok()?
+        .error_for_status()
+        .ok()?;
+    let release: Release = response.json().await.ok()?;
+    Some(release.tag_name.trim_start_matches('v').to_string())
+}
+
+/// Notice shown when `latest` is newer than the running `current` version
+pub fn newer_version_notice(current: &str, latest: &str) -> Option<String> {
+    (compare_versions(current, latest) == Ordering::Less).then(|| {
+        format!(
+            "A newer version {} is available (running {})",
+            latest, current
+        )
+    })
+}
+
+/// Newer-version notice for this CLI, `None` when up to date, offline or the check failed
+pub async fn check_for_update(config: &AppConfig, url: &str) -> Option<String> {
+    if config.offline {
+        return None;
+    }
+    let client = config.http_client().ok()?;
+    let latest = latest_cli_version(&client, url).await?;
+    newer_version_notice(env!("CARGO_PKG_VERSION"), &latest)
+}
+
+/// `system self-update-check`: reports whether a newer CLI is available
+pub async fn handle_self_update_check(config: &AppConfig) {
+    let current = env!("CARGO_PKG_VERSION");
+    if config.offline {
+        println!(
+            "Skipping the update check in offline mode (running {})",
+            current
+        );
+        return;
+    }
+    let latest = match config.http_client() {
+        Ok(client) => latest_cli_version(&client, CLI_RELEASES_URL).await,
+        Err(_) => None,
+    };
+    match latest {
+        Some(latest) => match newer_version_notice(current, &latest) {
+            Some(notice) => println!("{}", notice),
+            None => println!("You are running the latest version ({})", current),
+        },
+        None => println!(
+            "Could not determine the latest version (running {})",
+            current
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_newer_version_notice() {
+        assert_eq!(
+            newer_version_notice("0.1.0", "0.2.0").as_deref(),
+            Some("A newer version 0.2.0 is available (running 0.1.0)")
+        );
+        assert_eq!(newer_version_notice("0.2.0", "0.2.0"), None);
+        assert_eq!(newer_version_notice("0.10.0", "0.9.1"), None);
+    }
+
+    #[tokio::test]
+    async fn test_check_for_update_against_mocked_releases() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/releases/latest"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "tag_name": "v99.0.0",
+                "name": "v99.0.0"
+            })))
+            .mount(&server)
+            .await;
+        let url = format!("{}/releases/latest", server.uri());
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        let notice = check_for_update(&config, &url).await.unwrap();
+        assert!(
+            notice.contains("newer version 99.0.0 is available"),
+            "{}",
+            notice
+        );
+
+        let offline = AppConfig::new(temp_dir.path().to_path_buf()).with_offline();
+        assert_eq!(check_for_update(&offline, &url).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_check_is_quiet() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403).set_body_string("API rate limit exceeded"))
+            .mount(&server)
+            .await;
+
+        assert_eq!(
+            latest_cli_version(&Client::new(), &server.uri()).await,
+            None
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::utils::{
+    AppConfig, BinaryManager, app::output::print_json, bin::manager::is_binary_ready, registry,
+};
+
+/// Version of one managed binary, as reported by `system versions`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BinaryVersion {
+    pub name: &'static str,
+    pub installed: bool,
+    /// Detected version, `None` when not installed or when detection failed
+    pub version: Option<String>,
+    /// Why the version could not be detected for an installed binary
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Snapshot of the CLI and binary versions, for bug reports
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct VersionReport {
+    pub cli: &'static str,
+    pub binaries: Vec<BinaryVersion>,
+}
+
+/// Detects the version of every managed binary in `bin_dir`
+pub async fn version_report(bin_dir: &Path) -> VersionReport {
+    let manager = BinaryManager::new(bin_dir);
+    let mut binaries = Vec::new();
+
+    for provider in registry().providers() {
+        let installed = is_binary_ready(provider, bin_dir);
+        let (version, error) = if installed {
+            match manager.version(provider.name()).await {
+                Ok(version) => (Some(version), None),
+                Err(e) => (None, Some(e.to_string())),
+            }
+        } else {
+            (None, None)
+        };
+
+        binaries.push(BinaryVersion {
+            name: provider.name(),
+            installed,
+            version,
+            error,
+        });
+    }
+
+    VersionReport {
+        cli: env!("CARGO_PKG_VERSION"),
+        binaries,
+    }
+}
+
+/// Prints the version report, as JSON with `json`
+pub async fn handle_versions(config: &AppConfig, json: bool) {
+    let report = version_report(&config.layout().bin_dir()).await;
+
+    if json {
+        if let Err(e) = print_json(config, &report) {
+            eprintln!("Error printing versions: {}", e);
+        }
+        return;
+    }
+
+    println!("agnostic-cli {}", report.cli);
+    for binary in &report.binaries {
+        let version = match (&binary.version, &binary.error) {
+            (Some(version), _) => version.clone(),
+            (None, Some(error)) => format!("(unknown: {})", error),
+            (None, None) => "(not installed)".to_string(),
+        };
+        println!("{:<12} {}", binary.name, version);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_version_report() {
+        use crate::utils::bin::manager::{DEFAULT_BINARY_MODE, write_with_mode};
+
+        let temp_dir = TempDir::new().unwrap();
+        write_with_mode(
+            temp_dir.path().join("agt"),
+            b"#!/bin/sh\necho agt v0.0.23\n",
+            DEFAULT_BINARY_MODE,
+        )
+        .unwrap();
+
+        let report = version_report(temp_dir.path()).await;
+
+        assert_eq!(report.cli, env!("CARGO_PKG_VERSION"));
+        let names: Vec<_> = report.binaries.iter().map(|b| b.name).collect();
+        assert_eq!(names, ["s3fs", "ClickHouse", "agt"]);
+
+        let agt = &report.binaries[2];
+        assert!(agt.installed);
+        assert_eq!(agt.version.as_deref(), Some("agt v0.0.23"));
+        assert!(
+            report.binaries[..2]
+                .iter()
+                .all(|b| !b.installed && b.version.is_none())
+        );
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["binaries"][0]["version"], serde_json::Value::Null);
+        assert_eq!(json["binaries"][2]["version"], "agt v0.0.23");
+    }
+}
@@ -1,15 +1,26 @@
+use std::io::{self, Write};
 use std::sync::Arc;
 
+use std::net::IpAddr;
+
 use axum::{Json, Router, extract::State, http::StatusCode, response::IntoResponse, routing::post};
-use open::that;
+use clap::Args;
 use tokio::{net::TcpListener, sync::watch};
 
-use crate::{commands::UserAction, utils::AppConfig, utils::AuthTokens};
+use crate::{
+    commands::UserAction,
+    utils::AppConfig,
+    utils::AuthTokens,
+    utils::app::auth::auth_tokens_path,
+    utils::app::browser::{SystemBrowser, UrlOpener},
+    utils::app::clipboard::{Clipboard, SystemClipboard, copy_to_clipboard},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum ShutdownSignal {
     NotTriggered,
     Triggered,
+    Aborted,
 }
 
 struct LoginAppState {
@@ -17,63 +28,244 @@ struct LoginAppState {
     shutdown_tx: watch::Sender<ShutdownSignal>,
 }
 
+/// How the wait for the login callback ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum LoginOutcome {
+    /// Tokens were received and saved
+    Authenticated,
+    /// SIGINT or SIGTERM arrived before the callback
+    Aborted,
+}
+
+/// Where the browser sends the tokens back at the end of the login flow
+#[derive(Args, Debug, Clone, PartialEq, Eq)]
+pub struct CallbackOptions {
+    /// Host name used in the login redirect URI (e.g. 127.0.0.1 for strict allowlists)
+    #[arg(long, default_value = "localhost")]
+    pub callback_host: String,
+    /// Path the login callback is served on
+    #[arg(long, default_value = "/")]
+    pub callback_path: String,
+}
+
+impl Default for CallbackOptions {
+    fn default() -> Self {
+        Self {
+            callback_host: "localhost".to_string(),
+            callback_path: "/".to_string(),
+        }
+    }
+}
+
+impl CallbackOptions {
+    /// Callback path with a leading slash, as registered on the router
+    fn route_path(&self) -> String {
+        format!("/{}", self.callback_path.trim_start_matches('/'))
+    }
+
+    /// Redirect URI announced to the platform for a server listening on `port`
+    fn redirect_uri(&self, port: u16) -> String {
+        let host = match self.callback_host.parse::<IpAddr>() {
+            Ok(IpAddr::V6(ip)) => format!("[{}]", ip),
+            _ => self.callback_host.clone(),
+        };
+        let path = self.route_path();
+        if path == "/" {
+            format!("http://{}:{}", host, port)
+        } else {
+            format!("http://{}:{}{}", host, port, path)
+        }
+    }
+
+    /// Local address to listen on: the callback host itself when it is an IP
+    /// address, the IPv4 loopback otherwise
+    fn bind_addr(&self) -> (IpAddr, u16) {
+        let ip = self
+            .callback_host
+            .parse()
+            .unwrap_or(IpAddr::from([127, 0, 0, 1]));
+        (ip, 0)
+    }
+}
+
 impl UserAction {
     pub(super) async fn handle_login(
         self,
         config: &AppConfig,
-    ) -> Result<(), Box<dyn std::error::Error>> {
-        let (shutdown_tx, mut shutdown_rx) = watch::channel(ShutdownSignal::NotTriggered);
+    ) -> Result<LoginOutcome, Box<dyn std::error::Error>> {
+        let UserAction::Login {
+            copy_token,
+            copy_url,
+            no_browser,
+            callback,
+        } = self
+        else {
+            return Err("handle_login called for another user action".into());
+        };
 
-        let state = Arc::new(LoginAppState {
-            shutdown_tx,
-            config: config.clone(),
-        });
-
-        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let listener = TcpListener::bind(callback.bind_addr()).await?;
         let local_addr = listener.local_addr()?;
         let port = local_addr.port();
 
-        let redirect_uri = format!("http://localhost:{}", port);
-        let login_url = format!(
-            "https://app.agnostic.tech/login?redirectTo={}",
-            urlencoding::encode(&redirect_uri)
-        );
-
-        println!("Opening browser: {}", login_url);
-        if let Err(e) = that(&login_url) {
-            if config.verbose {
-                eprintln!("Failed to open browser: {}", e);
-            }
-            eprintln!("Please manually open: {}", login_url);
-        }
+        let redirect_uri = callback.redirect_uri(port);
+        let login_url = config.login_url(&redirect_uri);
 
-        // Build router with shutdown sender
-        let app = Router::new()
-            .route("/", post(handle_callback))
-            .layer(tower_http::cors::CorsLayer::permissive())
-            .with_state(state);
+        let mut browser = SystemBrowser;
+        let mut clipboard = SystemClipboard;
+        open_login_url(
+            config,
+            &login_url,
+            (!no_browser).then_some(&mut browser as &mut dyn UrlOpener),
+            &mut io::stderr().lock(),
+            copy_url.then_some(&mut clipboard as &mut dyn Clipboard),
+        )?;
 
         if config.verbose {
             println!("HTTP server listening at {}", redirect_uri);
         }
 
-        tokio::select! {
-            result = axum::serve(listener, app) => {
-                if let Err(e) = result {
-                    eprintln!("Server error: {}", e);
-                }
-            }
-            _ = shutdown_rx.wait_for(|&signal| signal == ShutdownSignal::Triggered) => {
+        let outcome = serve_login(
+            listener,
+            config,
+            &callback.route_path(),
+            termination_signal(),
+        )
+        .await?;
+
+        match outcome {
+            LoginOutcome::Authenticated => {
                 println!("Authentication successful!");
+                if copy_token {
+                    copy_saved_token(config, &mut SystemClipboard)?;
+                }
             }
+            LoginOutcome::Aborted => eprintln!("Login aborted, no token was saved."),
         }
 
         if config.verbose {
             println!("Shutting down HTTP server.");
         }
 
-        Ok(())
+        Ok(outcome)
+    }
+}
+
+/// Serves the login callback until tokens are saved or `abort` resolves
+///
+/// Either way the server shuts down gracefully, so an in-flight callback still
+/// gets its response.
+async fn serve_login(
+    listener: TcpListener,
+    config: &AppConfig,
+    callback_path: &str,
+    abort: impl Future<Output = ()> + Send + 'static,
+) -> Result<LoginOutcome, Box<dyn std::error::Error>> {
+    let (shutdown_tx, mut shutdown_rx) = watch::channel(ShutdownSignal::NotTriggered);
+    let state = Arc::new(LoginAppState {
+        shutdown_tx,
+        config: config.clone(),
+    });
+
+    // Build router with shutdown sender
+    let app = Router::new()
+        .route(callback_path, post(handle_callback))
+        .layer(tower_http::cors::CorsLayer::permissive())
+        .with_state(state.clone());
+
+    let signal_state = state.clone();
+    let shutdown = async move {
+        tokio::select! {
+            _ = shutdown_rx.wait_for(|&signal| signal == ShutdownSignal::Triggered) => {}
+            _ = abort => {
+                signal_state.shutdown_tx.send_if_modified(|signal| {
+                    let abort = *signal == ShutdownSignal::NotTriggered;
+                    if abort {
+                        *signal = ShutdownSignal::Aborted;
+                    }
+                    abort
+                });
+            }
+        }
+    };
+
+    axum::serve(listener, app)
+        .with_graceful_shutdown(shutdown)
+        .await?;
+
+    let outcome = match *state.shutdown_tx.borrow() {
+        ShutdownSignal::Triggered => LoginOutcome::Authenticated,
+        _ => LoginOutcome::Aborted,
+    };
+    Ok(outcome)
+}
+
+/// Resolves on Ctrl-C or, on Unix, SIGTERM
+async fn termination_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => {}
+                _ = terminate.recv() => {}
+            }
+            return;
+        }
+    }
+
+    let _ = tokio::signal::ctrl_c().await;
+}
+
+/// Opens the login URL with `opener`, printing it for the user to open instead
+/// when there is no opener (`--no-browser`) or it fails
+fn open_login_url(
+    config: &AppConfig,
+    login_url: &str,
+    opener: Option<&mut dyn UrlOpener>,
+    out: &mut dyn Write,
+    clipboard: Option<&mut dyn Clipboard>,
+) -> io::Result<()> {
+    if let Some(opener) = opener {
+        println!("Opening browser: {}", login_url);
+        match opener.open(login_url) {
+            Ok(()) => return Ok(()),
+            Err(e) if config.verbose => eprintln!("Failed to open browser: {}", e),
+            Err(_) => {}
+        }
+    }
+    print_login_url_fallback(out, login_url, clipboard)
+}
+
+/// Prints the login URL on its own line between rules so it stands out and
+/// copies cleanly over SSH, and copies it to `clipboard` when given
+fn print_login_url_fallback(
+    out: &mut dyn Write,
+    login_url: &str,
+    clipboard: Option<&mut dyn Clipboard>,
+) -> io::Result<()> {
+    let rule = "=".repeat(72);
+    writeln!(out)?;
+    writeln!(out, "{}", rule)?;
+    writeln!(out, "Could not open a browser. Open this URL to log in:")?;
+    writeln!(out)?;
+    writeln!(out, "{}", login_url)?;
+    writeln!(out)?;
+    writeln!(out, "{}", rule)?;
+
+    if let Some(clipboard) = clipboard {
+        copy_to_clipboard(clipboard, login_url, "Login URL");
     }
+    Ok(())
+}
+
+/// Copies the id token saved by the login callback to the clipboard
+fn copy_saved_token(
+    config: &AppConfig,
+    clipboard: &mut dyn Clipboard,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    let tokens = AuthTokens::load_from_config(config)?.ok_or("No auth tokens were saved")?;
+    Ok(copy_to_clipboard(clipboard, tokens.id_token(), "Token"))
 }
 
 async fn handle_callback(
@@ -85,10 +277,9 @@ async fn handle_callback(
         return StatusCode::BAD_REQUEST;
     }
 
-    let auth_file = state.config.agnostic_dir.join("user/auth.json");
-    if payload.save(&auth_file).is_ok() {
+    if payload.save_to_config(&state.config).is_ok() {
         if state.config.verbose {
-            println!("Tokens saved to {:?}", auth_file);
+            println!("Tokens saved to {:?}", auth_tokens_path(&state.config));
         }
         let _ = state.shutdown_tx.send(ShutdownSignal::Triggered);
         return StatusCode::NO_CONTENT;
@@ -96,3 +287,181 @@ async fn handle_callback(
 
     StatusCode::INTERNAL_SERVER_ERROR
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::app::browser::tests::{RecordingOpener, UnavailableBrowser};
+    use crate::utils::app::clipboard::tests::RecordingClipboard;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_copy_saved_token() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        std::fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        std::fs::write(
+            temp_dir.path().join("user/auth.json"),
+            r#"{"access_token": "access", "id_token": "id-token", "token_type": "Bearer"}"#,
+        )
+        .unwrap();
+
+        let mut clipboard = RecordingClipboard::default();
+        assert!(copy_saved_token(&config, &mut clipboard).unwrap());
+        assert_eq!(clipboard.copied, vec!["id-token".to_string()]);
+    }
+
+    #[test]
+    fn test_login_url_fallback() {
+        let url = "https://app.agnostic.tech/login?redirectTo=http%3A%2F%2Flocalhost%3A4000";
+
+        let mut out = Vec::new();
+        let mut clipboard = RecordingClipboard::default();
+        print_login_url_fallback(&mut out, url, Some(&mut clipboard)).unwrap();
+        let printed = String::from_utf8(out).unwrap();
+        assert!(printed.lines().any(|line| line == url));
+        assert_eq!(clipboard.copied, vec![url.to_string()]);
+
+        let mut out = Vec::new();
+        print_login_url_fallback(&mut out, url, None).unwrap();
+        assert!(
+            String::from_utf8(out)
+                .unwrap()
+                .lines()
+                .any(|line| line == url)
+        );
+    }
+
+    #[test]
+    fn test_login_url_is_opened() {
+        let config = AppConfig::new("/tmp/.agnostic".into());
+        let login_url = config.login_url(&CallbackOptions::default().redirect_uri(4000));
+        assert_eq!(
+            login_url,
+            "https://app.agnostic.tech/login?redirectTo=http%3A%2F%2Flocalhost%3A4000"
+        );
+
+        let mut opener = RecordingOpener::default();
+        let mut out = Vec::new();
+        open_login_url(&config, &login_url, Some(&mut opener), &mut out, None).unwrap();
+        assert_eq!(opener.opened, vec![login_url.clone()]);
+        assert!(out.is_empty(), "no fallback once the browser opened");
+
+        // Without a browser (--no-browser or none found) the URL is printed instead
+        for opener in [None, Some(&mut UnavailableBrowser as &mut dyn UrlOpener)] {
+            let mut out = Vec::new();
+            open_login_url(&config, &login_url, opener, &mut out, None).unwrap();
+            let printed = String::from_utf8(out).unwrap();
+            assert!(printed.lines().any(|line| line == login_url));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_abort_signal_stops_login_without_saving() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let outcome = serve_login(listener, &config, "/", std::future::ready(()))
+            .await
+            .unwrap();
+
+        assert_eq!(outcome, LoginOutcome::Aborted);
+        assert!(!temp_dir.path().join("user/auth.json").exists());
+    }
+
+    #[tokio::test]
+    async fn test_callback_completes_login() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        std::fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+
+        let callback = async {
+            reqwest::Client::new()
+                .post(&url)
+                .json(&serde_json::json!({
+                    "access_token": "access",
+                    "id_token": "id-token",
+                    "token_type": "Bearer"
+                }))
+                .send()
+                .await
+                .unwrap()
+        };
+        let (outcome, response) = tokio::join!(
+            serve_login(listener, &config, "/", std::future::pending()),
+            callback
+        );
+
+        assert_eq!(response.status(), reqwest::StatusCode::NO_CONTENT);
+        let outcome = outcome.unwrap();
+        assert_eq!(outcome, LoginOutcome::Authenticated);
+        assert!(temp_dir.path().join("user/auth.json").exists());
+    }
+
+    #[test]
+    fn test_callback_redirect_uri() {
+        let default = CallbackOptions::default();
+        assert_eq!(default.redirect_uri(4000), "http://localhost:4000");
+        assert_eq!(default.bind_addr().0, IpAddr::from([127, 0, 0, 1]));
+
+        let custom = CallbackOptions {
+            callback_host: "127.0.0.1".to_string(),
+            callback_path: "auth/callback".to_string(),
+        };
+        let redirect_uri = custom.redirect_uri(4000);
+        assert_eq!(redirect_uri, "http://127.0.0.1:4000/auth/callback");
+
+        let config = AppConfig::new("/tmp/.agnostic".into());
+        assert!(
+            config
+                .login_url(&redirect_uri)
+                .ends_with("redirectTo=http%3A%2F%2F127.0.0.1%3A4000%2Fauth%2Fcallback")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_callback_served_on_custom_path() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        std::fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        let tokens = serde_json::json!({
+            "access_token": "access",
+            "id_token": "id-token",
+            "token_type": "Bearer"
+        });
+
+        let callbacks = async {
+            let client = reqwest::Client::new();
+            let root = client.post(format!("{}/", base)).json(&tokens).send().await;
+            let custom = client
+                .post(format!("{}/auth/callback", base))
+                .json(&tokens)
+                .send()
+                .await;
+            (root.unwrap(), custom.unwrap())
+        };
+        let (outcome, (root, custom)) = tokio::join!(
+            serve_login(listener, &config, "/auth/callback", std::future::pending()),
+            callbacks
+        );
+
+        assert_eq!(root.status(), reqwest::StatusCode::NOT_FOUND);
+        assert_eq!(custom.status(), reqwest::StatusCode::NO_CONTENT);
+        assert_eq!(outcome.unwrap(), LoginOutcome::Authenticated);
+    }
+
+    #[test]
+    fn test_copy_saved_token_without_tokens() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+
+        let mut clipboard = RecordingClipboard::default();
+        assert!(copy_saved_token(&config, &mut clipboard).is_err());
+        assert!(clipboard.copied.is_empty());
+    }
+}
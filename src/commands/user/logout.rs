@@ -1,17 +1,61 @@
-use std::{error::Error, fs};
+use std::error::Error;
 
-use crate::{commands::UserAction, utils::AppConfig};
+use crate::{
+    commands::UserAction,
+    utils::{AppConfig, app::auth::auth_tokens_path},
+};
 
 impl UserAction {
     pub(super) async fn handle_logout(self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
-        let auth_json = config.agnostic_dir.join("user/auth.json");
-        if auth_json.try_exists()? {
-            fs::remove_file(auth_json)?;
+        let auth_json = auth_tokens_path(config);
+        if config.fs.exists(&auth_json)? {
+            config.fs.remove(&auth_json, &config.agnostic_dir)?;
             println!("auth.json file removed");
         }
 
+        let profile_json = super::status::profile_cache_path(config);
+        if config.fs.exists(&profile_json)? {
+            config.fs.remove(&profile_json, &config.agnostic_dir)?;
+        }
+
         println!("User logged out...");
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::fs::MemoryFs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_logout_removes_auth_and_profile() {
+        let memory = Arc::new(MemoryFs::default());
+        let config = AppConfig::new(PathBuf::from("/home/user/.agnostic")).with_fs(memory.clone());
+        let keep = config.layout().user_dir().join("settings.json");
+        for path in [
+            auth_tokens_path(&config),
+            super::super::status::profile_cache_path(&config),
+            keep.clone(),
+        ] {
+            config.fs.write(&path, b"{}").unwrap();
+        }
+
+        UserAction::Logout.handle_logout(&config).await.unwrap();
+
+        assert_eq!(memory.paths(), [keep]);
+    }
+
+    #[tokio::test]
+    async fn test_logout_without_auth_is_noop() {
+        let memory = Arc::new(MemoryFs::default());
+        let config = AppConfig::new(PathBuf::from("/home/user/.agnostic")).with_fs(memory.clone());
+
+        UserAction::Logout.handle_logout(&config).await.unwrap();
+
+        assert!(memory.paths().is_empty());
+    }
+}
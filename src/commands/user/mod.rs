@@ -1,34 +1,107 @@
 mod login;
 mod logout;
+mod reauth;
 mod status;
+mod transfer;
+#[allow(clippy::module_inception)]
 mod user;
 
+use std::path::PathBuf;
+
+pub use reauth::authenticate;
+pub(crate) use status::profile_cache_path;
+
 use clap::Subcommand;
 
 use crate::utils::AppConfig;
 
 #[derive(Subcommand, Debug)]
 pub enum UserAction {
-    Login,
+    Login {
+        /// Copy the id token to the clipboard after logging in
+        #[arg(long)]
+        copy_token: bool,
+        /// Copy the login URL to the clipboard if the browser cannot be opened
+        #[arg(long)]
+        copy_url: bool,
+        /// Print the login URL instead of opening a browser (e.g. over SSH)
+        #[arg(long)]
+        no_browser: bool,
+        #[command(flatten)]
+        callback: login::CallbackOptions,
+    },
     Logout,
-    Status,
+    Status {
+        /// Print the user profile as JSON
+        #[arg(long)]
+        json: bool,
+        /// Warn when the session expires within this many seconds
+        #[arg(long, value_name = "SECS", default_value_t = status::DEFAULT_EXPIRY_WARNING.as_secs())]
+        expiry_warning_secs: u64,
+    },
+    /// Print the saved login tokens to move them to another machine (sensitive!)
+    Export {
+        /// Encode the tokens as a single base64 line
+        #[arg(long)]
+        base64: bool,
+        /// Write the export to this file instead of stdout
+        #[arg(long, short = 'o', value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Save login tokens produced by `user export` on another machine
+    Import {
+        /// File holding the export, read from stdin when omitted
+        #[arg(value_name = "FILE")]
+        input: Option<PathBuf>,
+    },
 }
 
 impl UserAction {
+    /// Examples shown after `user --help`
+    pub fn help_examples() -> String {
+        let api_base_url = std::env::var("AGNOSTIC_API_BASE_URL")
+            .unwrap_or_else(|_| crate::utils::app::DEFAULT_API_BASE_URL.to_string());
+        format!(
+            "Examples:\n  \
+             ag user login                              # signs in on {}\n  \
+             ag user status --json\n  \
+             ag user export --base64 -o auth.txt       # then `ag user import auth.txt` elsewhere\n  \
+             ag user logout",
+            api_base_url
+        )
+    }
+
     pub async fn handle(self, config: &AppConfig) {
         match self {
-            Self::Login => self
-                .handle_login(config)
-                .await
-                .expect("Unable to handle login command"),
+            Self::Login { .. } => {
+                let outcome = self
+                    .handle_login(config)
+                    .await
+                    .expect("Unable to handle login command");
+                if outcome == login::LoginOutcome::Aborted {
+                    std::process::exit(1);
+                }
+            }
             Self::Logout => self
                 .handle_logout(config)
                 .await
                 .expect("Unable to handle logout command"),
-            Self::Status => self
+            Self::Status { .. } => self
                 .handle_status(config)
                 .await
                 .expect("Unable to handle status command"),
+            Self::Export { .. } => {
+                if let Err(e) = self.handle_export(config).await {
+                    eprintln!("Error exporting auth tokens: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Self::Import { .. } => {
+                if let Err(e) = self.handle_import(config).await {
+                    eprintln!("Error importing auth tokens: {}", e);
+                    std::process::exit(1);
+                }
+            }
         }
     }
 }
@@ -0,0 +1,112 @@
+use std::io::IsTerminal;
+
+use reqwest::Client;
+
+use super::{UserAction, login::LoginOutcome};
+use crate::utils::{
+    AppConfig, AuthTokens, app::auth::AuthTokenError, confirm, ensure_valid_tokens,
+};
+
+/// How a command recovers from failing to get valid tokens
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthRecovery {
+    /// Offer to run the login flow right away
+    Relogin,
+    /// Print this message and give up
+    Instruct(String),
+}
+
+/// Picks the recovery for `error`, only offering to log in again from a terminal
+pub fn recovery_for(error: &AuthTokenError, interactive: bool) -> AuthRecovery {
+    match error {
+        AuthTokenError::CorruptTokenFile { .. } => AuthRecovery::Instruct(error.to_string()),
+        AuthTokenError::RefreshRejected { .. } if interactive => AuthRecovery::Relogin,
+        AuthTokenError::RefreshRejected { .. } => {
+            AuthRecovery::Instruct(format!("{}. Run `user login` to sign in again.", error))
+        }
+        _ => AuthRecovery::Instruct(
+            "Authentication required. Please run `user login` first.".to_string(),
+        ),
+    }
+}
+
+/// Loads valid auth tokens for a command, telling the user how to log in when there are none
+///
+/// When the platform rejected the refresh token and the CLI runs in a
+/// terminal, the user is offered to log in again without leaving the command.
+pub async fn authenticate(config: &AppConfig, client: &Client) -> Option<AuthTokens> {
+    let error = match ensure_valid_tokens(config, client).await {
+        Ok(tokens) => return Some(tokens),
+        Err(error) => error,
+    };
+    if config.verbose && !matches!(error, AuthTokenError::CorruptTokenFile { .. }) {
+        eprintln!("{}", error);
+    }
+
+    match recovery_for(&error, std::io::stdin().is_terminal()) {
+        AuthRecovery::Relogin => relogin(config, &error).await,
+        AuthRecovery::Instruct(message) => {
+            println!("{}", message);
+            None
+        }
+    }
+}
+
+/// Asks to log in again after a rejected refresh, returning the new tokens
+async fn relogin(config: &AppConfig, error: &AuthTokenError) -> Option<AuthTokens> {
+    let message = format!("{}. Log in again now?", error);
+    if !confirm(config, &message).unwrap_or(false) {
+        println!("Run `user login` to sign in again.");
+        return None;
+    }
+
+    let login = UserAction::Login {
+        copy_token: false,
+        copy_url: false,
+        no_browser: false,
+        callback: Default::default(),
+    };
+    match login.handle_login(config).await {
+        Ok(LoginOutcome::Authenticated) => {}
+        Ok(LoginOutcome::Aborted) => return None,
+        Err(e) => {
+            eprintln!("Login failed: {}", e);
+            return None;
+        }
+    }
+    match AuthTokens::load_from_config(config) {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_recovery_for_rejected_refresh() {
+        let rejected = AuthTokenError::RefreshRejected {
+            status: StatusCode::UNAUTHORIZED,
+        };
+        assert_eq!(recovery_for(&rejected, true), AuthRecovery::Relogin);
+
+        let AuthRecovery::Instruct(message) = recovery_for(&rejected, false) else {
+            panic!("a non-interactive run cannot log in again");
+        };
+        assert!(message.contains("expired or was revoked"));
+        assert!(message.contains("user login"));
+
+        // Other failures keep the generic instruction, even in a terminal
+        assert_eq!(
+            recovery_for(&AuthTokenError::NoAuthTokens, true),
+            AuthRecovery::Instruct(
+                "Authentication required. Please run `user login` first.".to_string()
+            )
+        );
+    }
+}
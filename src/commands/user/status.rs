@@ -1,46 +1,312 @@
 use std::error::Error;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use reqwest::Client;
+use serde::Serialize;
 
 use crate::{
     commands::{UserAction, user::user::User},
-    utils::{AppConfig, ensure_valid_tokens},
+    utils::{AppConfig, AuthTokens, CliError, api_get, app::output::print_json},
 };
-use reqwest::Client;
+
+/// How long before expiry `user status` warns unless `--expiry-warning-secs` is given
+pub const DEFAULT_EXPIRY_WARNING: Duration = Duration::from_secs(10 * 60);
+
+/// Profile shown by `user status`
+#[derive(Debug)]
+enum Profile {
+    /// Fetched from the API just now
+    Fresh(User),
+    /// Read from the cache because the API could not be reached
+    Cached(User),
+}
+
+impl Profile {
+    fn user(&self) -> &User {
+        match self {
+            Self::Fresh(user) | Self::Cached(user) => user,
+        }
+    }
+}
+
+/// Where the last profile fetched by `user status` is cached
+pub(crate) fn profile_cache_path(config: &AppConfig) -> PathBuf {
+    config.layout().profile_file()
+}
+
+fn load_cached_profile(config: &AppConfig) -> Result<Option<User>, Box<dyn Error>> {
+    let path = profile_cache_path(config);
+    if !config.fs.exists(&path)? {
+        return Ok(None);
+    }
+    let content = config.fs.read_to_string(&path)?;
+    Ok(Some(serde_json::from_str(&content)?))
+}
+
+fn save_cached_profile(config: &AppConfig, user: &User) -> Result<(), Box<dyn Error>> {
+    config.fs.write(
+        &profile_cache_path(config),
+        serde_json::to_string_pretty(user)?.as_bytes(),
+    )?;
+    Ok(())
+}
+
+/// Fetches the user profile, falling back to the cache when the API is unreachable
+///
+/// Only transport failures (connection refused, timeouts, ...) fall back; an
+/// HTTP error from the server is still reported. Returns `None` after printing
+/// a message when the server rejects the token.
+async fn fetch_profile(
+    config: &AppConfig,
+    client: &Client,
+    id_token: &str,
+) -> Result<Option<Profile>, Box<dyn Error>> {
+    let user: User = match api_get(client, &config.api_url("user"), id_token).await {
+        Ok(user) => user,
+        Err(e @ CliError::Auth { .. }) => {
+            println!("Authentication failed. Please try to log in again.");
+            println!("Request id: {}", e.request_id());
+            return Ok(None);
+        }
+        Err(e @ CliError::Transport(_)) => match load_cached_profile(config)? {
+            Some(user) => {
+                if config.verbose {
+                    eprintln!("{}", e);
+                }
+                return Ok(Some(Profile::Cached(user)));
+            }
+            None => return Err(e.into()),
+        },
+        Err(e) => return Err(e.into()),
+    };
+    if let Err(e) = save_cached_profile(config, &user)
+        && config.verbose
+    {
+        eprintln!("Could not cache user profile: {}", e);
+    }
+
+    Ok(Some(Profile::Fresh(user)))
+}
+
+/// `user status --json` output: the profile and whether the session is about to expire
+#[derive(Serialize)]
+struct StatusJson<'a> {
+    #[serde(flatten)]
+    user: &'a User,
+    session_expiring: bool,
+}
+
+/// Time left on the session when it ends within `window`
+fn expiring_within(tokens: &AuthTokens, window: Duration) -> Option<Duration> {
+    if !tokens.needs_refresh(window).ok()? {
+        return None;
+    }
+    let expires_at = tokens.expires_at().ok()?;
+    Some(
+        expires_at
+            .duration_since(SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
+/// Warning printed when the session ends in `remaining`
+fn expiry_warning(remaining: Duration) -> String {
+    let when = match remaining.as_secs() / 60 {
+        0 => "less than a minute".to_string(),
+        1 => "1 minute".to_string(),
+        minutes => format!("{} minutes", minutes),
+    };
+    format!(
+        "Warning: your session expires in {}. Run `user login` to renew it before a long operation.",
+        when
+    )
+}
+
+/// Formats the profile for the terminal
+fn render_profile(profile: &Profile) -> String {
+    let user = profile.user();
+    let mut lines = vec![
+        "User Status".to_string(),
+        "=============".to_string(),
+        String::new(),
+    ];
+    if let Profile::Cached(_) = profile {
+        lines.push("(cached, possibly stale)".to_string());
+    }
+    lines.push("User logged in as:".to_string());
+    lines.push(format!("  id: {}", user.id()));
+    lines.push(format!("  email: {}", user.email()));
+    lines.push(format!("  username: {}", user.username()));
+    lines.join("\n")
+}
 
 impl UserAction {
     pub(super) async fn handle_status(self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
-        let client = Client::new();
-        let auth_tokens = match ensure_valid_tokens(config, &client).await {
-            Ok(tokens) => tokens,
-            Err(e) => {
-                if config.verbose {
-                    eprintln!("{}", e)
+        let Self::Status {
+            json,
+            expiry_warning_secs,
+        } = self
+        else {
+            unreachable!("handle_status called for another action");
+        };
+
+        let (profile, tokens) = if config.offline {
+            match load_cached_profile(config)? {
+                Some(user) => (
+                    Profile::Cached(user),
+                    AuthTokens::load_from_config(config).ok().flatten(),
+                ),
+                None => {
+                    println!("No cached profile. Run `user status` without --offline first.");
+                    return Ok(());
                 }
-                println!("Authentication required. Please run `user login` first.");
+            }
+        } else {
+            let client = config.http_client()?;
+            let Some(auth_tokens) = super::authenticate(config, &client).await else {
                 return Ok(());
+            };
+
+            match fetch_profile(config, &client, auth_tokens.id_token()).await? {
+                Some(profile) => (profile, Some(auth_tokens)),
+                None => return Ok(()),
             }
         };
+        let expiring = tokens
+            .as_ref()
+            .and_then(|tokens| expiring_within(tokens, Duration::from_secs(expiry_warning_secs)));
 
-        let response = client
-            .get("https://app.agnostic.tech/api/user")
-            .bearer_auth(auth_tokens.id_token())
-            .send()
-            .await?;
+        if json {
+            if let Profile::Cached(_) = profile {
+                eprintln!("(cached, possibly stale)");
+            }
+            return print_json(
+                config,
+                &StatusJson {
+                    user: profile.user(),
+                    session_expiring: expiring.is_some(),
+                },
+            );
+        }
 
-        if response.status() == reqwest::StatusCode::UNAUTHORIZED {
-            println!("Authentication failed. Please try to log in again.");
-            return Ok(());
+        println!("{}", render_profile(&profile));
+        if let Some(remaining) = expiring {
+            println!();
+            println!("{}", expiry_warning(remaining));
         }
 
-        let user: User = response.json().await?;
+        Ok(())
+    }
+}
 
-        println!("User Status");
-        println!("=============");
-        println!();
-        println!("User logged in as:");
-        println!("  id: {}", user.id());
-        println!("  email: {}", user.email());
-        println!("  username: {}", user.username());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
 
-        Ok(())
+    const PROFILE: &str = r#"{"id": 7, "username": "ada", "email": "ada@example.com"}"#;
+
+    fn config_for(temp_dir: &TempDir, api_base_url: &str) -> AppConfig {
+        fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        AppConfig::new(temp_dir.path().to_path_buf()).with_api_base_url(api_base_url)
+    }
+
+    /// A local address with nothing listening on it
+    fn unreachable_base_url() -> String {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}", listener.local_addr().unwrap())
+    }
+
+    /// Tokens whose id token expires `expires_in` from now
+    fn tokens_expiring_in(expires_in: Duration) -> AuthTokens {
+        let exp = (SystemTime::now() + expires_in)
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let id_token = jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "exp": exp }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test"),
+        )
+        .unwrap();
+        serde_json::from_value(serde_json::json!({ "access_token": "a", "id_token": id_token }))
+            .unwrap()
+    }
+
+    #[test]
+    fn test_expiry_warning_window() {
+        let soon = tokens_expiring_in(Duration::from_secs(4 * 60 + 30));
+        let remaining = expiring_within(&soon, DEFAULT_EXPIRY_WARNING).unwrap();
+        assert!(expiry_warning(remaining).contains("expires in 4 minutes"));
+
+        let later = tokens_expiring_in(Duration::from_secs(2 * 60 * 60));
+        assert_eq!(expiring_within(&later, DEFAULT_EXPIRY_WARNING), None);
+        assert!(expiring_within(&later, Duration::from_secs(3 * 60 * 60)).is_some());
+
+        let user: User = serde_json::from_str(PROFILE).unwrap();
+        let json = serde_json::to_value(StatusJson {
+            user: &user,
+            session_expiring: true,
+        })
+        .unwrap();
+        assert_eq!(json["email"], "ada@example.com");
+        assert_eq!(json["session_expiring"], true);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_api_falls_back_to_cached_profile() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for(&temp_dir, &unreachable_base_url());
+        fs::write(profile_cache_path(&config), PROFILE).unwrap();
+
+        let profile = fetch_profile(&config, &Client::new(), "token")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(profile, Profile::Cached(_)));
+        let output = render_profile(&profile);
+        assert!(output.contains("(cached, possibly stale)"));
+        assert!(output.contains("email: ada@example.com"));
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_api_without_cache_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for(&temp_dir, &unreachable_base_url());
+
+        assert!(
+            fetch_profile(&config, &Client::new(), "token")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_fetched_profile_is_cached() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/user"))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(PROFILE, "application/json"))
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let config = config_for(&temp_dir, &server.uri());
+
+        let profile = fetch_profile(&config, &Client::new(), "token")
+            .await
+            .unwrap()
+            .unwrap();
+
+        assert!(matches!(profile, Profile::Fresh(_)));
+        assert!(!render_profile(&profile).contains("cached"));
+        let cached = load_cached_profile(&config).unwrap().unwrap();
+        assert_eq!(cached.email(), "ada@example.com");
     }
 }
@@ -0,0 +1,225 @@
+use std::error::Error;
+use std::io::Read;
+
+use base64::{Engine, engine::general_purpose::STANDARD};
+
+use crate::{
+    commands::UserAction,
+    utils::{AppConfig, AuthTokens},
+};
+
+const EXPORT_BEGIN: &str = "-----BEGIN AGNOSTIC AUTH TOKENS-----";
+const EXPORT_END: &str = "-----END AGNOSTIC AUTH TOKENS-----";
+
+const SENSITIVE_WARNING: &str = "WARNING: this contains your login tokens. Anyone holding it can \
+     act as you on the platform until the tokens expire. Only move it over a channel you trust \
+     and delete any copy once imported.";
+
+/// Wraps the tokens between markers, as pretty JSON or as a single base64 line
+pub fn export_tokens(tokens: &AuthTokens, base64: bool) -> Result<String, Box<dyn Error>> {
+    let json = serde_json::to_string_pretty(tokens)?;
+    let body = if base64 { STANDARD.encode(json) } else { json };
+    Ok(format!("{}\n{}\n{}\n", EXPORT_BEGIN, body, EXPORT_END))
+}
+
+/// Reads tokens produced by [`export_tokens`]
+///
+/// The markers are optional, so a bare `auth.json` is accepted as well.
+pub fn parse_exported_tokens(text: &str) -> Result<AuthTokens, Box<dyn Error>> {
+    let mut body = text.trim();
+    if let Some(rest) = body.strip_prefix(EXPORT_BEGIN) {
+        body = rest
+            .trim_end()
+            .strip_suffix(EXPORT_END)
+            .ok_or("Export is truncated: missing end marker")?
+            .trim();
+    }
+
+    let json = if body.starts_with('{') {
+        body.to_string()
+    } else {
+        let decoded = STANDARD
+            .decode(body.split_whitespace().collect::<String>())
+            .map_err(|e| format!("Export is neither JSON nor base64: {}", e))?;
+        String::from_utf8(decoded).map_err(|_| "Decoded export is not valid UTF-8")?
+    };
+
+    let tokens: AuthTokens =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid auth tokens: {}", e))?;
+    if !tokens.is_valid_token_type() {
+        return Err(format!("Unsupported token type: {}", tokens.token_type()).into());
+    }
+    Ok(tokens)
+}
+
+impl UserAction {
+    pub(super) async fn handle_export(self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
+        let Self::Export { base64, output } = self else {
+            unreachable!("handle_export called with another action");
+        };
+
+        let Some(tokens) = AuthTokens::load_from_config(config)? else {
+            return Err("Not logged in, nothing to export".into());
+        };
+        let exported = export_tokens(&tokens, base64)?;
+
+        eprintln!("{}", SENSITIVE_WARNING);
+        match output {
+            Some(path) => {
+                config.fs.write_private(&path, exported.as_bytes())?;
+                eprintln!("Auth tokens exported to {}", path.display());
+            }
+            None => print!("{}", exported),
+        }
+        Ok(())
+    }
+
+    pub(super) async fn handle_import(self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
+        let Self::Import { input } = self else {
+            unreachable!("handle_import called with another action");
+        };
+
+        let text = match &input {
+            Some(path) => config.fs.read_to_string(path)?,
+            None => {
+                let mut text = String::new();
+                std::io::stdin().read_to_string(&mut text)?;
+                text
+            }
+        };
+        import_tokens(config, &text)?;
+
+        println!("Auth tokens imported");
+        eprintln!("{}", SENSITIVE_WARNING);
+        Ok(())
+    }
+}
+
+/// Validates exported tokens and saves them as the current login
+///
+/// The cached profile is dropped since it may belong to another account.
+fn import_tokens(config: &AppConfig, text: &str) -> Result<(), Box<dyn Error>> {
+    let tokens = parse_exported_tokens(text)?;
+    tokens.save_to_config(config)?;
+
+    let profile_json = super::status::profile_cache_path(config);
+    if config.fs.exists(&profile_json)? {
+        config.fs.remove(&profile_json, &config.agnostic_dir)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::app::auth::auth_tokens_path;
+    use crate::utils::fs::MemoryFs;
+    use std::path::PathBuf;
+    use std::sync::Arc;
+
+    const AUTH_JSON: &str = r#"{
+        "access_token": "access",
+        "id_token": "id",
+        "token_type": "Bearer",
+        "refresh_token": "refresh",
+        "expires_in": 3600
+    }"#;
+
+    fn memory_config() -> AppConfig {
+        AppConfig::new(PathBuf::from("/home/user/.agnostic")).with_fs(Arc::new(MemoryFs::default()))
+    }
+
+    fn as_value(tokens: &AuthTokens) -> serde_json::Value {
+        serde_json::to_value(tokens).unwrap()
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let tokens: AuthTokens = serde_json::from_str(AUTH_JSON).unwrap();
+
+        for base64 in [false, true] {
+            let exported = export_tokens(&tokens, base64).unwrap();
+            assert!(exported.starts_with(EXPORT_BEGIN));
+            assert_eq!(exported.contains("\"id_token\""), !base64);
+
+            let imported = parse_exported_tokens(&exported).unwrap();
+            assert_eq!(as_value(&imported), as_value(&tokens));
+        }
+
+        // A plain auth.json copied by hand is accepted too
+        let imported = parse_exported_tokens(AUTH_JSON).unwrap();
+        assert_eq!(as_value(&imported), as_value(&tokens));
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_tokens() {
+        let other_type = AUTH_JSON.replace("Bearer", "MAC");
+        assert!(parse_exported_tokens(&other_type).is_err());
+        assert!(parse_exported_tokens("not base64 !").is_err());
+        assert!(parse_exported_tokens(&format!("{}\n{{}}", EXPORT_BEGIN)).is_err());
+        assert!(parse_exported_tokens("{\"id_token\": \"id\"}").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_then_import_on_another_machine() {
+        let source = memory_config();
+        source
+            .fs
+            .write(&auth_tokens_path(&source), AUTH_JSON.as_bytes())
+            .unwrap();
+        let export_path = PathBuf::from("/tmp/auth-export.txt");
+        UserAction::Export {
+            base64: true,
+            output: Some(export_path.clone()),
+        }
+        .handle_export(&source)
+        .await
+        .unwrap();
+        let exported = source.fs.read_to_string(&export_path).unwrap();
+
+        let target = memory_config();
+        let stale_profile = super::super::status::profile_cache_path(&target);
+        target.fs.write(&stale_profile, b"{}").unwrap();
+        import_tokens(&target, &exported).unwrap();
+
+        let original = AuthTokens::load_from_config(&source).unwrap().unwrap();
+        let imported = AuthTokens::load_from_config(&target).unwrap().unwrap();
+        assert_eq!(as_value(&imported), as_value(&original));
+        assert!(!target.fs.exists(&stale_profile).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_export_file_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().join(".agnostic"));
+        let auth_path = auth_tokens_path(&config);
+        std::fs::create_dir_all(auth_path.parent().unwrap()).unwrap();
+        std::fs::write(&auth_path, AUTH_JSON).unwrap();
+        // A file left world-readable by an earlier export is restricted too
+        let export_path = temp_dir.path().join("auth-export.txt");
+        std::fs::write(&export_path, "old").unwrap();
+        std::fs::set_permissions(&export_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+
+        UserAction::Export {
+            base64: false,
+            output: Some(export_path.clone()),
+        }
+        .handle_export(&config)
+        .await
+        .unwrap();
+
+        let mode = std::fs::metadata(&export_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        assert!(
+            std::fs::read_to_string(&export_path)
+                .unwrap()
+                .starts_with(EXPORT_BEGIN)
+        );
+    }
+}
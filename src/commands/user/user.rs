@@ -1,19 +1,46 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
 /// Agnostic User entity
-#[derive(Serialize, Deserialize)]
+///
+/// Unknown fields returned by the API are kept in `extra` rather than
+/// rejected, so backend additions never break `user status`.
+#[derive(Debug, Serialize, Deserialize)]
 pub struct User {
-    id: u8,
+    id: UserId,
     username: String,
     email: String,
-    #[serde(rename = "createdAt")]
+    #[serde(rename = "createdAt", alias = "created_at", default)]
     created_at: String,
-    #[serde(rename = "updatedAt")]
+    #[serde(rename = "updatedAt", alias = "updated_at", default)]
     updated_at: String,
+    /// Fields not modelled by the CLI, kept for debugging
+    #[serde(flatten)]
+    extra: HashMap<String, Value>,
+}
+
+/// User identifier, accepted either as a number or a string
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum UserId {
+    Number(u64),
+    Text(String),
+}
+
+impl fmt::Display for UserId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Number(id) => write!(f, "{}", id),
+            Self::Text(id) => write!(f, "{}", id),
+        }
+    }
 }
 
 impl User {
-    pub fn id(&self) -> &u8 {
+    pub fn id(&self) -> &UserId {
         &self.id
     }
 
@@ -24,4 +51,49 @@ impl User {
     pub fn email(&self) -> &str {
         &self.email
     }
+
+    /// Fields returned by the API that the CLI does not know about
+    #[allow(dead_code)]
+    pub fn extra(&self) -> &HashMap<String, Value> {
+        &self.extra
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_user_with_extra_fields() {
+        let user: User = serde_json::from_str(
+            r#"{
+                "id": 1024,
+                "username": "ada",
+                "email": "ada@example.com",
+                "createdAt": "2024-01-01T00:00:00Z",
+                "updatedAt": "2024-01-02T00:00:00Z",
+                "role": "admin",
+                "teams": [{"id": 7}]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(user.id(), &UserId::Number(1024));
+        assert_eq!(user.username(), "ada");
+        assert_eq!(user.extra()["role"], "admin");
+        assert!(user.extra().contains_key("teams"));
+        assert!(!user.extra().contains_key("createdAt"));
+    }
+
+    #[test]
+    fn test_deserialize_user_with_string_id_and_missing_dates() {
+        let user: User = serde_json::from_str(
+            r#"{"id": "usr_42", "username": "ada", "email": "ada@example.com"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(user.id().to_string(), "usr_42");
+        assert!(user.created_at.is_empty());
+        assert!(user.extra().is_empty());
+    }
 }
@@ -1,12 +1,22 @@
-use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{CommandFactory, FromArgMatches, Parser, Subcommand};
 
 mod commands;
 mod utils;
 use commands::{
     PipelineAction, ProjectAction, SystemAction, UserAction, handle_pipeline_command,
-    handle_project_command,
+    handle_project_command, with_help_examples,
 };
-use utils::app::{cleanup_app, initialize_app};
+use utils::BinarySelection;
+use utils::app::auth::parse_refresh_threshold;
+use utils::app::events::EventEmitter;
+use utils::app::logging::LogTarget;
+use utils::app::output::{ColorChoice, JsonStyle, apply_color_choice};
+use utils::app::{InitOptions, cleanup_app, initialize_app_with};
+use utils::bin::manager::parse_binary_name;
+use utils::net::{ClientOptions, DEFAULT_MAX_REDIRECTS, ProxySetting};
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
@@ -14,6 +24,91 @@ struct Args {
     #[arg(long, short = 'v', env = "VERBOSE")]
     verbose: bool,
 
+    /// Automatically confirm all prompts (for scripts and CI)
+    #[arg(long, short = 'y')]
+    yes: bool,
+
+    /// Base URL of the Agnostic platform, for self-hosted or staging deployments
+    #[arg(long, env = "AGNOSTIC_API_BASE_URL")]
+    api_base_url: Option<String>,
+
+    /// Trust an additional PEM root certificate (private CA of a self-hosted deployment)
+    #[arg(long, value_name = "PATH")]
+    ca_cert: Option<PathBuf>,
+
+    /// Disable TLS certificate verification (DANGEROUS, local testing only)
+    #[arg(long)]
+    insecure: bool,
+
+    /// Override the User-Agent sent with every request
+    #[arg(long, value_name = "UA")]
+    user_agent: Option<String>,
+
+    /// Send HTTP requests through this proxy instead of the one from the environment
+    #[arg(long, value_name = "URL")]
+    proxy: Option<String>,
+
+    /// Only use --proxy for binary and template downloads, reaching the API directly
+    #[arg(long, requires = "proxy")]
+    proxy_binary_downloads_only: bool,
+
+    /// Maximum number of HTTP redirects to follow per request, 0 to disable redirects
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_REDIRECTS)]
+    max_redirects: usize,
+
+    /// Print `--json` output on a single line instead of indented
+    #[arg(long)]
+    json_compact: bool,
+
+    /// Write `--json` output to this file (atomically) instead of stdout
+    #[arg(long, value_name = "PATH")]
+    output_file: Option<PathBuf>,
+
+    /// Skip optional network access such as connectivity checks
+    #[arg(long)]
+    offline: bool,
+
+    /// Disable colored output (also disabled by the NO_COLOR environment variable)
+    #[arg(long, conflicts_with = "force_color")]
+    no_color: bool,
+
+    /// Use colored output even when not writing to a terminal
+    #[arg(long)]
+    force_color: bool,
+
+    /// Skip downloading and verifying managed binaries at startup
+    #[arg(long)]
+    no_install: bool,
+
+    /// Only set up this binary at startup, repeatable (e.g. --only-binary s3fs)
+    #[arg(long, value_name = "NAME", value_parser = parse_binary_name, conflicts_with = "skip_binary")]
+    only_binary: Vec<String>,
+
+    /// Do not set up this binary at startup, repeatable
+    #[arg(long, value_name = "NAME", value_parser = parse_binary_name)]
+    skip_binary: Vec<String>,
+
+    /// Write the CLI log here instead of ~/.agnostic/logs/cli.log (`-` for stderr only)
+    #[arg(long, value_name = "PATH", env = "AGNOSTIC_LOG_FILE")]
+    log_file: Option<LogTarget>,
+
+    /// Refresh auth tokens when they expire within this many seconds (default 300)
+    #[arg(
+        long = "token-refresh-threshold-secs",
+        env = "AGNOSTIC_TOKEN_REFRESH_THRESHOLD_SECS",
+        value_name = "SECS",
+        value_parser = parse_refresh_threshold
+    )]
+    token_refresh_threshold: Option<Duration>,
+
+    /// Emit JSON Lines lifecycle events on stderr for wrapping tools
+    #[arg(long)]
+    events: bool,
+
+    /// Append the JSON Lines events to this file instead of stderr (implies --events)
+    #[arg(long, value_name = "PATH")]
+    events_file: Option<PathBuf>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -44,10 +139,72 @@ enum Commands {
 
 #[tokio::main]
 async fn main() {
-    let args = Args::parse();
+    let matches = with_help_examples(Args::command()).get_matches();
+    let args = Args::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+
+    apply_color_choice(if args.force_color {
+        ColorChoice::Always
+    } else if args.no_color {
+        ColorChoice::Never
+    } else {
+        ColorChoice::Auto
+    });
+
+    if args.insecure {
+        eprintln!(
+            "WARNING: TLS certificate verification is disabled (--insecure). \
+             Connections can be intercepted; never use this against production."
+        );
+    }
+    // Downloads always go through --proxy; the API skips it with --proxy-binary-downloads-only
+    let download_client = ClientOptions {
+        ca_cert: args.ca_cert.clone(),
+        insecure: args.insecure,
+        user_agent: args.user_agent.clone(),
+        max_redirects: args.max_redirects,
+        log_redirects: args.verbose,
+        proxy: args
+            .proxy
+            .clone()
+            .map_or(ProxySetting::System, ProxySetting::Url),
+    };
+    let api_client = if args.proxy_binary_downloads_only {
+        ClientOptions {
+            proxy: ProxySetting::Disabled,
+            ..download_client.clone()
+        }
+    } else {
+        download_client.clone()
+    };
+
+    let events = if let Some(path) = &args.events_file {
+        match EventEmitter::file(path) {
+            Ok(events) => events,
+            Err(e) => {
+                eprintln!("Error opening events file {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    } else if args.events {
+        EventEmitter::stderr()
+    } else {
+        EventEmitter::default()
+    };
 
     // Initialize the application environment
-    let config = match initialize_app().await {
+    let init_options = InitOptions {
+        install_binaries: !args.no_install,
+        binaries: BinarySelection {
+            only: args.only_binary.clone(),
+            skip: args.skip_binary.clone(),
+        },
+        log_file: args.log_file.clone(),
+        verbose: args.verbose,
+        download_client: download_client.clone(),
+        events,
+        ..Default::default()
+    };
+    let mut config = match initialize_app_with(&init_options).await {
         Ok(config) => {
             if args.verbose {
                 println!("Application initialized successfully");
@@ -63,10 +220,38 @@ async fn main() {
         }
     };
 
+    if args.yes {
+        config = config.with_assume_yes();
+    }
+
+    config = config
+        .with_client_options(api_client)
+        .with_download_client_options(download_client);
+
+    if args.json_compact {
+        config = config.with_json_style(JsonStyle::Compact);
+    }
+
+    if let Some(path) = args.output_file.clone() {
+        config = config.with_output_file(path);
+    }
+
+    if args.offline {
+        config = config.with_offline();
+    }
+
+    if let Some(threshold) = args.token_refresh_threshold {
+        config = config.with_token_refresh_threshold(threshold);
+    }
+
+    if let Some(api_base_url) = &args.api_base_url {
+        config = config.with_api_base_url(api_base_url);
+    }
+
     // Handle the command
     match args.command {
-        Commands::Project { action } => handle_project_command(action).await,
-        Commands::Pipeline { action } => handle_pipeline_command(action).await,
+        Commands::Project { action } => handle_project_command(action, &config).await,
+        Commands::Pipeline { action } => handle_pipeline_command(action, &config).await,
         Commands::System { action } => action.handle(&config).await,
         Commands::User { action } => action.handle(&config).await,
     };
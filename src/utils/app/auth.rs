@@ -1,47 +1,92 @@
 use std::{
     collections::HashMap,
     error::Error,
-    fs,
-    path::Path,
+    path::PathBuf,
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
 use jsonwebtoken::dangerous::insecure_decode;
-use reqwest::Client;
+use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
 
-use crate::utils::AppConfig;
+use crate::utils::app::events::Event;
+use crate::utils::net::api::{API_TIMEOUT, MAX_RESPONSE_BYTES, read_body_limited};
+use crate::utils::net::client::response_request_id;
+use crate::utils::{ApiError, AppConfig, send_api_request};
 
+/// How long before expiry tokens are refreshed unless configured otherwise
+pub const DEFAULT_REFRESH_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+
+/// Largest accepted refresh threshold; id tokens rarely live longer than this
+pub const MAX_REFRESH_THRESHOLD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Parses `--token-refresh-threshold-secs`, rejecting values above [`MAX_REFRESH_THRESHOLD`]
+pub fn parse_refresh_threshold(s: &str) -> Result<Duration, String> {
+    let secs: u64 = s
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a non-negative number of seconds", s))?;
+    let threshold = Duration::from_secs(secs);
+    if threshold > MAX_REFRESH_THRESHOLD {
+        return Err(format!(
+            "refresh threshold must be at most {} seconds",
+            MAX_REFRESH_THRESHOLD.as_secs()
+        ));
+    }
+    Ok(threshold)
+}
+
+/// Tokens returned by the login callback and the refresh endpoint
+///
+/// Unknown fields (e.g. `expires_in`, `scope`) are preserved in `extra` so they
+/// survive a save/load round trip instead of failing deserialization.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct AuthTokens {
+    #[serde(alias = "accessToken")]
     access_token: String,
+    #[serde(alias = "idToken")]
     id_token: String,
+    #[serde(alias = "tokenType", default = "default_token_type")]
     token_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(alias = "refreshToken", skip_serializing_if = "Option::is_none")]
     refresh_token: Option<String>,
+    #[serde(flatten)]
+    extra: HashMap<String, serde_json::Value>,
+}
+
+fn default_token_type() -> String {
+    "Bearer".to_string()
+}
+
+/// Where the login callback saves the auth tokens
+pub fn auth_tokens_path(config: &AppConfig) -> PathBuf {
+    config.layout().auth_file()
 }
 
 impl AuthTokens {
-    pub fn load_from_config(config: &AppConfig) -> Result<Option<Self>, Box<dyn Error>> {
-        let auth_json = config.agnostic_dir.join("user/auth.json");
-        if !auth_json.try_exists()? {
+    /// Loads the saved tokens, distinguishing a missing file from a corrupt one
+    pub fn load_from_config(config: &AppConfig) -> Result<Option<Self>, AuthTokenError> {
+        let auth_json = auth_tokens_path(config);
+        if !config.fs.exists(&auth_json)? {
             return Ok(None);
         }
 
-        let tokens = AuthTokens::load(auth_json)?;
+        let content = config.fs.read_to_string(&auth_json)?;
+        let tokens =
+            serde_json::from_str(&content).map_err(|source| AuthTokenError::CorruptTokenFile {
+                path: auth_json,
+                source,
+            })?;
 
         Ok(Some(tokens))
     }
 
-    fn load<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
-        let content = fs::read_to_string(path)?;
-        let tokens = serde_json::from_str(&content)?;
-        Ok(tokens)
-    }
-
-    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+    /// Saves the tokens where [`AuthTokens::load_from_config`] finds them
+    pub fn save_to_config(&self, config: &AppConfig) -> Result<(), Box<dyn Error>> {
         let json = serde_json::to_string_pretty(&self)?;
-        fs::write(path, &json)?;
+        config
+            .fs
+            .write(&auth_tokens_path(config), json.as_bytes())?;
         Ok(())
     }
 
@@ -77,7 +122,11 @@ impl AuthTokens {
         Ok(now + threshold >= expires_at)
     }
 
-    pub async fn refresh(&mut self, client: &Client) -> Result<(), AuthTokenError> {
+    pub async fn refresh(
+        &mut self,
+        client: &Client,
+        refresh_url: &str,
+    ) -> Result<(), AuthTokenError> {
         let refresh_token = self
             .refresh_token
             .as_ref()
@@ -86,16 +135,21 @@ impl AuthTokens {
         let mut body = HashMap::new();
         body.insert("refresh_token", refresh_token);
 
-        let response = client
-            .post("https://app.agnostic.tech/api/refresh_token")
-            .json(&body)
-            .send()
-            .await?;
+        let response = send_api_request(client.post(refresh_url).json(&body).timeout(API_TIMEOUT))
+            .await
+            .map_err(|e| match e.status() {
+                Some(status @ (StatusCode::BAD_REQUEST | StatusCode::UNAUTHORIZED)) => {
+                    AuthTokenError::RefreshRejected { status }
+                }
+                _ => AuthTokenError::HttpFailed(e),
+            })?;
+        let request_id = response_request_id(&response);
 
-        let new_tokens: AuthTokens = response
-            .json()
+        let body = read_body_limited(response, MAX_RESPONSE_BYTES, &request_id)
             .await
             .map_err(|e| AuthTokenError::InvalidResponse(e.to_string()))?;
+        let new_tokens: AuthTokens = serde_json::from_slice(&body)
+            .map_err(|e| AuthTokenError::InvalidResponse(e.to_string()))?;
 
         *self = new_tokens;
 
@@ -103,25 +157,33 @@ impl AuthTokens {
     }
 }
 
-/// check if needs refresh soon (5 min)
+/// Loads the saved tokens, refreshing them when they expire within
+/// `config.token_refresh_threshold` (5 minutes by default)
 pub async fn ensure_valid_tokens(
     config: &AppConfig,
     client: &Client,
 ) -> Result<AuthTokens, AuthTokenError> {
-    let result = AuthTokens::load_from_config(config).map_err(move |e| {
-        if config.verbose {
-            eprintln!("{}", e);
-        }
-        AuthTokenError::NoAuthTokens
-    })?;
+    let mut tokens = AuthTokens::load_from_config(config)?.ok_or(AuthTokenError::NoAuthTokens)?;
 
-    let mut tokens = result.ok_or(AuthTokenError::NoAuthTokens)?;
-
-    if tokens.needs_refresh(Duration::from_secs(5 * 60))? {
-        tokens.refresh(client).await?;
+    if tokens.needs_refresh(config.token_refresh_threshold)? {
+        if let Err(e) = tokens
+            .refresh(client, &config.api_url("refresh_token"))
+            .await
+        {
+            config.log(&format!("Token refresh failed: {}", e));
+            return Err(e);
+        }
+        config.log("Auth tokens refreshed");
         tokens
-            .save(config.agnostic_dir.join("user/auth.json"))
+            .save_to_config(config)
             .map_err(|e| AuthTokenError::InvalidResponse(e.to_string()))?;
+        config.events.emit(Event::AuthRefreshed {
+            expires_at: tokens
+                .expires_at()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs()),
+        });
     }
 
     Ok(tokens)
@@ -138,6 +200,16 @@ struct IdTokenClaims {
 pub enum AuthTokenError {
     #[error("Missing auth tokens")]
     NoAuthTokens,
+    #[error("Unable to read auth tokens: {0}")]
+    ReadFailed(#[from] std::io::Error),
+    #[error(
+        "Auth token file {} is corrupt ({source}). Run `user logout` then `user login` to sign in again.",
+        path.display()
+    )]
+    CorruptTokenFile {
+        path: PathBuf,
+        source: serde_json::Error,
+    },
     #[error("Missing refresh token")]
     NoRefreshToken,
     #[error("JWT decode failed: {0}")]
@@ -145,7 +217,230 @@ pub enum AuthTokenError {
     #[error("Token already expired")]
     AlreadyExpired,
     #[error("Refresh request failed: {0}")]
-    HttpFailed(#[from] reqwest::Error),
+    HttpFailed(#[from] ApiError),
+    /// The platform refused the refresh token (HTTP 400/401): it expired or was revoked
+    #[error("Your session expired or was revoked (refresh token rejected with HTTP {status})")]
+    RefreshRejected { status: StatusCode },
     #[error("Invalid refresh response: {0}")]
     InvalidResponse(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::app::logging::LogTarget;
+    use crate::utils::fs::MemoryFs;
+    use std::fs;
+    use std::sync::Arc;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn sample_tokens() -> AuthTokens {
+        AuthTokens {
+            access_token: "access".to_string(),
+            id_token: "id".to_string(),
+            token_type: "Bearer".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            extra: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_deserialize_tokens_with_extra_fields() {
+        let tokens: AuthTokens = serde_json::from_str(
+            r#"{
+                "access_token": "access",
+                "id_token": "id",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+                "scope": "openid profile"
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(tokens.access_token(), "access");
+        assert!(tokens.refresh_token.is_none());
+        assert_eq!(tokens.extra["expires_in"], 3600);
+
+        // Unknown fields survive a save/load round trip
+        let config = AppConfig::new(PathBuf::from("/home/user/.agnostic"))
+            .with_fs(Arc::new(MemoryFs::default()));
+        tokens.save_to_config(&config).unwrap();
+        let reloaded = AuthTokens::load_from_config(&config).unwrap().unwrap();
+        assert_eq!(reloaded.extra["scope"], "openid profile");
+    }
+
+    #[test]
+    fn test_deserialize_tokens_with_camel_case_and_missing_type() {
+        let tokens: AuthTokens = serde_json::from_str(
+            r#"{"accessToken": "access", "idToken": "id", "refreshToken": "refresh"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(tokens.id_token(), "id");
+        assert_eq!(tokens.token_type(), "Bearer");
+        assert!(tokens.is_valid_token_type());
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        assert!(tokens.extra.is_empty());
+    }
+
+    #[test]
+    fn test_load_missing_token_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+
+        assert!(AuthTokens::load_from_config(&config).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_corrupt_token_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        fs::write(
+            temp_dir.path().join("user/auth.json"),
+            "{\"access_token\": \"tru",
+        )
+        .unwrap();
+
+        let err = AuthTokens::load_from_config(&config).unwrap_err();
+        assert!(matches!(err, AuthTokenError::CorruptTokenFile { .. }));
+        assert!(err.to_string().contains("user login"));
+    }
+
+    #[tokio::test]
+    async fn test_ensure_valid_tokens_reports_corrupt_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let config = AppConfig::new(temp_dir.path().to_path_buf());
+        fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        fs::write(temp_dir.path().join("user/auth.json"), "not json").unwrap();
+
+        let err = ensure_valid_tokens(&config, &Client::new())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthTokenError::CorruptTokenFile { .. }));
+    }
+
+    #[tokio::test]
+    async fn test_refresh_uses_configured_base_url() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/refresh_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "new-access",
+                "id_token": "new-id",
+                "token_type": "Bearer"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+        let mut tokens = sample_tokens();
+        tokens
+            .refresh(&Client::new(), &config.api_url("refresh_token"))
+            .await
+            .unwrap();
+
+        assert_eq!(tokens.id_token(), "new-id");
+    }
+
+    #[tokio::test]
+    async fn test_rejected_refresh_token() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/refresh_token"))
+            .respond_with(ResponseTemplate::new(401))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/api/unavailable"))
+            .respond_with(ResponseTemplate::new(503))
+            .mount(&server)
+            .await;
+        let config =
+            AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_api_base_url(&server.uri());
+
+        let err = sample_tokens()
+            .refresh(&Client::new(), &config.api_url("refresh_token"))
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            AuthTokenError::RefreshRejected {
+                status: StatusCode::UNAUTHORIZED
+            }
+        ));
+
+        // A server failure says nothing about the refresh token itself
+        let err = sample_tokens()
+            .refresh(&Client::new(), &config.api_url("unavailable"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, AuthTokenError::HttpFailed(_)));
+    }
+
+    /// An id token expiring `expires_in` from now (its signature is never checked)
+    fn id_token_expiring_in(expires_in: Duration) -> String {
+        let exp = (SystemTime::now() + expires_in)
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &serde_json::json!({ "exp": exp }),
+            &jsonwebtoken::EncodingKey::from_secret(b"test"),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_refresh_threshold() {
+        assert_eq!(parse_refresh_threshold("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_refresh_threshold("900"), Ok(Duration::from_secs(900)));
+        assert!(parse_refresh_threshold("-1").is_err());
+        assert!(parse_refresh_threshold("soon").is_err());
+        assert!(parse_refresh_threshold("86401").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_larger_threshold_refreshes_earlier() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/refresh_token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "new-access",
+                "id_token": "new-id",
+                "token_type": "Bearer"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        fs::create_dir_all(temp_dir.path().join("user")).unwrap();
+        let mut tokens = sample_tokens();
+        tokens.id_token = id_token_expiring_in(Duration::from_secs(10 * 60));
+
+        assert!(!tokens.needs_refresh(DEFAULT_REFRESH_THRESHOLD).unwrap());
+        assert!(tokens.needs_refresh(Duration::from_secs(15 * 60)).unwrap());
+
+        // The default threshold keeps the tokens, a 15 minute one refreshes them
+        let log_file = temp_dir.path().join("cli.log");
+        let config = AppConfig::new(temp_dir.path().to_path_buf())
+            .with_api_base_url(&server.uri())
+            .with_log_target(LogTarget::File(log_file.clone()));
+        tokens.save_to_config(&config).unwrap();
+        let kept = ensure_valid_tokens(&config, &Client::new()).await.unwrap();
+        assert_eq!(kept.id_token(), tokens.id_token());
+
+        let config = config.with_token_refresh_threshold(Duration::from_secs(15 * 60));
+        let refreshed = ensure_valid_tokens(&config, &Client::new()).await.unwrap();
+        assert_eq!(refreshed.id_token(), "new-id");
+
+        let log = fs::read_to_string(&log_file).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(log.contains("Auth tokens refreshed"), "{}", log);
+    }
+}
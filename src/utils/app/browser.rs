@@ -0,0 +1,42 @@
+/// Abstraction over opening URLs in a browser, so the login flow can be tested without launching one
+pub trait UrlOpener {
+    /// Opens `url` in the user's browser
+    fn open(&mut self, url: &str) -> Result<(), String>;
+}
+
+/// Opener backed by the operating system's default browser (via `open`)
+#[derive(Debug, Default)]
+pub struct SystemBrowser;
+
+impl UrlOpener for SystemBrowser {
+    fn open(&mut self, url: &str) -> Result<(), String> {
+        open::that(url).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Opener recording every URL instead of opening it
+    #[derive(Debug, Default)]
+    pub struct RecordingOpener {
+        pub opened: Vec<String>,
+    }
+
+    impl UrlOpener for RecordingOpener {
+        fn open(&mut self, url: &str) -> Result<(), String> {
+            self.opened.push(url.to_string());
+            Ok(())
+        }
+    }
+
+    /// Opener that never finds a browser, as on a headless machine
+    pub struct UnavailableBrowser;
+
+    impl UrlOpener for UnavailableBrowser {
+        fn open(&mut self, _url: &str) -> Result<(), String> {
+            Err("no browser found".to_string())
+        }
+    }
+}
@@ -0,0 +1,76 @@
+/// Abstraction over the system clipboard, so copy flows can be tested without a display
+pub trait Clipboard {
+    /// Replaces the clipboard content with `text`
+    fn set_text(&mut self, text: &str) -> Result<(), String>;
+}
+
+/// Clipboard backed by the operating system (via `arboard`)
+#[derive(Debug, Default)]
+pub struct SystemClipboard;
+
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<(), String> {
+        arboard::Clipboard::new()
+            .and_then(|mut clipboard| clipboard.set_text(text.to_owned()))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Copies `text` to the clipboard, warning instead of failing when no clipboard is available
+///
+/// Returns whether the copy succeeded.
+pub fn copy_to_clipboard(clipboard: &mut dyn Clipboard, text: &str, label: &str) -> bool {
+    match clipboard.set_text(text) {
+        Ok(()) => {
+            println!("{} copied to clipboard", label);
+            true
+        }
+        Err(e) => {
+            eprintln!("Warning: could not copy {} to clipboard: {}", label, e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Clipboard recording every copied value
+    #[derive(Debug, Default)]
+    pub struct RecordingClipboard {
+        pub copied: Vec<String>,
+    }
+
+    impl Clipboard for RecordingClipboard {
+        fn set_text(&mut self, text: &str) -> Result<(), String> {
+            self.copied.push(text.to_string());
+            Ok(())
+        }
+    }
+
+    /// Clipboard that is never available, as on a headless machine
+    struct UnavailableClipboard;
+
+    impl Clipboard for UnavailableClipboard {
+        fn set_text(&mut self, _text: &str) -> Result<(), String> {
+            Err("no display".to_string())
+        }
+    }
+
+    #[test]
+    fn test_copy_to_clipboard() {
+        let mut clipboard = RecordingClipboard::default();
+        assert!(copy_to_clipboard(&mut clipboard, "secret", "token"));
+        assert_eq!(clipboard.copied, vec!["secret".to_string()]);
+    }
+
+    #[test]
+    fn test_copy_to_unavailable_clipboard() {
+        assert!(!copy_to_clipboard(
+            &mut UnavailableClipboard,
+            "secret",
+            "token"
+        ));
+    }
+}
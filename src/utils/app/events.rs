@@ -0,0 +1,257 @@
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Minimum delay between two `download_progress` events for the same download
+pub const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Lifecycle event emitted as one JSON line with `--events`
+///
+/// The `type` tag and field names are a stable interface for wrapping tools;
+/// add new variants or optional fields rather than renaming existing ones.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Event {
+    DownloadStarted {
+        name: String,
+        url: String,
+        total_bytes: Option<u64>,
+    },
+    DownloadProgress {
+        name: String,
+        downloaded_bytes: u64,
+        total_bytes: Option<u64>,
+    },
+    DownloadComplete {
+        name: String,
+        bytes: u64,
+    },
+    InstallComplete {
+        name: String,
+        path: PathBuf,
+        /// URL the binary was downloaded from (the primary or a mirror)
+        url: String,
+        /// Time spent downloading and writing the binary
+        elapsed_ms: u64,
+    },
+    AuthRefreshed {
+        expires_at: Option<u64>,
+    },
+    PipelineStage {
+        name: String,
+        stage: String,
+        message: String,
+    },
+    PipelineFailed {
+        name: String,
+        stage: String,
+        error: String,
+    },
+}
+
+#[derive(Clone)]
+enum Sink {
+    Stderr,
+    /// Shared by every clone of the emitter, so concurrent writers take turns
+    File(Arc<Mutex<File>>),
+    #[cfg(test)]
+    Memory(Arc<Mutex<Vec<Event>>>),
+}
+
+/// Writes [`Event`]s as JSON Lines to stderr or a file when `--events` is enabled
+///
+/// A disabled emitter (the default) drops every event, so callers can emit
+/// unconditionally.
+#[derive(Clone, Default)]
+pub struct EventEmitter {
+    sink: Option<Sink>,
+}
+
+impl fmt::Debug for EventEmitter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EventEmitter")
+            .field("enabled", &self.is_enabled())
+            .finish()
+    }
+}
+
+impl EventEmitter {
+    /// Creates an emitter writing JSON Lines to stderr
+    pub fn stderr() -> Self {
+        Self {
+            sink: Some(Sink::Stderr),
+        }
+    }
+
+    /// Creates an emitter appending JSON Lines to `path` (`--events-file`)
+    ///
+    /// Missing parent directories are created and existing content is kept.
+    pub fn file(path: &Path) -> io::Result<Self> {
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            sink: Some(Sink::File(Arc::new(Mutex::new(file)))),
+        })
+    }
+
+    /// Whether events are written anywhere
+    pub fn is_enabled(&self) -> bool {
+        self.sink.is_some()
+    }
+
+    /// Emits an event, ignoring write failures so they never abort the operation
+    ///
+    /// Each event is written as one complete line with a single write under a
+    /// lock, so events emitted from parallel downloads never interleave.
+    pub fn emit(&self, event: Event) {
+        let write_line = |writer: &mut dyn Write| {
+            if let Ok(mut line) = serde_json::to_string(&event) {
+                line.push('\n');
+                let _ = writer.write_all(line.as_bytes());
+            }
+        };
+
+        match &self.sink {
+            None => {}
+            Some(Sink::Stderr) => write_line(&mut io::stderr().lock()),
+            Some(Sink::File(file)) => {
+                write_line(&mut *file.lock().unwrap_or_else(PoisonError::into_inner))
+            }
+            #[cfg(test)]
+            Some(Sink::Memory(events)) => events.lock().unwrap().push(event),
+        }
+    }
+
+    /// Creates an emitter that keeps events in memory for assertions
+    #[cfg(test)]
+    pub fn recording() -> Self {
+        Self {
+            sink: Some(Sink::Memory(Arc::default())),
+        }
+    }
+
+    /// Events recorded by an emitter created with [`EventEmitter::recording`]
+    #[cfg(test)]
+    pub fn recorded(&self) -> Vec<Event> {
+        match &self.sink {
+            Some(Sink::Memory(events)) => events.lock().unwrap().clone(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// Rate-limits `download_progress` events to one per [`PROGRESS_INTERVAL`]
+#[derive(Debug)]
+pub struct ProgressThrottle {
+    last: Option<Instant>,
+    interval: Duration,
+}
+
+impl ProgressThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            last: None,
+            interval,
+        }
+    }
+
+    /// Returns whether an update should be emitted now
+    pub fn ready(&mut self) -> bool {
+        let now = Instant::now();
+        match self.last {
+            Some(last) if now.duration_since(last) < self.interval => false,
+            _ => {
+                self.last = Some(now);
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_serialization() {
+        let event = Event::DownloadComplete {
+            name: "agt".to_string(),
+            bytes: 42,
+        };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"download_complete","name":"agt","bytes":42}"#
+        );
+
+        let event = Event::AuthRefreshed { expires_at: None };
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"type":"auth_refreshed","expires_at":null}"#
+        );
+    }
+
+    #[test]
+    fn test_disabled_emitter_drops_events() {
+        let emitter = EventEmitter::default();
+        assert!(!emitter.is_enabled());
+        emitter.emit(Event::AuthRefreshed { expires_at: None });
+        assert!(emitter.recorded().is_empty());
+    }
+
+    #[test]
+    fn test_concurrent_file_events_do_not_interleave() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("logs").join("events.jsonl");
+        let emitter = EventEmitter::file(&path).unwrap();
+
+        let handles: Vec<_> = (0..8)
+            .map(|task| {
+                let emitter = emitter.clone();
+                std::thread::spawn(move || {
+                    for downloaded_bytes in 0..200 {
+                        emitter.emit(Event::DownloadProgress {
+                            name: format!("binary-{}-{}", task, "x".repeat(512)),
+                            downloaded_bytes,
+                            total_bytes: Some(200),
+                        });
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 8 * 200);
+        for line in content.lines() {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(value["type"], "download_progress");
+        }
+
+        // Reopening appends rather than truncating
+        EventEmitter::file(&path)
+            .unwrap()
+            .emit(Event::AuthRefreshed { expires_at: None });
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 8 * 200 + 1);
+    }
+
+    #[test]
+    fn test_progress_throttle() {
+        let mut throttle = ProgressThrottle::new(Duration::from_secs(60));
+        assert!(throttle.ready());
+        assert!(!throttle.ready());
+
+        let mut throttle = ProgressThrottle::new(Duration::ZERO);
+        assert!(throttle.ready());
+        assert!(throttle.ready());
+    }
+}
@@ -1,11 +1,25 @@
-use std::path::PathBuf;
-
-use super::super::bin::ensure_required_binaries;
-use super::super::fs::filesystem::create_agnostic_working_dir;
+use std::fmt;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::super::bin::{BinResult, BinarySelection, InstallOptions, ensure_required_binaries};
+use super::super::fs::filesystem::{create_agnostic_working_dir, create_agnostic_working_dir_in};
+use super::super::fs::{FsProvider, RealFs};
+use super::super::net::ClientOptions;
+use super::auth::DEFAULT_REFRESH_THRESHOLD;
+use super::events::EventEmitter;
+use super::layout::Layout;
+use super::logging::LogTarget;
+use super::output::JsonStyle;
 
 /// Result type for initialization operations
 pub type InitResult<T> = Result<T, Box<dyn std::error::Error>>;
 
+/// Default base URL of the Agnostic platform (web app, login and API)
+pub const DEFAULT_API_BASE_URL: &str = "https://app.agnostic.tech";
+
 /// Configuration structure for the CLI application
 #[derive(Debug, Clone)]
 pub struct AppConfig {
@@ -14,6 +28,29 @@ pub struct AppConfig {
     /// Whether verbose logging is enabled
     #[allow(dead_code)]
     pub verbose: bool,
+    /// Base URL of the Agnostic platform, without trailing slash
+    pub api_base_url: String,
+    /// Whether confirmation prompts are answered automatically (`--yes`)
+    pub assume_yes: bool,
+    /// Whether network checks are skipped (`--offline`)
+    pub offline: bool,
+    /// Emitter for JSON Lines lifecycle events (`--events`)
+    pub events: EventEmitter,
+    /// TLS settings for clients talking to the platform (`--ca-cert`, `--insecure`)
+    pub client: ClientOptions,
+    /// Settings for clients downloading binaries and templates, which may use
+    /// a different proxy (`--proxy-binary-downloads-only`)
+    pub download_client: ClientOptions,
+    /// Formatting of `--json` output (`--json-compact`)
+    pub json_style: JsonStyle,
+    /// File receiving `--json` output instead of stdout (`--output-file`)
+    pub output_file: Option<PathBuf>,
+    /// How long before expiry auth tokens are refreshed (`--token-refresh-threshold-secs`)
+    pub token_refresh_threshold: Duration,
+    /// File access for command state files (auth tokens, cached profile)
+    pub fs: Arc<dyn FsProvider>,
+    /// Where notable events are logged (`--log-file`), nowhere when unset
+    pub log_target: Option<LogTarget>,
 }
 
 impl AppConfig {
@@ -22,6 +59,17 @@ impl AppConfig {
         Self {
             agnostic_dir,
             verbose: false,
+            api_base_url: DEFAULT_API_BASE_URL.to_string(),
+            assume_yes: false,
+            offline: false,
+            events: EventEmitter::default(),
+            client: ClientOptions::default(),
+            download_client: ClientOptions::default(),
+            json_style: JsonStyle::default(),
+            output_file: None,
+            token_refresh_threshold: DEFAULT_REFRESH_THRESHOLD,
+            fs: Arc::new(RealFs),
+            log_target: None,
         }
     }
 
@@ -31,13 +79,154 @@ impl AppConfig {
         self.verbose = true;
         self
     }
+
+    /// Answers all confirmation prompts with "yes"
+    pub fn with_assume_yes(mut self) -> Self {
+        self.assume_yes = true;
+        self
+    }
+
+    /// Skips optional network access such as connectivity checks
+    pub fn with_offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Uses an already configured event emitter, e.g. the one used during startup
+    pub fn with_event_emitter(mut self, events: EventEmitter) -> Self {
+        self.events = events;
+        self
+    }
+
+    /// Records notable events in `target`
+    pub fn with_log_target(mut self, target: LogTarget) -> Self {
+        self.log_target = Some(target);
+        self
+    }
+
+    /// Records an event in the CLI log, if one is configured
+    pub fn log(&self, message: &str) {
+        if let Some(target) = &self.log_target {
+            target.record(message);
+        }
+    }
+
+    /// Overrides the platform base URL (self-hosted or staging deployments)
+    pub fn with_api_base_url(mut self, api_base_url: &str) -> Self {
+        self.api_base_url = api_base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Selects how `--json` output is formatted
+    pub fn with_json_style(mut self, json_style: JsonStyle) -> Self {
+        self.json_style = json_style;
+        self
+    }
+
+    /// Writes `--json` output to `path` instead of stdout
+    pub fn with_output_file(mut self, path: PathBuf) -> Self {
+        self.output_file = Some(path);
+        self
+    }
+
+    /// Refreshes auth tokens when they expire within `threshold`
+    pub fn with_token_refresh_threshold(mut self, threshold: Duration) -> Self {
+        self.token_refresh_threshold = threshold;
+        self
+    }
+
+    /// Replaces the file access used for command state files, e.g. with an in-memory one
+    #[allow(dead_code)]
+    pub fn with_fs(mut self, fs: Arc<dyn FsProvider>) -> Self {
+        self.fs = fs;
+        self
+    }
+
+    /// Applies the same client settings to API calls and downloads
+    pub fn with_client_options(mut self, client: ClientOptions) -> Self {
+        self.download_client = client.clone();
+        self.client = client;
+        self
+    }
+
+    /// Overrides the client settings used for downloads only
+    pub fn with_download_client_options(mut self, client: ClientOptions) -> Self {
+        self.download_client = client;
+        self
+    }
+
+    /// Builds an HTTP client for the platform API with the configured TLS settings
+    pub fn http_client(&self) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+        self.client.build()
+    }
+
+    /// Builds an HTTP client for binary and template downloads
+    pub fn download_http_client(&self) -> Result<reqwest::Client, Box<dyn std::error::Error>> {
+        self.download_client.build()
+    }
+
+    /// Locations of the files under the working directory
+    pub fn layout(&self) -> Layout {
+        Layout::new(&self.agnostic_dir)
+    }
+
+    /// Builds the URL of an API endpoint, e.g. `api_url("user")`
+    pub fn api_url(&self, endpoint: &str) -> String {
+        format!(
+            "{}/api/{}",
+            self.api_base_url,
+            endpoint.trim_start_matches('/')
+        )
+    }
+
+    /// Builds the browser login URL redirecting back to `redirect_uri`
+    pub fn login_url(&self, redirect_uri: &str) -> String {
+        format!(
+            "{}/login?redirectTo={}",
+            self.api_base_url,
+            urlencoding::encode(redirect_uri)
+        )
+    }
+}
+
+/// Startup behaviour of [`initialize_app_with`]
+#[derive(Debug, Clone)]
+pub struct InitOptions {
+    /// Download missing binaries and verify installed ones (`false` with `--no-install`)
+    pub install_binaries: bool,
+    /// Binaries to set up (`--only-binary`, `--skip-binary`), all by default
+    pub binaries: BinarySelection,
+    /// Home directory to create `.agnostic` in, `$HOME` when unset
+    pub home: Option<PathBuf>,
+    /// Where the CLI logs (`--log-file`), `logs/cli.log` when unset
+    pub log_file: Option<LogTarget>,
+    /// Report which directories were created or already present (`--verbose`)
+    pub verbose: bool,
+    /// HTTP settings for the startup binary downloads (`--proxy`, `--ca-cert`, ...)
+    pub download_client: ClientOptions,
+    /// Receives lifecycle events of the startup install (`--events`), kept in the returned config
+    pub events: EventEmitter,
+}
+
+impl Default for InitOptions {
+    fn default() -> Self {
+        Self {
+            install_binaries: true,
+            binaries: BinarySelection::default(),
+            home: None,
+            log_file: None,
+            verbose: false,
+            download_client: ClientOptions::default(),
+            events: EventEmitter::default(),
+        }
+    }
 }
 
 /// Initializes the CLI application environment
 ///
 /// This function performs all necessary setup tasks at application startup:
 /// - Creates the ~/.agnostic working directory
-/// - Sets up logging (if needed)
+/// - Resolves where notable events are logged
 /// - Validates system requirements
 /// - Returns configuration for the application
 ///
@@ -66,74 +255,144 @@ impl AppConfig {
 ///     }
 /// }
 /// ```
+#[allow(dead_code)]
 pub async fn initialize_app() -> InitResult<AppConfig> {
+    initialize_app_with(&InitOptions::default()).await
+}
+
+/// Initializes the CLI application environment, see [`initialize_app`]
+///
+/// With `install_binaries` unset (`--no-install`), binaries are neither
+/// downloaded nor verified; only directories and configuration are set up.
+pub async fn initialize_app_with(options: &InitOptions) -> InitResult<AppConfig> {
     // Create the .agnostic working directory
-    let agnostic_dir = create_agnostic_working_dir()
-        .map_err(|e| format!("Failed to create agnostic working directory: {}", e))?;
+    let agnostic_dir = match &options.home {
+        Some(home) => create_agnostic_working_dir_in(home),
+        None => create_agnostic_working_dir(),
+    }
+    .map_err(|e| format!("Failed to create agnostic working directory: {}", e))?;
 
-    // Create subdirectories for organization
-    create_app_subdirectories(&agnostic_dir)?;
+    let install_options = startup_install_options(&agnostic_dir, options);
+    initialize_app_in(agnostic_dir, options, |bin_dir| async move {
+        ensure_required_binaries(&bin_dir, &install_options).await
+    })
+    .await
+}
 
-    // Initialize logging (basic setup for now)
-    setup_logging(&agnostic_dir)?;
+/// Install settings for the binaries set up at startup
+fn startup_install_options(agnostic_dir: &Path, options: &InitOptions) -> InstallOptions {
+    InstallOptions {
+        selection: options.binaries.clone(),
+        install_log: Some(Layout::new(agnostic_dir).install_log()),
+        http: options.download_client.clone(),
+        events: options.events.clone(),
+        ..Default::default()
+    }
+}
+
+/// Sets up `agnostic_dir`, calling `ensure_binaries` unless `options` disable it
+async fn initialize_app_in<F, Fut>(
+    agnostic_dir: PathBuf,
+    options: &InitOptions,
+    ensure_binaries: F,
+) -> InitResult<AppConfig>
+where
+    F: FnOnce(PathBuf) -> Fut,
+    Fut: Future<Output = BinResult<Vec<PathBuf>>>,
+{
+    // Create subdirectories for organization
+    let dirs = create_app_subdirectories(&agnostic_dir)?;
+    if options.verbose {
+        for dir in &dirs {
+            eprintln!("{}", dir);
+        }
+    }
 
     // Validate system requirements
-    validate_system_requirements()?;
+    validate_system_requirements(options.home.as_deref())?;
+
+    let log_target = options
+        .log_file
+        .clone()
+        .unwrap_or_else(|| LogTarget::File(Layout::new(&agnostic_dir).cli_log()));
+    let config = AppConfig::new(agnostic_dir)
+        .with_event_emitter(options.events.clone())
+        .with_log_target(log_target);
 
     // Download and install required binaries
-    let bin_dir = agnostic_dir.join("bin");
-    match ensure_required_binaries(&bin_dir).await {
+    if !options.install_binaries {
+        return Ok(config);
+    }
+    match ensure_binaries(config.layout().bin_dir()).await {
         Ok(_binaries) => {
             // Binary installation messages are handled by ensure_required_binaries
         }
         Err(e) => {
-            eprintln!("Warning: Failed to install some binaries: {}", e);
+            let message = format!("Failed to install some binaries: {}", e);
+            // A stderr log already shows the failure
+            if config.log_target != Some(LogTarget::Stderr) {
+                eprintln!("Warning: {}", message);
+            }
+            config.log(&message);
             // Don't fail initialization for binary installation failures
             // The CLI can still work without external binaries in most cases
         }
     }
 
-    // Create and return configuration
-    let config = AppConfig::new(agnostic_dir);
-
     Ok(config)
 }
 
-/// Creates necessary subdirectories within the .agnostic directory
-fn create_app_subdirectories(agnostic_dir: &PathBuf) -> InitResult<()> {
-    use super::super::fs::filesystem::ensure_dir_exists;
-
-    // Create common subdirectories
-    let subdirs = ["bin", "user"];
+/// A directory set up at startup, and whether this run created it
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DirSetup {
+    name: &'static str,
+    path: PathBuf,
+    created: bool,
+}
 
-    for subdir in &subdirs {
-        let dir_path = agnostic_dir.join(subdir);
-        ensure_dir_exists(&dir_path)
-            .map_err(|e| format!("Failed to create {} directory: {}", subdir, e))?;
+impl fmt::Display for DirSetup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = if self.created {
+            "created"
+        } else {
+            "already present"
+        };
+        write!(
+            f,
+            "{} directory {}: {}",
+            self.name,
+            self.path.display(),
+            state
+        )
     }
-
-    Ok(())
 }
 
-/// Sets up basic logging for the application
-fn setup_logging(agnostic_dir: &PathBuf) -> InitResult<()> {
-    // For now, this is a placeholder
-    // In the future, you might want to set up file logging to ~/.agnostic/logs/
-    let _log_dir = agnostic_dir.join("logs");
+/// Creates necessary subdirectories within the .agnostic directory
+fn create_app_subdirectories(agnostic_dir: &Path) -> InitResult<Vec<DirSetup>> {
+    use super::super::fs::filesystem::{check_dir_path, ensure_dir_exists};
 
-    // TODO: Implement proper logging setup
-    // This could include:
-    // - Setting up file rotation
-    // - Configuring log levels
-    // - Setting up structured logging
+    // Create common subdirectories
+    let mut dirs = Vec::new();
+    for (subdir, dir_path) in Layout::new(agnostic_dir).required_dirs() {
+        check_dir_path(&dir_path)?;
+        let created = ensure_dir_exists(&dir_path)
+            .map_err(|e| format!("Failed to create {} directory: {}", subdir, e))?;
+        dirs.push(DirSetup {
+            name: subdir,
+            path: dir_path,
+            created,
+        });
+    }
 
-    Ok(())
+    Ok(dirs)
 }
 
 /// Validates system requirements for the CLI application
-fn validate_system_requirements() -> InitResult<()> {
+///
+/// `$HOME` is only required when no `home` override was given.
+fn validate_system_requirements(home: Option<&Path>) -> InitResult<()> {
     // Check if we can write to the home directory
-    if std::env::var("HOME").is_err() {
+    if home.is_none() && std::env::var("HOME").is_err() {
         return Err("HOME environment variable not set".into());
     }
 
@@ -174,10 +433,10 @@ fn validate_system_requirements() -> InitResult<()> {
 /// ```
 pub async fn cleanup_app(config: &AppConfig) -> InitResult<()> {
     // Clean up temporary files
-    let temp_dir = config.agnostic_dir.join("temp");
+    let temp_dir = config.layout().temp_dir();
     if temp_dir.exists() {
         // Remove old temporary files (keep recent ones)
-        cleanup_temp_directory(&temp_dir)?;
+        cleanup_temp_directory(&temp_dir, &config.agnostic_dir)?;
     }
 
     // TODO: Add other cleanup tasks as needed
@@ -189,7 +448,8 @@ pub async fn cleanup_app(config: &AppConfig) -> InitResult<()> {
 }
 
 /// Cleans up old temporary files from the temp directory
-fn cleanup_temp_directory(temp_dir: &PathBuf) -> InitResult<()> {
+fn cleanup_temp_directory(temp_dir: &Path, agnostic_dir: &Path) -> InitResult<()> {
+    use super::super::fs::filesystem::remove_path_guarded;
     use std::fs;
     use std::time::{Duration, SystemTime};
 
@@ -200,12 +460,11 @@ fn cleanup_temp_directory(temp_dir: &PathBuf) -> InitResult<()> {
 
     if let Ok(entries) = fs::read_dir(temp_dir) {
         for entry in entries.flatten() {
-            if let Ok(metadata) = entry.metadata() {
-                if let Ok(modified) = metadata.modified() {
-                    if modified < cutoff_time {
-                        let _ = fs::remove_file(entry.path());
-                    }
-                }
+            if let Ok(metadata) = entry.metadata()
+                && let Ok(modified) = metadata.modified()
+                && modified < cutoff_time
+            {
+                let _ = remove_path_guarded(entry.path(), agnostic_dir);
             }
         }
     }
@@ -246,16 +505,24 @@ pub fn get_agnostic_subdir(config: &AppConfig, subdir: &str) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::net::ProxySetting;
 
     use tempfile::TempDir;
 
     #[tokio::test]
     async fn test_initialize_app() {
-        // This test will create the actual ~/.agnostic directory
-        let result = initialize_app().await;
+        // Set up an isolated home without downloading binaries
+        let home = TempDir::new().unwrap();
+        let options = InitOptions {
+            install_binaries: false,
+            home: Some(home.path().to_path_buf()),
+            ..Default::default()
+        };
+        let result = initialize_app_with(&options).await;
         assert!(result.is_ok());
 
         let config = result.unwrap();
+        assert_eq!(config.agnostic_dir, home.path().join(".agnostic"));
         assert!(config.agnostic_dir.exists());
         assert!(config.agnostic_dir.is_dir());
 
@@ -272,6 +539,254 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_custom_log_file() {
+        let home = TempDir::new().unwrap();
+        let log_file = home.path().join("shared").join("ag.log");
+        let options = InitOptions {
+            log_file: Some(LogTarget::File(log_file.clone())),
+            ..Default::default()
+        };
+
+        // A run where nothing goes wrong logs nothing
+        let agnostic_dir = home.path().join(".agnostic");
+        initialize_app_in(agnostic_dir.clone(), &options, |_| async { Ok(Vec::new()) })
+            .await
+            .unwrap();
+        assert!(!log_file.exists());
+
+        let config = initialize_app_in(agnostic_dir, &options, |_| async {
+            Err("connection refused".into())
+        })
+        .await
+        .unwrap();
+
+        let content = std::fs::read_to_string(&log_file).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(
+            content.contains("Failed to install some binaries: connection refused"),
+            "{}",
+            content
+        );
+        assert!(!config.layout().cli_log().exists());
+    }
+
+    #[tokio::test]
+    async fn test_home_override_without_home_env() {
+        // HOME is process-wide, so the init runs in a child test process without it
+        const CHILD_HOME: &str = "AG_TEST_ISOLATED_HOME";
+        let Some(home) = std::env::var_os(CHILD_HOME) else {
+            let home = TempDir::new().unwrap();
+            let test_name = format!(
+                "{}::test_home_override_without_home_env",
+                module_path!().split_once("::").unwrap().1
+            );
+            let status = std::process::Command::new(std::env::current_exe().unwrap())
+                .args(["--exact", &test_name, "--quiet"])
+                .env_remove("HOME")
+                .env(CHILD_HOME, home.path())
+                .status()
+                .unwrap();
+            assert!(status.success());
+            assert!(home.path().join(".agnostic").join("bin").is_dir());
+            return;
+        };
+
+        assert!(std::env::var_os("HOME").is_none());
+        let options = InitOptions {
+            install_binaries: false,
+            home: Some(PathBuf::from(home)),
+            ..Default::default()
+        };
+        initialize_app_with(&options).await.unwrap();
+        assert!(validate_system_requirements(None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_no_install_skips_binaries() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = InitOptions {
+            install_binaries: false,
+            ..Default::default()
+        };
+
+        let config = initialize_app_in(temp_dir.path().to_path_buf(), &options, |_| async {
+            panic!("binaries must not be ensured with --no-install")
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(config.agnostic_dir, temp_dir.path());
+        assert!(temp_dir.path().join("bin").is_dir());
+        assert!(temp_dir.path().join("user").is_dir());
+    }
+
+    #[tokio::test]
+    async fn test_default_init_ensures_binaries() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut ensured = None;
+
+        initialize_app_in(
+            temp_dir.path().to_path_buf(),
+            &InitOptions::default(),
+            |bin_dir| {
+                ensured = Some(bin_dir);
+                async { Ok(Vec::new()) }
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(ensured, Some(temp_dir.path().join("bin")));
+    }
+
+    #[test]
+    fn test_startup_install_uses_download_client() {
+        let options = InitOptions {
+            download_client: ClientOptions {
+                proxy: ProxySetting::Url("http://proxy.internal:3128".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let install_options = startup_install_options(Path::new("/tmp/.agnostic"), &options);
+
+        assert_eq!(
+            install_options.http.proxy,
+            ProxySetting::Url("http://proxy.internal:3128".to_string())
+        );
+        assert_eq!(
+            install_options.install_log,
+            Some(Layout::new(Path::new("/tmp/.agnostic")).install_log())
+        );
+    }
+
+    #[test]
+    fn test_startup_install_uses_tls_settings() {
+        let options = InitOptions {
+            download_client: ClientOptions {
+                ca_cert: Some(PathBuf::from("/etc/agnostic/ca.pem")),
+                insecure: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let install_options = startup_install_options(Path::new("/tmp/.agnostic"), &options);
+
+        assert_eq!(
+            install_options.http.ca_cert,
+            Some(PathBuf::from("/etc/agnostic/ca.pem"))
+        );
+        assert!(install_options.http.insecure);
+    }
+
+    #[test]
+    fn test_startup_install_sends_user_agent() {
+        let options = InitOptions {
+            download_client: ClientOptions {
+                user_agent: Some("acme-ci/1.0".to_string()),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let install_options = startup_install_options(Path::new("/tmp/.agnostic"), &options);
+
+        assert_eq!(
+            install_options.http.user_agent.as_deref(),
+            Some("acme-ci/1.0")
+        );
+    }
+
+    #[test]
+    fn test_startup_install_limits_redirects() {
+        let options = InitOptions {
+            download_client: ClientOptions {
+                max_redirects: 2,
+                log_redirects: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let install_options = startup_install_options(Path::new("/tmp/.agnostic"), &options);
+
+        assert_eq!(install_options.http.max_redirects, 2);
+        assert!(install_options.http.log_redirects);
+    }
+
+    #[tokio::test]
+    async fn test_startup_install_emits_events() {
+        use super::super::events::Event;
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = InitOptions {
+            events: EventEmitter::recording(),
+            ..Default::default()
+        };
+        let install_options = startup_install_options(temp_dir.path(), &options);
+        let complete = Event::InstallComplete {
+            name: "agt".to_string(),
+            path: temp_dir.path().join("bin").join("agt"),
+            url: "https://example.com/agt".to_string(),
+            elapsed_ms: 1200,
+        };
+
+        let config = initialize_app_in(temp_dir.path().to_path_buf(), &options, |_| async {
+            install_options.events.emit(complete.clone());
+            Ok(Vec::new())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(options.events.recorded(), vec![complete.clone()]);
+        assert_eq!(config.events.recorded(), vec![complete]);
+    }
+
+    #[test]
+    fn test_create_app_subdirectories() {
+        let temp_dir = TempDir::new().unwrap();
+        create_app_subdirectories(temp_dir.path()).unwrap();
+        assert!(temp_dir.path().join("bin").is_dir());
+        assert!(temp_dir.path().join("user").is_dir());
+    }
+
+    #[test]
+    fn test_create_app_subdirectories_reports_creations() {
+        let home = TempDir::new().unwrap();
+        let agnostic_dir = home.path().join(".agnostic");
+
+        let first = create_app_subdirectories(&agnostic_dir).unwrap();
+        assert_eq!(first.len(), 2);
+        assert!(first.iter().all(|dir| dir.created));
+        assert!(first[0].to_string().ends_with(": created"));
+
+        let second = create_app_subdirectories(&agnostic_dir).unwrap();
+        assert!(second.iter().all(|dir| !dir.created));
+        assert_eq!(
+            second[0].to_string(),
+            format!(
+                "bin directory {}: already present",
+                agnostic_dir.join("bin").display()
+            )
+        );
+    }
+
+    #[test]
+    fn test_create_app_subdirectories_with_file_in_the_way() {
+        let temp_dir = TempDir::new().unwrap();
+        let user_path = temp_dir.path().join("user");
+        std::fs::write(&user_path, "not a directory").unwrap();
+
+        let err = create_app_subdirectories(temp_dir.path())
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(&user_path.display().to_string()));
+        assert!(err.contains("is not a directory"));
+    }
+
     #[test]
     fn test_app_config() {
         let temp_dir = TempDir::new().unwrap();
@@ -281,8 +796,42 @@ mod tests {
         assert_eq!(config.agnostic_dir, agnostic_path);
         assert!(!config.verbose);
 
+        assert!(!config.assume_yes);
+
         let verbose_config = config.with_verbose();
         assert!(verbose_config.verbose);
+
+        let yes_config = verbose_config.with_assume_yes();
+        assert!(yes_config.assume_yes);
+    }
+
+    #[test]
+    fn test_default_api_urls() {
+        let config = AppConfig::new(PathBuf::from("/tmp/.agnostic"));
+        assert_eq!(config.api_url("user"), "https://app.agnostic.tech/api/user");
+        assert_eq!(
+            config.login_url("http://localhost:1234"),
+            "https://app.agnostic.tech/login?redirectTo=http%3A%2F%2Flocalhost%3A1234"
+        );
+    }
+
+    #[test]
+    fn test_api_base_url_override() {
+        let config = AppConfig::new(PathBuf::from("/tmp/.agnostic"))
+            .with_api_base_url("https://agnostic.example.com/");
+
+        assert_eq!(
+            config.login_url("http://localhost:1234"),
+            "https://agnostic.example.com/login?redirectTo=http%3A%2F%2Flocalhost%3A1234"
+        );
+        assert_eq!(
+            config.api_url("refresh_token"),
+            "https://agnostic.example.com/api/refresh_token"
+        );
+        assert_eq!(
+            config.api_url("/user"),
+            "https://agnostic.example.com/api/user"
+        );
     }
 
     #[test]
@@ -298,8 +847,9 @@ mod tests {
     #[test]
     fn test_validate_system_requirements() {
         // This should pass on most systems
-        let result = validate_system_requirements();
+        let result = validate_system_requirements(None);
         assert!(result.is_ok());
+        assert!(validate_system_requirements(Some(Path::new("/tmp"))).is_ok());
     }
 
     #[tokio::test]
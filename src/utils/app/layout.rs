@@ -0,0 +1,122 @@
+//! Where the CLI keeps its files inside the `.agnostic` directory
+//!
+//! Code locating a file under the working directory goes through [`Layout`]
+//! (usually [`AppConfig::layout`](super::AppConfig::layout)) rather than
+//! joining subdirectory names itself.
+
+use std::path::{Path, PathBuf};
+
+use super::logging::CLI_LOG_FILE;
+use crate::utils::bin::install_log::INSTALL_LOG_FILE;
+
+/// Paths of the files and directories under an `.agnostic` root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Layout {
+    root: PathBuf,
+}
+
+impl Layout {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// The `.agnostic` directory itself
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Managed binaries and their manifest
+    pub fn bin_dir(&self) -> PathBuf {
+        self.root.join("bin")
+    }
+
+    /// Per-user state: auth tokens, cached profile, settings
+    pub fn user_dir(&self) -> PathBuf {
+        self.root.join("user")
+    }
+
+    /// Auth tokens saved by `user login`
+    pub fn auth_file(&self) -> PathBuf {
+        self.user_dir().join("auth.json")
+    }
+
+    /// Last profile fetched by `user status`
+    pub fn profile_file(&self) -> PathBuf {
+        self.user_dir().join("profile.json")
+    }
+
+    pub fn logs_dir(&self) -> PathBuf {
+        self.root.join("logs")
+    }
+
+    /// The CLI's own log, unless moved with `--log-file`
+    pub fn cli_log(&self) -> PathBuf {
+        self.logs_dir().join(CLI_LOG_FILE)
+    }
+
+    /// Audit log of binary downloads, see [`crate::utils::bin::install_log`]
+    pub fn install_log(&self) -> PathBuf {
+        self.logs_dir().join(INSTALL_LOG_FILE)
+    }
+
+    /// Scratch files, pruned by `cleanup_app` after a day
+    pub fn temp_dir(&self) -> PathBuf {
+        self.root.join("temp")
+    }
+
+    #[allow(dead_code)]
+    pub fn cache_dir(&self) -> PathBuf {
+        self.root.join("cache")
+    }
+
+    /// State of pipelines started with `pipeline spawn --detach`
+    pub fn pipelines_dir(&self) -> PathBuf {
+        self.root.join("pipelines")
+    }
+
+    /// PID file of a detached pipeline, named after the (URL-encoded) pipeline
+    pub fn pipeline_pid_file(&self, name: &str) -> PathBuf {
+        self.pipelines_dir()
+            .join(format!("{}.pid", urlencoding::encode(name)))
+    }
+
+    /// Directories created at startup and checked by `system doctor`, with their names
+    pub fn required_dirs(&self) -> [(&'static str, PathBuf); 2] {
+        [("bin", self.bin_dir()), ("user", self.user_dir())]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_layout_paths() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path();
+        let layout = Layout::new(root);
+
+        assert_eq!(layout.root(), root);
+        assert_eq!(layout.bin_dir(), root.join("bin"));
+        assert_eq!(layout.user_dir(), root.join("user"));
+        assert_eq!(layout.auth_file(), root.join("user").join("auth.json"));
+        assert_eq!(
+            layout.profile_file(),
+            root.join("user").join("profile.json")
+        );
+        assert_eq!(layout.logs_dir(), root.join("logs"));
+        assert_eq!(layout.cli_log(), root.join("logs").join("cli.log"));
+        assert_eq!(layout.install_log(), root.join("logs").join("install.log"));
+        assert_eq!(layout.temp_dir(), root.join("temp"));
+        assert_eq!(layout.cache_dir(), root.join("cache"));
+        assert_eq!(
+            layout.pipeline_pid_file("team/demo"),
+            root.join("pipelines").join("team%2Fdemo.pid")
+        );
+        assert_eq!(
+            layout.required_dirs().map(|(name, _)| name),
+            ["bin", "user"]
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! The CLI's own log file, `~/.agnostic/logs/cli.log` unless `--log-file` says otherwise
+//!
+//! Only notable events are recorded (failed startup installs, token refreshes),
+//! so a run where nothing goes wrong writes nothing.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{SecondsFormat, Utc};
+
+/// Name of the default log file in the logs directory
+pub const CLI_LOG_FILE: &str = "cli.log";
+
+/// Size past which a log file is rotated to `<file>.1` before the next record
+pub const MAX_LOG_BYTES: u64 = 1024 * 1024;
+
+/// Where log records go (`--log-file`, `AGNOSTIC_LOG_FILE`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogTarget {
+    /// Append to this file; `/dev/null` disables file logging
+    File(PathBuf),
+    /// `-`: no log file, records are printed on stderr
+    Stderr,
+}
+
+impl FromStr for LogTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" => Err("log file path must not be empty".to_string()),
+            "-" => Ok(Self::Stderr),
+            path => Ok(Self::File(PathBuf::from(path))),
+        }
+    }
+}
+
+impl LogTarget {
+    /// Writes one record, reporting a log file that cannot be written without failing
+    pub fn record(&self, message: &str) {
+        if let Err(e) = self.write(message)
+            && let Self::File(path) = self
+        {
+            eprintln!(
+                "Warning: Could not write log file {}: {}",
+                path.display(),
+                e
+            );
+        }
+    }
+
+    /// Writes one timestamped record, creating the log file's parent directories as needed
+    ///
+    /// A file that grew past [`MAX_LOG_BYTES`] is first moved to `<file>.1`,
+    /// replacing the previous rotation, so the log never takes more than about
+    /// twice that size.
+    pub fn write(&self, message: &str) -> io::Result<()> {
+        let line = format!(
+            "{} {}",
+            Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            message
+        );
+        match self {
+            Self::File(path) => {
+                if let Some(parent) = path.parent()
+                    && !parent.as_os_str().is_empty()
+                {
+                    fs::create_dir_all(parent)?;
+                }
+                rotate_if_full(path)?;
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                writeln!(file, "{}", line)
+            }
+            Self::Stderr => writeln!(io::stderr(), "{}", line),
+        }
+    }
+}
+
+/// Moves a regular file of at least [`MAX_LOG_BYTES`] to `<file>.1`
+fn rotate_if_full(path: &Path) -> io::Result<()> {
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_file() && metadata.len() >= MAX_LOG_BYTES => {
+            let mut rotated = path.as_os_str().to_owned();
+            rotated.push(".1");
+            fs::rename(path, rotated)
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_log_target() {
+        assert_eq!("-".parse(), Ok(LogTarget::Stderr));
+        assert_eq!(
+            "/dev/null".parse(),
+            Ok(LogTarget::File(PathBuf::from("/dev/null")))
+        );
+        assert!("".parse::<LogTarget>().is_err());
+    }
+
+    #[test]
+    fn test_write_appends_and_creates_parents() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("shared").join("logs").join("ag.log");
+        let target = LogTarget::File(path.clone());
+
+        target.write("first").unwrap();
+        target.write("second").unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" first"));
+        assert!(lines[1].ends_with(" second"));
+    }
+
+    #[test]
+    fn test_write_rotates_full_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("cli.log");
+        let rotated = temp_dir.path().join("cli.log.1");
+        fs::write(&path, vec![b'x'; MAX_LOG_BYTES as usize]).unwrap();
+
+        LogTarget::File(path.clone()).write("fresh").unwrap();
+
+        assert_eq!(fs::metadata(&rotated).unwrap().len(), MAX_LOG_BYTES);
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().count(), 1);
+        assert!(content.ends_with(" fresh\n"));
+    }
+}
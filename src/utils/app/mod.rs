@@ -1,7 +1,23 @@
 pub mod auth;
+pub mod browser;
+pub mod clipboard;
+pub mod events;
 pub mod init;
+pub mod layout;
+pub mod logging;
+pub mod output;
+pub mod prompt;
 
 // Re-export commonly used application functions
 pub use auth::{AuthTokens, ensure_valid_tokens};
 #[allow(unused_imports)]
-pub use init::{AppConfig, cleanup_app, get_agnostic_subdir, initialize_app};
+pub use events::{Event, EventEmitter};
+#[allow(unused_imports)]
+pub use init::{
+    AppConfig, DEFAULT_API_BASE_URL, InitOptions, cleanup_app, get_agnostic_subdir, initialize_app,
+    initialize_app_with,
+};
+#[allow(unused_imports)]
+pub use layout::Layout;
+#[allow(unused_imports)]
+pub use prompt::confirm;
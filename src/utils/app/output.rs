@@ -0,0 +1,173 @@
+use std::error::Error;
+use std::ffi::OsStr;
+use std::io::{self, IsTerminal, Write};
+
+use serde::Serialize;
+
+use super::init::AppConfig;
+use crate::utils::fs::write_atomic;
+
+/// Formatting of `--json` output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum JsonStyle {
+    /// Indented, for reading
+    #[default]
+    Pretty,
+    /// Single line, for piping (`--json-compact`)
+    Compact,
+}
+
+/// Whether styled output (colors in progress bars and messages) is used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// Colorize terminals unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    /// Colorize even when not writing to a terminal (`--force-color`)
+    Always,
+    /// Never colorize (`--no-color`)
+    Never,
+}
+
+/// Decides whether a stream gets colors
+///
+/// `--force-color` wins over everything; otherwise `--no-color` or a
+/// non-empty `NO_COLOR` (see <https://no-color.org>) disable colors, and the
+/// remaining case colorizes terminals only.
+pub fn should_colorize(choice: ColorChoice, no_color_env: Option<&OsStr>, is_tty: bool) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => no_color_env.is_none_or(OsStr::is_empty) && is_tty,
+    }
+}
+
+/// Applies `choice` to stdout and stderr, including `indicatif` progress bars
+pub fn apply_color_choice(choice: ColorChoice) {
+    let no_color = std::env::var_os("NO_COLOR");
+    console::set_colors_enabled(should_colorize(
+        choice,
+        no_color.as_deref(),
+        io::stdout().is_terminal(),
+    ));
+    console::set_colors_enabled_stderr(should_colorize(
+        choice,
+        no_color.as_deref(),
+        io::stderr().is_terminal(),
+    ));
+}
+
+/// Serializes a value for output, honouring the configured [`JsonStyle`]
+///
+/// Every `--json` mode should go through this helper so formatting flags apply
+/// consistently across commands.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cli::utils::app::{AppConfig, output::{JsonStyle, to_json}};
+///
+/// let config = AppConfig::new("/tmp/.agnostic".into()).with_json_style(JsonStyle::Compact);
+/// assert_eq!(to_json(&config, &vec![1, 2]).unwrap(), "[1,2]");
+/// ```
+pub fn to_json<T: Serialize + ?Sized>(config: &AppConfig, value: &T) -> serde_json::Result<String> {
+    match config.json_style {
+        JsonStyle::Pretty => serde_json::to_string_pretty(value),
+        JsonStyle::Compact => serde_json::to_string(value),
+    }
+}
+
+/// Prints a value as JSON on stdout, or to `--output-file` when set, see [`to_json`]
+pub fn print_json<T: Serialize + ?Sized>(
+    config: &AppConfig,
+    value: &T,
+) -> Result<(), Box<dyn Error>> {
+    write_json(config, value, &mut io::stdout().lock())
+}
+
+/// Writes a value as JSON to `--output-file` when set, or to `out` otherwise
+///
+/// The output file is replaced atomically and its parent directories are
+/// created as needed.
+fn write_json<T: Serialize + ?Sized, W: Write>(
+    config: &AppConfig,
+    value: &T,
+    out: &mut W,
+) -> Result<(), Box<dyn Error>> {
+    let json = to_json(config, value)?;
+    match &config.output_file {
+        Some(path) => write_atomic(path, format!("{}\n", json).as_bytes()),
+        None => Ok(writeln!(out, "{}", json)?),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_should_colorize() {
+        let no_color = Some(OsStr::new("1"));
+
+        assert!(should_colorize(ColorChoice::Auto, None, true));
+        assert!(!should_colorize(ColorChoice::Auto, None, false));
+        assert!(!should_colorize(ColorChoice::Auto, no_color, true));
+        assert!(should_colorize(
+            ColorChoice::Auto,
+            Some(OsStr::new("")),
+            true
+        ));
+        assert!(!should_colorize(ColorChoice::Never, None, true));
+
+        // --force-color overrides both the TTY check and NO_COLOR
+        assert!(should_colorize(ColorChoice::Always, None, false));
+        assert!(should_colorize(ColorChoice::Always, no_color, false));
+    }
+
+    #[test]
+    fn test_to_json_follows_style() {
+        let value = json!({ "name": "agt", "installed": true });
+
+        let config = AppConfig::new(PathBuf::from("/tmp/.agnostic"));
+        let pretty = to_json(&config, &value).unwrap();
+        assert_eq!(pretty, "{\n  \"installed\": true,\n  \"name\": \"agt\"\n}");
+
+        let config = config.with_json_style(JsonStyle::Compact);
+        let compact = to_json(&config, &value).unwrap();
+        assert_eq!(compact, r#"{"installed":true,"name":"agt"}"#);
+        assert!(!compact.contains('\n'));
+    }
+
+    #[test]
+    fn test_write_json_to_output_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let output_file = temp_dir.path().join("reports/status.json");
+        let value = json!({ "name": "agt", "installed": true });
+
+        let mut stdout = Vec::new();
+        let config = AppConfig::new(PathBuf::from("/tmp/.agnostic"));
+        write_json(&config, &value, &mut stdout).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&stdout).unwrap(),
+            value
+        );
+
+        let mut stdout = Vec::new();
+        let config = config.with_output_file(output_file.clone());
+        write_json(&config, &value, &mut stdout).unwrap();
+        assert!(stdout.is_empty());
+        let written = std::fs::read_to_string(&output_file).unwrap();
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&written).unwrap(),
+            value
+        );
+        assert_eq!(
+            std::fs::read_dir(output_file.parent().unwrap())
+                .unwrap()
+                .count(),
+            1
+        );
+    }
+}
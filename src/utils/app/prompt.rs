@@ -0,0 +1,65 @@
+use std::io::{self, BufRead, Write};
+
+use crate::utils::AppConfig;
+
+/// Asks the user to confirm an action
+///
+/// Returns `true` without prompting when `--yes` was passed, so destructive
+/// commands stay scriptable while remaining interactive by default.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cli::utils::app::{AppConfig, prompt::confirm};
+///
+/// let config = AppConfig::new("/tmp/.agnostic".into()).with_assume_yes();
+/// assert!(confirm(&config, "Remove all binaries?").unwrap());
+/// ```
+pub fn confirm(config: &AppConfig, message: &str) -> io::Result<bool> {
+    if config.assume_yes {
+        return Ok(true);
+    }
+
+    read_confirmation(message, &mut io::stdin().lock(), &mut io::stdout())
+}
+
+/// Prints a `[y/N]` prompt and reads the answer, defaulting to "no"
+fn read_confirmation<R: BufRead, W: Write>(
+    message: &str,
+    input: &mut R,
+    output: &mut W,
+) -> io::Result<bool> {
+    write!(output, "{} [y/N] ", message)?;
+    output.flush()?;
+
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_confirm_with_assume_yes() {
+        let config = AppConfig::new(PathBuf::from("/tmp/.agnostic")).with_assume_yes();
+        assert!(confirm(&config, "Proceed?").unwrap());
+    }
+
+    #[test]
+    fn test_read_confirmation() {
+        let mut output = Vec::new();
+        assert!(read_confirmation("Proceed?", &mut "y\n".as_bytes(), &mut output).unwrap());
+        assert!(read_confirmation("Proceed?", &mut "YES\n".as_bytes(), &mut output).unwrap());
+        assert!(!read_confirmation("Proceed?", &mut "n\n".as_bytes(), &mut output).unwrap());
+        assert!(!read_confirmation("Proceed?", &mut "\n".as_bytes(), &mut output).unwrap());
+        assert!(
+            String::from_utf8(output)
+                .unwrap()
+                .contains("Proceed? [y/N]")
+        );
+    }
+}
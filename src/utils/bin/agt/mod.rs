@@ -3,10 +3,13 @@
 //! This module provides configuration and information for the AGT binary.
 //! All actual operations (install, run, check) are handled by the common manager.
 
-use crate::utils::bin::manager::{BinaryInfoProvider, SystemTarget};
+use crate::utils::bin::manager::{BinResult, BinaryInfoProvider, SystemTarget, github_mirror_url};
+
+/// Release of agt installed by default
+const AGT_VERSION: &str = "0.0.23";
 
 /// AGT binary information provider
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct AgtProvider;
 
 impl AgtProvider {
@@ -25,17 +28,18 @@ impl BinaryInfoProvider for AgtProvider {
         "agt"
     }
 
-    fn get_download_url(&self, target: &SystemTarget) -> String {
-        let asset_name = match target {
-            SystemTarget::MacOsAarch64 => "agt_0.0.23_darwin_arm64",
-            SystemTarget::MacOsX86_64 => "agt_0.0.23_darwin_amd64_v1",
-            SystemTarget::LinuxX86_64 => "agt_0.0.23_linux_amd64_v1",
-        };
-
-        format!(
-            "https://github.com/agnosticeng/agt/releases/download/v0.0.23/{}",
-            asset_name
-        )
+    fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+        Ok(release_url(AGT_VERSION, target))
+    }
+
+    fn get_download_urls(&self, target: &SystemTarget) -> BinResult<Vec<String>> {
+        let primary = self.get_download_url(target)?;
+        let mirror = github_mirror_url(&primary);
+        Ok(std::iter::once(primary).chain(mirror).collect())
+    }
+
+    fn get_versioned_download_url(&self, target: &SystemTarget, version: &str) -> Option<String> {
+        Some(release_url(version.trim_start_matches('v'), target))
     }
 
     fn version_args(&self) -> &[&str] {
@@ -48,6 +52,20 @@ impl BinaryInfoProvider for AgtProvider {
     }
 }
 
+/// Builds the GitHub release asset URL of an agt version
+fn release_url(version: &str, target: &SystemTarget) -> String {
+    let platform = match target {
+        SystemTarget::MacOsAarch64 => "darwin_arm64",
+        SystemTarget::MacOsX86_64 => "darwin_amd64_v1",
+        SystemTarget::LinuxX86_64 => "linux_amd64_v1",
+    };
+
+    format!(
+        "https://github.com/agnosticeng/agt/releases/download/v{}/agt_{}_{}",
+        version, version, platform
+    )
+}
+
 /// Create a new AGT provider instance
 pub fn provider() -> AgtProvider {
     AgtProvider::new()
@@ -63,6 +81,8 @@ mod tests {
         assert_eq!(provider.name(), "agt");
         assert_eq!(provider.local_name(), "agt");
         assert_eq!(provider.version_args(), &["--version"]);
+        assert!(provider.has_version_flag());
+        assert!(!provider.is_long_running());
     }
 
     #[test]
@@ -86,14 +106,20 @@ mod tests {
     fn test_agt_download_urls() {
         let provider = AgtProvider::new();
 
-        let macos_arm_url = provider.get_download_url(&SystemTarget::MacOsAarch64);
+        let macos_arm_url = provider
+            .get_download_url(&SystemTarget::MacOsAarch64)
+            .unwrap();
         assert!(macos_arm_url.contains("agt_0.0.23_darwin_arm64"));
         assert!(macos_arm_url.contains("github.com/agnosticeng/agt"));
 
-        let macos_x86_url = provider.get_download_url(&SystemTarget::MacOsX86_64);
+        let macos_x86_url = provider
+            .get_download_url(&SystemTarget::MacOsX86_64)
+            .unwrap();
         assert!(macos_x86_url.contains("agt_0.0.23_darwin_amd64_v1"));
 
-        let linux_url = provider.get_download_url(&SystemTarget::LinuxX86_64);
+        let linux_url = provider
+            .get_download_url(&SystemTarget::LinuxX86_64)
+            .unwrap();
         assert!(linux_url.contains("agt_0.0.23_linux_amd64_v1"));
 
         // All should contain the release URL pattern
@@ -101,4 +127,17 @@ mod tests {
             assert!(url.contains("releases/download/v0.0.23"));
         }
     }
+
+    #[test]
+    fn test_agt_versioned_download_url() {
+        let provider = AgtProvider::new();
+
+        let url = provider
+            .get_versioned_download_url(&SystemTarget::LinuxX86_64, "v0.1.0")
+            .unwrap();
+        assert_eq!(
+            url,
+            "https://github.com/agnosticeng/agt/releases/download/v0.1.0/agt_0.1.0_linux_amd64_v1"
+        );
+    }
 }
@@ -0,0 +1,119 @@
+//! Download concurrency picked from a quick bandwidth probe (`--auto-concurrency`)
+//!
+//! Installing every binary at once is fastest on a good link but makes all of
+//! them crawl on a slow one. The probe times the first [`PROBE_BYTES`] of a
+//! binary that is about to be downloaded anyway, so it measures the real
+//! download host without fetching anything extra.
+
+use std::num::NonZeroUsize;
+use std::time::{Duration, Instant};
+
+use futures_util::TryStreamExt;
+use reqwest::{Client, header::RANGE};
+
+use super::manager::BinResult;
+
+/// Bytes fetched to estimate the throughput
+pub const PROBE_BYTES: u64 = 256 * 1024;
+
+/// Longest the probe may take; a link this slow is measured on what arrived
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+const MIB: f64 = 1024.0 * 1024.0;
+
+/// Concurrency for a measured throughput in bytes per second
+///
+/// Below 1 MiB/s downloads run one at a time, up to 4 at once from 20 MiB/s.
+pub fn concurrency_for_throughput(bytes_per_sec: f64) -> NonZeroUsize {
+    let concurrency = match bytes_per_sec / MIB {
+        mib if mib < 1.0 => 1,
+        mib if mib < 5.0 => 2,
+        mib if mib < 20.0 => 3,
+        _ => 4,
+    };
+    NonZeroUsize::new(concurrency).expect("concurrency is at least 1")
+}
+
+/// Measures the download throughput of `url` in bytes per second
+///
+/// Only the first [`PROBE_BYTES`] are requested (with a `Range` header) and
+/// read, even from servers that send the whole file.
+pub async fn probe_throughput(client: &Client, url: &str) -> BinResult<f64> {
+    let started = Instant::now();
+    let response = client
+        .get(url)
+        .header(RANGE, format!("bytes=0-{}", PROBE_BYTES - 1))
+        .send()
+        .await?
+        .error_for_status()?;
+
+    let mut received = 0u64;
+    let mut stream = response.bytes_stream();
+    let read = async {
+        while let Some(chunk) = stream.try_next().await? {
+            received += chunk.len() as u64;
+            if received >= PROBE_BYTES {
+                break;
+            }
+        }
+        Ok::<_, reqwest::Error>(())
+    };
+    if let Ok(result) = tokio::time::timeout(PROBE_TIMEOUT, read).await {
+        result?;
+    }
+
+    if received == 0 {
+        return Err(format!("Bandwidth probe of {} received no data", url).into());
+    }
+    Ok(received as f64 / started.elapsed().as_secs_f64().max(0.001))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrency_for_throughput() {
+        let cases = [
+            (0.0, 1),
+            (200.0 * 1024.0, 1),
+            (2.0 * MIB, 2),
+            (8.0 * MIB, 3),
+            (100.0 * MIB, 4),
+        ];
+        for (bytes_per_sec, expected) in cases {
+            assert_eq!(
+                concurrency_for_throughput(bytes_per_sec).get(),
+                expected,
+                "{} B/s",
+                bytes_per_sec
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_probe_reads_at_most_probe_bytes() {
+        use wiremock::matchers::{header, method};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("range", "bytes=0-262143"))
+            .respond_with(ResponseTemplate::new(206).set_body_bytes(vec![0; PROBE_BYTES as usize]))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let throughput = probe_throughput(&Client::new(), &format!("{}/bin", server.uri()))
+            .await
+            .unwrap();
+        assert!(throughput > 0.0);
+
+        let missing = MockServer::start().await;
+        assert!(
+            probe_throughput(&Client::new(), &missing.uri())
+                .await
+                .is_err()
+        );
+    }
+}
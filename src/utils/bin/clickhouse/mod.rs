@@ -3,10 +3,10 @@
 //! This module provides configuration and information for the ClickHouse binary.
 //! All actual operations (install, run, check) are handled by the common manager.
 
-use crate::utils::bin::manager::{BinaryInfoProvider, SystemTarget};
+use crate::utils::bin::manager::{BinResult, BinaryInfoProvider, SystemTarget};
 
 /// ClickHouse binary information provider
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct ClickhouseProvider;
 
 impl ClickhouseProvider {
@@ -25,18 +25,15 @@ impl BinaryInfoProvider for ClickhouseProvider {
         "clickhouse"
     }
 
-    fn get_download_url(&self, target: &SystemTarget) -> String {
-        match target {
+    fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+        let url = match target {
             SystemTarget::MacOsAarch64 => {
-                "https://builds.clickhouse.com/master/macos-aarch64/clickhouse".to_string()
+                "https://builds.clickhouse.com/master/macos-aarch64/clickhouse"
             }
-            SystemTarget::MacOsX86_64 => {
-                "https://builds.clickhouse.com/master/macos/clickhouse".to_string()
-            }
-            SystemTarget::LinuxX86_64 => {
-                "https://builds.clickhouse.com/master/amd64/clickhouse".to_string()
-            }
-        }
+            SystemTarget::MacOsX86_64 => "https://builds.clickhouse.com/master/macos/clickhouse",
+            SystemTarget::LinuxX86_64 => "https://builds.clickhouse.com/master/amd64/clickhouse",
+        };
+        Ok(url.to_string())
     }
 
     fn version_args(&self) -> &[&str] {
@@ -50,6 +47,14 @@ impl BinaryInfoProvider for ClickhouseProvider {
             .find(|line| line.contains("ClickHouse"))
             .map(|line| line.trim().to_string())
     }
+
+    fn smoke_test_args(&self) -> Option<&[&str]> {
+        Some(&["local", "--query", "SELECT 1"])
+    }
+
+    fn validate_smoke_output(&self, output: &str) -> bool {
+        output.trim() == "1"
+    }
 }
 
 /// Create a new ClickHouse provider instance
@@ -67,6 +72,19 @@ mod tests {
         assert_eq!(provider.name(), "ClickHouse");
         assert_eq!(provider.local_name(), "clickhouse");
         assert_eq!(provider.version_args(), &["--version"]);
+        assert!(provider.has_version_flag());
+        assert!(!provider.is_long_running());
+    }
+
+    #[test]
+    fn test_clickhouse_smoke_output() {
+        let provider = ClickhouseProvider::new();
+        assert_eq!(
+            provider.smoke_test_args(),
+            Some(&["local", "--query", "SELECT 1"][..])
+        );
+        assert!(provider.validate_smoke_output("1\n"));
+        assert!(!provider.validate_smoke_output("<html>Not Found</html>"));
     }
 
     #[test]
@@ -96,15 +114,21 @@ mod tests {
     fn test_clickhouse_download_urls() {
         let provider = ClickhouseProvider::new();
 
-        let macos_arm_url = provider.get_download_url(&SystemTarget::MacOsAarch64);
+        let macos_arm_url = provider
+            .get_download_url(&SystemTarget::MacOsAarch64)
+            .unwrap();
         assert!(macos_arm_url.contains("macos-aarch64"));
         assert!(macos_arm_url.contains("builds.clickhouse.com"));
 
-        let macos_x86_url = provider.get_download_url(&SystemTarget::MacOsX86_64);
+        let macos_x86_url = provider
+            .get_download_url(&SystemTarget::MacOsX86_64)
+            .unwrap();
         assert!(macos_x86_url.contains("macos"));
         assert!(!macos_x86_url.contains("aarch64"));
 
-        let linux_url = provider.get_download_url(&SystemTarget::LinuxX86_64);
+        let linux_url = provider
+            .get_download_url(&SystemTarget::LinuxX86_64)
+            .unwrap();
         assert!(linux_url.contains("amd64"));
         assert!(linux_url.contains("builds.clickhouse.com"));
     }
@@ -0,0 +1,119 @@
+//! Audit trail of binary downloads
+//!
+//! Every install attempt appends one JSON line to `install.log` in the logs
+//! directory, whatever the verbosity, so there is always a record of what was
+//! fetched from where and whether it succeeded.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use super::manager::BinResult;
+
+/// Name of the install log inside the logs directory
+pub const INSTALL_LOG_FILE: &str = "install.log";
+
+/// Result of one install attempt
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "outcome", content = "error")]
+pub enum InstallOutcome {
+    Success,
+    Failed(String),
+}
+
+/// One line of `install.log`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstallRecord {
+    /// RFC 3339 time the attempt finished
+    pub timestamp: String,
+    /// Provider name of the binary
+    pub binary: String,
+    /// URL the binary was fetched from, or the last one tried on failure
+    pub url: Option<String>,
+    /// Size of the downloaded binary in bytes
+    pub size: Option<u64>,
+    /// Hex SHA-256 of the downloaded binary
+    pub sha256: Option<String>,
+    /// Time spent downloading and writing the binary, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<u64>,
+    #[serde(flatten)]
+    pub outcome: InstallOutcome,
+}
+
+impl InstallRecord {
+    /// A record stamped with the current time
+    pub fn new(binary: &str, url: Option<&str>, outcome: InstallOutcome) -> Self {
+        Self {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            binary: binary.to_string(),
+            url: url.map(str::to_string),
+            size: None,
+            sha256: None,
+            duration_ms: None,
+            outcome,
+        }
+    }
+
+    /// Appends the record as a single line, creating the log and its directory if needed
+    pub fn append(&self, log_path: &Path) -> BinResult<()> {
+        if let Some(parent) = log_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        let mut line = serde_json::to_string(self)?;
+        line.push('\n');
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(log_path)?
+            .write_all(line.as_bytes())?;
+        Ok(())
+    }
+}
+
+/// Reads every record of an install log, skipping lines that cannot be parsed
+#[allow(dead_code)]
+pub fn read_install_log(log_path: &Path) -> BinResult<Vec<InstallRecord>> {
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(log_path)?;
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_append_and_read_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("logs").join(INSTALL_LOG_FILE);
+
+        let mut success = InstallRecord::new(
+            "agt",
+            Some("https://example.com/agt"),
+            InstallOutcome::Success,
+        );
+        success.size = Some(42);
+        success.append(&log_path).unwrap();
+        let failure = InstallRecord::new(
+            "s3fs",
+            None,
+            InstallOutcome::Failed("connection refused".to_string()),
+        );
+        failure.append(&log_path).unwrap();
+
+        let content = fs::read_to_string(&log_path).unwrap();
+        assert_eq!(content.lines().count(), 2);
+        assert!(content.contains(r#""outcome":"failed","error":"connection refused""#));
+        assert_eq!(read_install_log(&log_path).unwrap(), vec![success, failure]);
+    }
+}
@@ -0,0 +1,87 @@
+//! Typed names for the built-in binaries
+//!
+//! Internal code refers to a binary with [`BinaryKind`] instead of a string,
+//! so a typo is a compile error. User input keeps going through the
+//! string-based [`ProviderRegistry::get_provider`](super::manager::ProviderRegistry::get_provider).
+
+use std::fmt;
+use std::str::FromStr;
+
+use super::manager::{BinaryInfoProvider, registry};
+
+/// One of the binaries managed by the CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BinaryKind {
+    S3fs,
+    Clickhouse,
+    Agt,
+}
+
+impl BinaryKind {
+    /// Every kind, in registration order
+    #[allow(dead_code)]
+    pub const ALL: [BinaryKind; 3] = [Self::S3fs, Self::Clickhouse, Self::Agt];
+
+    /// The provider's local name, which is also its file name in the bin dir
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::S3fs => "s3fs",
+            Self::Clickhouse => "clickhouse",
+            Self::Agt => "agt",
+        }
+    }
+
+    /// The registered provider of this binary
+    pub fn provider(self) -> &'static dyn BinaryInfoProvider {
+        provider_for(self)
+    }
+}
+
+/// The registered provider of a binary
+pub fn provider_for(kind: BinaryKind) -> &'static dyn BinaryInfoProvider {
+    registry()
+        .get_provider(kind.as_str())
+        .expect("every BinaryKind has a registered provider")
+}
+
+impl fmt::Display for BinaryKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for BinaryKind {
+    type Err = String;
+
+    /// Accepts the local or display name of a provider, ignoring case
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "s3fs" => Ok(Self::S3fs),
+            "clickhouse" => Ok(Self::Clickhouse),
+            "agt" => Ok(Self::Agt),
+            _ => Err(format!("Unknown binary '{}'", s)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kind_string_round_trip() {
+        for kind in BinaryKind::ALL {
+            assert_eq!(kind.to_string().parse::<BinaryKind>(), Ok(kind));
+        }
+        assert_eq!("ClickHouse".parse(), Ok(BinaryKind::Clickhouse));
+        assert!("postgres".parse::<BinaryKind>().is_err());
+    }
+
+    #[test]
+    fn test_every_kind_resolves_to_a_provider() {
+        for kind in BinaryKind::ALL {
+            assert_eq!(provider_for(kind).local_name(), kind.as_str());
+        }
+        assert_eq!(BinaryKind::ALL.len(), registry().providers().count());
+    }
+}
@@ -5,14 +5,32 @@
 //! and provider coordination in a single, efficient module.
 
 use futures_util::TryStreamExt;
-use indicatif::{ProgressBar, ProgressStyle};
-use reqwest::Client;
+use indicatif::{HumanBytes, MultiProgress, ProgressBar, ProgressStyle};
+use reqwest::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+use reqwest::{Client, StatusCode};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fmt;
 use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::process::{ExitStatus, Stdio};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
 
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+use crate::utils::app::events::{Event, EventEmitter, PROGRESS_INTERVAL, ProgressThrottle};
+use crate::utils::bin::bandwidth;
+use crate::utils::bin::install_log::{InstallOutcome, InstallRecord};
+use crate::utils::bin::manifest::{BinManifest, CacheValidators, ManifestEntry};
+use crate::utils::fs::{sha256_file, temp_file_path};
+use crate::utils::net::{ClientOptions, RetryPolicy, is_transient_network_error, retry};
+
 // Re-export binary providers
 pub use crate::utils::bin::agt::provider as agt;
 pub use crate::utils::bin::clickhouse::provider as clickhouse;
@@ -21,6 +39,9 @@ pub use crate::utils::bin::s3fs::provider as s3fs;
 /// Result type for binary operations
 pub type BinResult<T> = Result<T, Box<dyn std::error::Error>>;
 
+/// Set to `0` or `false` to keep x86_64 binaries when running under Rosetta
+pub const PREFER_NATIVE_ARCH_ENV: &str = "AGNOSTIC_PREFER_NATIVE_ARCH";
+
 /// Supported system architectures and platforms
 #[derive(Debug, Clone, PartialEq)]
 pub enum SystemTarget {
@@ -31,21 +52,332 @@ pub enum SystemTarget {
 
 impl SystemTarget {
     /// Detect the current system platform and architecture
+    ///
+    /// An x86_64 build running under Rosetta on Apple Silicon resolves to
+    /// `macos-aarch64` so native binaries are used, unless
+    /// [`PREFER_NATIVE_ARCH_ENV`] is set to `0` or `false`.
     pub fn detect() -> BinResult<Self> {
         let os = std::env::consts::OS;
         let arch = std::env::consts::ARCH;
+        let translated = is_rosetta_translated();
+        let prefer_native = std::env::var(PREFER_NATIVE_ARCH_ENV)
+            .map_or(true, |value| !matches!(value.trim(), "0" | "false"));
+
+        let target = Self::resolve(os, arch, translated, prefer_native)?;
+        if translated {
+            static NOTICE: std::sync::Once = std::sync::Once::new();
+            NOTICE.call_once(|| {
+                if target == SystemTarget::MacOsAarch64 {
+                    eprintln!(
+                        "Note: running under Rosetta, using native macos-aarch64 binaries \
+                         (set {}=0 to keep x86_64)",
+                        PREFER_NATIVE_ARCH_ENV
+                    );
+                } else {
+                    eprintln!(
+                        "Hint: running under Rosetta; unset {} to use faster native arm64 binaries",
+                        PREFER_NATIVE_ARCH_ENV
+                    );
+                }
+            });
+        }
+
+        Ok(target)
+    }
 
+    /// Maps an OS/architecture pair to a target, accounting for Rosetta translation
+    fn resolve(os: &str, arch: &str, translated: bool, prefer_native: bool) -> BinResult<Self> {
         match (os, arch) {
             ("macos", "aarch64") => Ok(SystemTarget::MacOsAarch64),
+            ("macos", "x86_64") if translated && prefer_native => Ok(SystemTarget::MacOsAarch64),
             ("macos", "x86_64") => Ok(SystemTarget::MacOsX86_64),
             ("linux", "x86_64") => Ok(SystemTarget::LinuxX86_64),
             _ => Err(format!("Unsupported system: {} {}", os, arch).into()),
         }
     }
+
+    /// All supported targets
+    pub fn all() -> &'static [SystemTarget] {
+        &[
+            SystemTarget::MacOsAarch64,
+            SystemTarget::MacOsX86_64,
+            SystemTarget::LinuxX86_64,
+        ]
+    }
+}
+
+/// Whether this process is an x86_64 binary translated by Rosetta
+#[cfg(target_os = "macos")]
+fn is_rosetta_translated() -> bool {
+    std::process::Command::new("sysctl")
+        .args(["-n", "sysctl.proc_translated"])
+        .output()
+        .is_ok_and(|output| {
+            output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "1"
+        })
+}
+
+/// Whether this process is an x86_64 binary translated by Rosetta
+#[cfg(not(target_os = "macos"))]
+fn is_rosetta_translated() -> bool {
+    false
+}
+
+impl fmt::Display for SystemTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SystemTarget::MacOsAarch64 => "macos-aarch64",
+            SystemTarget::MacOsX86_64 => "macos-x86_64",
+            SystemTarget::LinuxX86_64 => "linux-x86_64",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for SystemTarget {
+    type Err = String;
+
+    /// Parse a target from an `<os>-<arch>` string such as `linux-x86_64`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "macos-aarch64" | "macos-arm64" | "darwin-arm64" => Ok(SystemTarget::MacOsAarch64),
+            "macos-x86_64" | "macos-amd64" | "darwin-amd64" => Ok(SystemTarget::MacOsX86_64),
+            "linux-x86_64" | "linux-amd64" => Ok(SystemTarget::LinuxX86_64),
+            _ => {
+                let supported: Vec<String> =
+                    SystemTarget::all().iter().map(|t| t.to_string()).collect();
+                Err(format!(
+                    "Unsupported target '{}'. Supported targets: {}",
+                    s,
+                    supported.join(", ")
+                ))
+            }
+        }
+    }
+}
+
+/// Permission bits applied to installed binaries unless overridden with `--chmod`
+pub const DEFAULT_BINARY_MODE: u32 = 0o755;
+
+/// Parses an octal permission mode such as `755` or `0o750`
+///
+/// The owner executable bit is required, since installed binaries must be runnable.
+pub fn parse_binary_mode(s: &str) -> Result<u32, String> {
+    let digits = s.trim_start_matches("0o");
+    let mode = u32::from_str_radix(digits, 8)
+        .map_err(|_| format!("Invalid mode '{}': expected an octal value such as 755", s))?;
+
+    if mode > 0o777 {
+        return Err(format!("Invalid mode '{}': must be at most 777", s));
+    }
+    if mode & 0o100 == 0 {
+        return Err(format!(
+            "Invalid mode '{}': the owner executable bit (0o100) is required",
+            s
+        ));
+    }
+
+    Ok(mode)
+}
+
+/// Environment variable holding the base URL of a mirror of GitHub release assets
+pub const BIN_MIRROR_ENV: &str = "AGNOSTIC_BIN_MIRROR";
+
+/// Rewrites a GitHub release URL onto the mirror configured in [`BIN_MIRROR_ENV`]
+///
+/// The mirror is expected to serve the same `<owner>/<repo>/releases/download/...`
+/// paths as GitHub. Returns `None` when no mirror is configured.
+pub fn github_mirror_url(url: &str) -> Option<String> {
+    let mirror = std::env::var(BIN_MIRROR_ENV).ok()?;
+    mirror_url(url, &mirror)
+}
+
+fn mirror_url(url: &str, mirror: &str) -> Option<String> {
+    let mirror = mirror.trim().trim_end_matches('/');
+    if mirror.is_empty() {
+        return None;
+    }
+    let path = url.strip_prefix("https://github.com/")?;
+    Some(format!("{}/{}", mirror, path))
+}
+
+/// Options controlling how binaries are installed
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Re-download binaries even if they are already installed
+    pub force_download: bool,
+    /// Install binaries for this target instead of the detected host
+    pub target: Option<SystemTarget>,
+    /// Permission bits applied to installed binaries (defaults to [`DEFAULT_BINARY_MODE`])
+    pub mode: Option<u32>,
+    /// Receives download and install lifecycle events
+    pub events: EventEmitter,
+    /// Maximum number of binaries installed at once (defaults to all of them)
+    pub concurrency: Option<NonZeroUsize>,
+    /// Without an explicit `concurrency`, pick one from a quick bandwidth probe
+    pub auto_concurrency: bool,
+    /// One-off version pins from `--bin-version`, taking precedence over the defaults
+    pub versions: Vec<VersionOverride>,
+    /// HTTP settings (TLS, `User-Agent`) used for downloads
+    pub http: ClientOptions,
+    /// Retries of each download URL after a transient network failure
+    pub retry: RetryPolicy,
+    /// Which providers [`ProviderRegistry::ensure_all_binaries`] installs
+    pub selection: BinarySelection,
+    /// JSON-lines audit log each install attempt is appended to, see [`super::install_log`]
+    pub install_log: Option<PathBuf>,
+    /// Don't run installed binaries to check their version or smoke test them
+    pub skip_verify: bool,
+    /// Shared by the progress bars of parallel downloads so they don't overwrite each other
+    pub progress: MultiProgress,
+}
+
+/// Subset of binaries to install, from `--only-binary` / `--skip-binary`
+///
+/// Names may be display or local names. An empty `only` selects every provider.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BinarySelection {
+    pub only: Vec<String>,
+    pub skip: Vec<String>,
+}
+
+impl BinarySelection {
+    /// Whether nothing is filtered out
+    pub fn is_all(&self) -> bool {
+        self.only.is_empty() && self.skip.is_empty()
+    }
+}
+
+/// Parses a binary name from the command line, checking it is a known provider
+pub fn parse_binary_name(name: &str) -> Result<String, String> {
+    registry()
+        .get_provider(name.trim())
+        .map(|provider| provider.name().to_string())
+        .ok_or_else(|| {
+            let names: Vec<&str> = registry().providers().map(|p| p.local_name()).collect();
+            format!(
+                "Unknown binary '{}'. Available binaries: {}",
+                name,
+                names.join(", ")
+            )
+        })
+}
+
+/// A `name=version` pin from `--bin-version`, e.g. `agt=0.0.24`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VersionOverride {
+    /// Canonical provider name (as returned by [`BinaryInfoProvider::name`])
+    pub name: String,
+    pub version: String,
+}
+
+impl FromStr for VersionOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, version) = s
+            .split_once('=')
+            .ok_or_else(|| format!("Invalid version override '{}': expected NAME=VERSION", s))?;
+        let (name, version) = (name.trim(), version.trim());
+
+        if name.is_empty() || version.is_empty() {
+            return Err(format!(
+                "Invalid version override '{}': expected NAME=VERSION",
+                s
+            ));
+        }
+        if version.contains(|c: char| c.is_whitespace() || c == '/') {
+            return Err(format!(
+                "Invalid version '{}': must not contain whitespace or '/'",
+                version
+            ));
+        }
+
+        let provider = registry().get_provider(name).ok_or_else(|| {
+            let names: Vec<&str> = registry().providers().map(|p| p.local_name()).collect();
+            format!(
+                "Unknown binary '{}'. Available binaries: {}",
+                name,
+                names.join(", ")
+            )
+        })?;
+
+        Ok(Self {
+            name: provider.name().to_string(),
+            version: version.to_string(),
+        })
+    }
+}
+
+impl InstallOptions {
+    /// Prints a status line above the download progress bars
+    fn status(&self, line: fmt::Arguments<'_>) {
+        self.progress.suspend(|| println!("{}", line));
+    }
+
+    /// Prints a warning above the download progress bars
+    fn warn(&self, line: fmt::Arguments<'_>) {
+        self.progress.suspend(|| eprintln!("{}", line));
+    }
+
+    /// Resolve the target to install for, falling back to host detection
+    pub fn resolve_target(&self) -> BinResult<SystemTarget> {
+        match &self.target {
+            Some(target) => Ok(target.clone()),
+            None => SystemTarget::detect(),
+        }
+    }
+
+    /// The pinned version of a provider, if overridden with `--bin-version`
+    pub fn version_for(&self, provider: &dyn BinaryInfoProvider) -> Option<&str> {
+        self.versions
+            .iter()
+            .rev()
+            .find(|o| o.name.eq_ignore_ascii_case(provider.name()))
+            .map(|o| o.version.as_str())
+    }
+
+    /// Whether a provider must be downloaded even if already installed
+    ///
+    /// A version pin always downloads, since the installed binary may be another version.
+    pub fn forces_download(&self, provider: &dyn BinaryInfoProvider) -> bool {
+        self.force_download || self.version_for(provider).is_some()
+    }
+
+    /// Resolve the candidate download URLs of a provider, honouring version pins
+    pub fn download_urls(
+        &self,
+        provider: &dyn BinaryInfoProvider,
+        target: &SystemTarget,
+    ) -> BinResult<Vec<String>> {
+        match self.version_for(provider) {
+            Some(version) => {
+                let url = provider
+                    .get_versioned_download_url(target, version)
+                    .ok_or_else(|| {
+                        format!(
+                            "{} does not support pinning a version with --bin-version",
+                            provider.name()
+                        )
+                    })?;
+                let mirror = github_mirror_url(&url);
+                Ok(std::iter::once(url).chain(mirror).collect())
+            }
+            None => provider.get_download_urls(target),
+        }
+    }
+
+    /// Whether binaries are installed for a platform other than the host
+    pub fn is_cross_install(&self) -> bool {
+        match &self.target {
+            Some(target) => SystemTarget::detect().map_or(true, |host| &host != target),
+            None => false,
+        }
+    }
 }
 
 /// Information about a binary's status
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BinaryInfo {
     /// Name of the binary
     pub name: String,
@@ -57,6 +389,22 @@ pub struct BinaryInfo {
     pub executable: bool,
     /// Size of the binary in bytes
     pub size: Option<u64>,
+    /// Last modification time, serialized as seconds since the Unix epoch
+    #[serde(rename = "modified_unix_secs", serialize_with = "serialize_unix_secs")]
+    pub modified: Option<std::time::SystemTime>,
+    /// SHA-256 of the binary, only filled in by [`BinaryInfo::with_checksum`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+}
+
+fn serialize_unix_secs<S: serde::Serializer>(
+    time: &Option<std::time::SystemTime>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let secs = time
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+    serializer.serialize_some(&secs)
 }
 
 impl BinaryInfo {
@@ -68,8 +416,8 @@ impl BinaryInfo {
         } else {
             false
         };
-        let size = if exists {
-            std::fs::metadata(&path).ok().map(|m| m.len())
+        let metadata = if exists {
+            std::fs::metadata(&path).ok()
         } else {
             None
         };
@@ -79,7 +427,9 @@ impl BinaryInfo {
             path,
             exists,
             executable,
-            size,
+            size: metadata.as_ref().map(|m| m.len()),
+            modified: metadata.and_then(|m| m.modified().ok()),
+            sha256: None,
         }
     }
 
@@ -87,6 +437,14 @@ impl BinaryInfo {
     pub fn is_ready(&self) -> bool {
         self.exists && self.executable
     }
+
+    /// Hashes the binary when it exists, reading the whole file
+    pub fn with_checksum(mut self) -> io::Result<Self> {
+        if self.exists {
+            self.sha256 = Some(sha256_file(&self.path)?);
+        }
+        Ok(self)
+    }
 }
 
 /// Trait that all binary information providers must implement
@@ -98,13 +456,62 @@ pub trait BinaryInfoProvider: Send + Sync {
     fn local_name(&self) -> &'static str;
 
     /// Generate the download URL for this binary on the given platform
-    fn get_download_url(&self, target: &SystemTarget) -> String;
+    ///
+    /// Fails for platforms the provider publishes no build for.
+    fn get_download_url(&self, target: &SystemTarget) -> BinResult<String>;
+
+    /// Candidate download URLs in the order they should be tried (primary first)
+    ///
+    /// Defaults to the single [`get_download_url`](Self::get_download_url); providers
+    /// hosted on GitHub releases add a mirror.
+    fn get_download_urls(&self, target: &SystemTarget) -> BinResult<Vec<String>> {
+        Ok(vec![self.get_download_url(target)?])
+    }
 
     /// Arguments to pass to get version info (e.g., ["--version"] or ["--help"])
     fn version_args(&self) -> &[&str];
 
     /// Parse version information from the command output
     fn parse_version_output(&self, output: &str) -> Option<String>;
+
+    /// Generate the download URL of a specific upstream version, if the provider can pin one
+    fn get_versioned_download_url(&self, _target: &SystemTarget, _version: &str) -> Option<String> {
+        None
+    }
+
+    /// Expected SHA-256 digest of the binary for the given platform, when published
+    fn expected_sha256(&self, _target: &SystemTarget) -> Option<&'static str> {
+        None
+    }
+
+    /// Arguments of a post-install liveness check stronger than the version check
+    ///
+    /// `None` (the default) skips the smoke test.
+    fn smoke_test_args(&self) -> Option<&[&str]> {
+        None
+    }
+
+    /// Whether the smoke test output shows a working binary
+    fn validate_smoke_output(&self, _output: &str) -> bool {
+        true
+    }
+
+    /// Whether [`version_args`](Self::version_args) makes the binary print its version
+    ///
+    /// When `false`, the version command only proves the binary starts: its
+    /// exit status is not treated as a failure and the version comes from
+    /// [`parse_version_output`](Self::parse_version_output) alone.
+    fn has_version_flag(&self) -> bool {
+        true
+    }
+
+    /// Whether the binary is a server that keeps running until stopped
+    ///
+    /// Output of long-running binaries is streamed rather than captured, and
+    /// they get no smoke test.
+    fn is_long_running(&self) -> bool {
+        false
+    }
 }
 
 /// Registry of all available binary providers
@@ -118,17 +525,52 @@ impl ProviderRegistry {
         let providers: Vec<Box<dyn BinaryInfoProvider>> =
             vec![Box::new(s3fs()), Box::new(clickhouse()), Box::new(agt())];
 
-        Self { providers }
+        Self::try_new(providers).expect("built-in binary providers must have unique names")
     }
 
-    /// Get a provider by name
+    /// Create a registry, rejecting providers whose names or local filenames clash
+    ///
+    /// Providers are looked up by either name (case-insensitive), so a display
+    /// name may not collide with another provider's local filename either.
+    pub fn try_new(providers: Vec<Box<dyn BinaryInfoProvider>>) -> BinResult<Self> {
+        let mut seen = HashSet::new();
+
+        for provider in &providers {
+            let mut keys = vec![provider.name().to_lowercase()];
+            if !provider.local_name().eq_ignore_ascii_case(provider.name()) {
+                keys.push(provider.local_name().to_lowercase());
+            }
+
+            for key in keys {
+                if !seen.insert(key.clone()) {
+                    return Err(format!(
+                        "Binary provider '{}' clashes with another provider on '{}'",
+                        provider.name(),
+                        key
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(Self { providers })
+    }
+
+    /// Get a provider by display name or local filename (case-insensitive)
     pub fn get_provider(&self, name: &str) -> Option<&dyn BinaryInfoProvider> {
         self.providers
             .iter()
-            .find(|p| p.name() == name)
+            .find(|p| {
+                p.name().eq_ignore_ascii_case(name) || p.local_name().eq_ignore_ascii_case(name)
+            })
             .map(|p| p.as_ref())
     }
 
+    /// Iterate over all registered providers, in registration order
+    pub fn providers(&self) -> impl Iterator<Item = &dyn BinaryInfoProvider> {
+        self.providers.iter().map(|p| p.as_ref())
+    }
+
     /// Get status of all binary providers
     pub fn get_all_status<P: AsRef<Path>>(&self, bin_dir: P) -> Vec<BinaryInfo> {
         let bin_dir = bin_dir.as_ref();
@@ -138,35 +580,130 @@ impl ProviderRegistry {
             .collect()
     }
 
+    /// Providers picked by `selection`, in registration order
+    ///
+    /// Fails on a name that matches no registered provider.
+    pub fn select(&self, selection: &BinarySelection) -> BinResult<Vec<&dyn BinaryInfoProvider>> {
+        let resolve = |names: &[String]| -> BinResult<HashSet<&'static str>> {
+            names
+                .iter()
+                .map(|name| {
+                    self.get_provider(name)
+                        .map(|provider| provider.name())
+                        .ok_or_else(|| format!("Unknown binary provider: {}", name).into())
+                })
+                .collect()
+        };
+        let only = resolve(&selection.only)?;
+        let skip = resolve(&selection.skip)?;
+
+        Ok(self
+            .providers()
+            .filter(|provider| only.is_empty() || only.contains(provider.name()))
+            .filter(|provider| !skip.contains(provider.name()))
+            .collect())
+    }
+
     /// Ensures all required binaries are installed
-    pub async fn ensure_all_binaries<P: AsRef<Path>>(&self, bin_dir: P) -> BinResult<Vec<PathBuf>> {
+    pub async fn ensure_all_binaries<P: AsRef<Path>>(
+        &self,
+        bin_dir: P,
+        options: &InstallOptions,
+    ) -> BinResult<Vec<PathBuf>> {
         let bin_dir = bin_dir.as_ref();
-        let mut installed_binaries = Vec::new();
-        let mut newly_installed = 0;
-
-        for provider in &self.providers {
-            let binary_exists = get_binary_info(provider.as_ref(), bin_dir).exists;
-            if !binary_exists {
-                println!("Installing {} binary...", provider.name());
+        let providers = self.select(&options.selection)?;
+        if !options.selection.is_all() {
+            let names: Vec<&str> = providers.iter().map(|p| p.name()).collect();
+            options.status(format_args!("Selected binaries: {}", names.join(", ")));
+        }
+        let limit = match options.concurrency {
+            Some(limit) => limit.get(),
+            None if options.auto_concurrency => {
+                probed_concurrency(&providers, bin_dir, options).await
             }
-            let binary_path = install_binary(provider.as_ref(), bin_dir, false).await?;
-            if !binary_exists {
-                newly_installed += 1;
+            None => providers.len().max(1),
+        };
+        let semaphore = Semaphore::new(limit);
+
+        let installs = providers.into_iter().map(|provider| {
+            let semaphore = &semaphore;
+            async move {
+                let _permit = semaphore.acquire().await?;
+                let binary_exists = get_binary_info(provider, bin_dir).exists;
+                let needs_install = !binary_exists || options.forces_download(provider);
+                if needs_install {
+                    options.status(format_args!("Installing {} binary...", provider.name()));
+                }
+                let started = Instant::now();
+                let binary_path = install_binary(provider, bin_dir, options).await?;
+                let timing = needs_install.then(|| (provider.name(), started.elapsed()));
+                BinResult::Ok((binary_path, timing))
             }
-            installed_binaries.push(binary_path);
-        }
+        });
 
-        if newly_installed > 0 {
-            println!(
+        let results = futures_util::future::try_join_all(installs).await?;
+        let timings: Vec<(&str, Duration)> = results.iter().filter_map(|(_, t)| *t).collect();
+        let installed_binaries = results.into_iter().map(|(path, _)| path).collect();
+
+        if !timings.is_empty() {
+            options.status(format_args!(
                 "Binary setup completed: {} new binaries installed",
-                newly_installed
-            );
+                timings.len()
+            ));
+            options.status(format_args!("Install times: {}", format_timings(&timings)));
         }
 
         Ok(installed_binaries)
     }
 }
 
+/// Concurrency picked by `--auto-concurrency` from a bandwidth probe
+///
+/// Probes the primary URL of the first binary that is about to be downloaded.
+/// Falls back to one download at a time when the probe fails, and to all of
+/// them when nothing needs downloading.
+async fn probed_concurrency(
+    providers: &[&dyn BinaryInfoProvider],
+    bin_dir: &Path,
+    options: &InstallOptions,
+) -> usize {
+    let all = providers.len().max(1);
+    let Some(provider) = providers.iter().find(|provider| {
+        !get_binary_info(**provider, bin_dir).exists || options.forces_download(**provider)
+    }) else {
+        return all;
+    };
+
+    let probe = async {
+        let target = options.resolve_target()?;
+        let urls = options.download_urls(*provider, &target)?;
+        let url = urls
+            .first()
+            .ok_or_else(|| format!("No download URL for {}", provider.name()))?;
+        bandwidth::probe_throughput(&options.http.build()?, url).await
+    };
+    match probe.await {
+        Ok(throughput) => {
+            let limit = bandwidth::concurrency_for_throughput(throughput)
+                .get()
+                .min(all);
+            options.status(format_args!(
+                "Measured download speed: {}/s, installing up to {} binaries at once",
+                HumanBytes(throughput as u64),
+                limit
+            ));
+            limit
+        }
+        Err(e) => {
+            options.warn(format_args!(
+                "Warning: bandwidth probe failed ({}), installing one binary at a time",
+                e
+            ));
+            1
+        }
+    }
+}
+
 /// Global provider registry instance
 static REGISTRY: std::sync::LazyLock<ProviderRegistry> =
     std::sync::LazyLock::new(ProviderRegistry::new);
@@ -176,13 +713,90 @@ pub fn registry() -> &'static ProviderRegistry {
     &REGISTRY
 }
 
+/// Build a registry from custom providers, checking that their names are unique
+#[allow(dead_code)]
+pub fn registry_try_new(
+    providers: Vec<Box<dyn BinaryInfoProvider>>,
+) -> BinResult<ProviderRegistry> {
+    ProviderRegistry::try_new(providers)
+}
+
 // Core utility functions
 
 /// Downloads a binary from a URL with progress bar
-pub async fn download_binary_with_progress(url: &str, binary_name: &str) -> BinResult<Vec<u8>> {
-    let client = Client::new();
-    let response = client.get(url).send().await?;
+#[allow(dead_code)]
+pub async fn download_binary_with_progress(
+    url: &str,
+    binary_name: &str,
+) -> BinResult<DownloadResult> {
+    let client = ClientOptions::default().build()?;
+    download_binary_with_events(&client, url, binary_name, &EventEmitter::default()).await
+}
+
+/// Downloads a binary with a progress bar, also reporting progress as events
+pub async fn download_binary_with_events(
+    client: &Client,
+    url: &str,
+    binary_name: &str,
+    events: &EventEmitter,
+) -> BinResult<DownloadResult> {
+    let progress = MultiProgress::new();
+    match download_binary_if_modified(client, url, binary_name, events, &progress, None).await? {
+        Download::Fetched(result, _) => Ok(result),
+        Download::NotModified(_) => Err(format!("Unexpected HTTP 304 from {}", url).into()),
+    }
+}
+
+/// A downloaded binary, with the digest computed while it streamed in
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DownloadResult {
+    pub bytes: Vec<u8>,
+    /// Lowercase hex SHA-256 of `bytes`, so checking it needs no second pass
+    pub sha256: String,
+    pub len: u64,
+}
+
+/// Result of a download that may be revalidated against the installed binary
+pub enum Download {
+    /// The binary content, with the validators the server sent for it
+    Fetched(DownloadResult, CacheValidators),
+    /// The server answered 304: the installed binary is still current, with
+    /// its validators as refreshed by the response
+    NotModified(CacheValidators),
+}
+
+/// Downloads a binary like [`download_binary_with_events`], as a conditional request when `cached` is given
+///
+/// The progress bar is added to `progress`, so parallel downloads sharing it each get their own line.
+pub async fn download_binary_if_modified(
+    client: &Client,
+    url: &str,
+    binary_name: &str,
+    events: &EventEmitter,
+    progress: &MultiProgress,
+    cached: Option<&CacheValidators>,
+) -> BinResult<Download> {
+    let mut request = client.get(url);
+    if let Some(cached) = cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+    let response = request.send().await?;
 
+    if let Some(cached) = cached
+        && response.status() == StatusCode::NOT_MODIFIED
+    {
+        // A 304 may carry updated validators; keep the cached ones it leaves out
+        let fresh = CacheValidators::from_headers(response.headers());
+        return Ok(Download::NotModified(CacheValidators {
+            etag: fresh.etag.or_else(|| cached.etag.clone()),
+            last_modified: fresh.last_modified.or_else(|| cached.last_modified.clone()),
+        }));
+    }
     if !response.status().is_success() {
         return Err(format!(
             "Failed to download {} binary: HTTP {}",
@@ -192,10 +806,16 @@ pub async fn download_binary_with_progress(url: &str, binary_name: &str) -> BinR
         .into());
     }
 
+    let validators = CacheValidators::from_headers(response.headers());
     let total_size = response.content_length();
-
-    // Create progress bar
-    let progress_bar = if let Some(size) = total_size {
+    events.emit(Event::DownloadStarted {
+        name: binary_name.to_string(),
+        url: url.to_string(),
+        total_bytes: total_size,
+    });
+
+    // Create progress bar, or a spinner when the server doesn't announce the size
+    let progress_bar = progress.add(if let Some(size) = total_size {
         let pb = ProgressBar::new(size);
         pb.set_style(
             ProgressStyle::default_bar()
@@ -203,56 +823,131 @@ pub async fn download_binary_with_progress(url: &str, binary_name: &str) -> BinR
                 .unwrap()
                 .progress_chars("#>-"),
         );
-        pb.set_message(format!("Downloading {}", binary_name));
-        Some(pb)
+        pb
     } else {
-        println!("Starting download (size unknown)...");
-        None
-    };
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .template("{spinner:.green} [{elapsed_precise}] {bytes} ({bytes_per_sec}) {msg}")
+                .unwrap(),
+        );
+        pb
+    });
+    progress_bar.set_message(format!("Downloading {}", binary_name));
 
-    // Stream the download with progress updates
+    // Stream the download with progress updates, hashing each chunk as it arrives
     let mut content = Vec::new();
+    let mut hasher = Sha256::new();
     let mut stream = response.bytes_stream();
+    let mut throttle = ProgressThrottle::new(PROGRESS_INTERVAL);
 
     while let Some(chunk) = stream.try_next().await? {
+        hasher.update(&chunk);
         content.extend_from_slice(&chunk);
-        if let Some(pb) = &progress_bar {
-            pb.set_position(content.len() as u64);
+        progress_bar.set_position(content.len() as u64);
+        if events.is_enabled() && throttle.ready() {
+            events.emit(Event::DownloadProgress {
+                name: binary_name.to_string(),
+                downloaded_bytes: content.len() as u64,
+                total_bytes: total_size,
+            });
         }
     }
 
-    if let Some(pb) = progress_bar {
-        pb.finish_with_message("Download completed");
-    } else {
-        println!("Download completed: {} bytes", content.len());
-    }
-
-    Ok(content)
+    progress_bar.finish_with_message(format!(
+        "Download completed: {}",
+        HumanBytes(content.len() as u64)
+    ));
+    events.emit(Event::DownloadComplete {
+        name: binary_name.to_string(),
+        bytes: content.len() as u64,
+    });
+
+    let result = DownloadResult {
+        len: content.len() as u64,
+        sha256: format!("{:x}", hasher.finalize()),
+        bytes: content,
+    };
+    Ok(Download::Fetched(result, validators))
 }
 
 /// Writes binary content to file and makes it executable
+#[allow(dead_code)]
 pub fn write_and_make_executable<P: AsRef<Path>>(binary_path: P, content: &[u8]) -> BinResult<()> {
+    write_with_mode(binary_path, content, DEFAULT_BINARY_MODE)
+}
+
+/// Writes binary content to file with the given permission bits (see [`set_binary_mode`])
+///
+/// The content is written to a temporary file in the same directory, made
+/// executable, then renamed over `binary_path`. Replacing a binary that is
+/// currently running therefore never fails with "text file busy", and a crash
+/// mid-write leaves the previous binary in place.
+pub fn write_with_mode<P: AsRef<Path>>(binary_path: P, content: &[u8], mode: u32) -> BinResult<()> {
     let binary_path = binary_path.as_ref();
+    let parent = binary_path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
 
     // Ensure parent directory exists
-    if let Some(parent) = binary_path.parent() {
-        fs::create_dir_all(parent)?;
+    fs::create_dir_all(parent)?;
+
+    let file_name = binary_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let part_path = temp_file_path(parent, Some(&format!(".{}", file_name)), Some(".part"));
+
+    let result = fs::write(&part_path, content)
+        .map_err(Into::into)
+        .and_then(|()| set_binary_mode(&part_path, mode))
+        .and_then(|()| fs::rename(&part_path, binary_path).map_err(Into::into));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&part_path);
     }
+    result
+}
 
-    // Write binary to file
-    fs::write(binary_path, content)?;
+/// Applies permission bits to an installed binary and checks it is now executable
+///
+/// On Unix the permissions are read back after being set, so a filesystem that
+/// silently drops them fails here rather than at the first exec.
+pub fn set_binary_mode<P: AsRef<Path>>(binary_path: P, mode: u32) -> BinResult<()> {
+    let binary_path = binary_path.as_ref();
 
-    // Make executable on Unix systems
     #[cfg(unix)]
     {
         let mut perms = fs::metadata(binary_path)?.permissions();
-        perms.set_mode(0o755);
+        perms.set_mode(mode);
         fs::set_permissions(binary_path, perms)?;
+
+        let actual = fs::metadata(binary_path)?.permissions().mode();
+        check_executable_mode(binary_path, actual)?;
     }
+    #[cfg(not(unix))]
+    let _ = (binary_path, mode);
 
     Ok(())
 }
 
+/// Checks that permission bits read back from disk include the owner executable bit
+fn check_executable_mode(binary_path: &Path, mode: u32) -> BinResult<()> {
+    if mode & 0o100 != 0 {
+        return Ok(());
+    }
+
+    Err(format!(
+        "{} is not executable after setting permissions (mode {:o}). \
+         The bin directory may be on a `noexec` mount or a filesystem that ignores \
+         permissions; try installing elsewhere with `--bin-dir`.",
+        binary_path.display(),
+        mode & 0o777
+    )
+    .into())
+}
+
 /// Check if a file exists and is executable
 pub fn is_executable<P: AsRef<Path>>(path: P) -> BinResult<bool> {
     let path = path.as_ref();
@@ -305,91 +1000,470 @@ pub fn get_binary_info<P: AsRef<Path>>(
 }
 
 /// Install a binary using provider information
+///
+/// When `options.target` names a platform other than the host, the binary is
+/// fetched for that platform and the post-install version check is skipped.
 pub async fn install_binary<P: AsRef<Path>>(
     provider: &dyn BinaryInfoProvider,
     bin_dir: P,
-    force_download: bool,
+    options: &InstallOptions,
 ) -> BinResult<PathBuf> {
     let bin_dir = bin_dir.as_ref();
     let binary_path = get_provider_binary_path(provider, bin_dir);
 
     // Check if binary already exists and is executable
-    if !force_download && binary_path.exists() && is_executable(&binary_path)? {
+    if !options.forces_download(provider) && binary_path.exists() && is_executable(&binary_path)? {
         return Ok(binary_path);
     }
 
-    let target = SystemTarget::detect()?;
-    let download_url = provider.get_download_url(&target);
+    download_and_install(provider, &binary_path, Some(bin_dir), options).await?;
+    Ok(binary_path)
+}
+
+/// Install a binary at an arbitrary location outside the managed bin directory
+///
+/// `path` is either the file to write or an existing directory, in which case
+/// the binary keeps its usual local name. The binary is always downloaded,
+/// checked against the published digest, verified and made executable, but
+/// not recorded in the bin manifest.
+pub async fn install_binary_to<P: AsRef<Path>>(
+    provider: &dyn BinaryInfoProvider,
+    path: P,
+    options: &InstallOptions,
+) -> BinResult<PathBuf> {
+    let path = path.as_ref();
+    let binary_path = if path.is_dir() {
+        path.join(provider.local_name())
+    } else {
+        path.to_path_buf()
+    };
+    ensure_writable_parent(&binary_path)?;
 
-    println!(
-        "Downloading {} binary for {}...",
-        provider.name(),
-        format!("{:?}", target).to_lowercase()
-    );
+    download_and_install(provider, &binary_path, None, options).await?;
+    Ok(binary_path)
+}
 
-    // Download the binary with progress
-    let content = download_binary_with_progress(&download_url, provider.name()).await?;
+/// Check that the directory `path` will be written to exists and accepts new files
+fn ensure_writable_parent(path: &Path) -> BinResult<()> {
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    if !parent.is_dir() {
+        return Err(format!("Directory {} does not exist", parent.display()).into());
+    }
 
-    // Write and make executable
-    write_and_make_executable(&binary_path, &content)?;
+    tempfile::tempfile_in(parent)
+        .map(drop)
+        .map_err(|e| format!("Cannot write to {}: {}", parent.display(), e).into())
+}
+
+/// Download, write and verify a binary at `binary_path`
+///
+/// A download whose digest differs from the provider's published one (unless a
+/// version is pinned) is rejected before it is written.
+///
+/// The install is recorded in the manifest of `manifest_dir` when given. An
+/// installed binary listed there with HTTP validators is revalidated rather
+/// than downloaded again, and kept when the server answers 304.
+async fn download_and_install(
+    provider: &dyn BinaryInfoProvider,
+    binary_path: &Path,
+    manifest_dir: Option<&Path>,
+    options: &InstallOptions,
+) -> BinResult<()> {
+    let target = options.resolve_target()?;
+    let download_urls = options.download_urls(provider, &target)?;
+
+    match options.version_for(provider) {
+        Some(version) => options.status(format_args!(
+            "Downloading {} binary {} for {}...",
+            provider.name(),
+            version,
+            format!("{:?}", target).to_lowercase()
+        )),
+        None => options.status(format_args!(
+            "Downloading {} binary for {}...",
+            provider.name(),
+            format!("{:?}", target).to_lowercase()
+        )),
+    }
+
+    // Download the binary with progress, falling back to the next mirror on failure
+    let started = Instant::now();
+    let client = options.http.build()?;
+    let manifest = match manifest_dir {
+        Some(dir) if binary_path.exists() && is_executable(binary_path)? => {
+            BinManifest::load(dir).ok()
+        }
+        _ => None,
+    };
+    let mut last_error = None;
+    let mut downloaded = None;
+    for url in &download_urls {
+        let cached = manifest
+            .as_ref()
+            .and_then(|manifest| manifest.validators_for(provider.name(), url));
+        let download = retry(
+            || {
+                download_binary_if_modified(
+                    &client,
+                    url,
+                    provider.name(),
+                    &options.events,
+                    &options.progress,
+                    cached,
+                )
+            },
+            &options.retry,
+            |e| is_transient_network_error(e.as_ref()),
+        );
+        match download.await {
+            Ok(Download::Fetched(result, validators)) => {
+                downloaded = Some((url, result, validators));
+                break;
+            }
+            Ok(Download::NotModified(validators)) => {
+                options.status(format_args!(
+                    "{} binary is up to date ({} not modified)",
+                    provider.name(),
+                    url
+                ));
+                let record =
+                    InstallRecord::new(provider.name(), Some(url), InstallOutcome::Success);
+                log_install(options, record);
+                if let Some(manifest_dir) = manifest_dir {
+                    record_in_manifest(options, manifest_dir, provider, url, &target, validators);
+                }
+                return Ok(());
+            }
+            Err(e) => {
+                options.warn(format_args!("Download from {} failed: {}", url, e));
+                last_error = Some(e);
+            }
+        }
+    }
+    let (source_url, download, validators) = match downloaded {
+        Some(downloaded) => downloaded,
+        None => {
+            let error = last_error.unwrap_or_else(|| {
+                format!("No download URL for {} binary", provider.name()).into()
+            });
+            let record = InstallRecord::new(
+                provider.name(),
+                download_urls.last().map(String::as_str),
+                InstallOutcome::Failed(error.to_string()),
+            );
+            log_install(options, record);
+            return Err(error);
+        }
+    };
+    if download_urls.len() > 1 {
+        options.status(format_args!(
+            "Downloaded {} binary from {}",
+            provider.name(),
+            source_url
+        ));
+    }
+
+    // Check the digest computed while streaming, before anything is written
+    if options.version_for(provider).is_none()
+        && let Some(expected) = provider.expected_sha256(&target)
+        && !download.sha256.eq_ignore_ascii_case(expected)
+    {
+        let error = format!(
+            "Checksum mismatch for {} binary from {}: expected {}, got {}",
+            provider.name(),
+            source_url,
+            expected,
+            download.sha256
+        );
+        let mut record = InstallRecord::new(
+            provider.name(),
+            Some(source_url),
+            InstallOutcome::Failed(error.clone()),
+        );
+        record.size = Some(download.len);
+        record.sha256 = Some(download.sha256);
+        log_install(options, record);
+        return Err(error.into());
+    }
 
-    println!(
-        "{} binary installed successfully at: {}",
+    // Write and make executable
+    let written = write_with_mode(
+        binary_path,
+        &download.bytes,
+        options.mode.unwrap_or(DEFAULT_BINARY_MODE),
+    );
+    let elapsed = started.elapsed();
+    let mut record = InstallRecord::new(
         provider.name(),
-        binary_path.display()
+        Some(source_url),
+        match &written {
+            Ok(()) => InstallOutcome::Success,
+            Err(e) => InstallOutcome::Failed(e.to_string()),
+        },
     );
+    record.size = Some(download.len);
+    record.sha256 = Some(download.sha256);
+    record.duration_ms = Some(elapsed.as_millis() as u64);
+    log_install(options, record);
+    written?;
+    if let Some(manifest_dir) = manifest_dir {
+        record_in_manifest(
+            options,
+            manifest_dir,
+            provider,
+            source_url,
+            &target,
+            validators,
+        );
+    }
+    options.events.emit(Event::InstallComplete {
+        name: provider.name().to_string(),
+        path: binary_path.to_path_buf(),
+        url: source_url.clone(),
+        elapsed_ms: elapsed.as_millis() as u64,
+    });
+
+    options.status(format_args!(
+        "{} binary installed successfully at: {} ({})",
+        provider.name(),
+        binary_path.display(),
+        format_elapsed(elapsed)
+    ));
+
+    if options.is_cross_install() {
+        options.status(format_args!(
+            "Skipping {} verification: installed for {} on a different host",
+            provider.name(),
+            target
+        ));
+        return Ok(());
+    }
+    if options.skip_verify {
+        options.status(format_args!(
+            "Skipping {} verification: --skip-verify is set",
+            provider.name()
+        ));
+        return Ok(());
+    }
 
     // Verify the binary works by checking version
-    println!("Verifying {} binary...", provider.name());
-    match get_binary_version(provider, &bin_dir).await {
-        Ok(version) => println!("{} version: {}", provider.name(), version),
+    options.status(format_args!("Verifying {} binary...", provider.name()));
+    match get_binary_version_at(provider, binary_path).await {
+        Ok(version) => options.status(format_args!("{} version: {}", provider.name(), version)),
         Err(e) => {
-            eprintln!(
+            options.warn(format_args!(
                 "Warning: Could not verify {} version: {}",
                 provider.name(),
                 e
-            );
+            ));
         }
     }
 
-    Ok(binary_path)
-}
+    if provider.smoke_test_args().is_some() {
+        match smoke_test_binary_at(provider, binary_path).await {
+            Ok(()) => options.status(format_args!("{} smoke test passed", provider.name())),
+            Err(e) => options.warn(format_args!(
+                "Warning: {} smoke test failed: {}",
+                provider.name(),
+                e
+            )),
+        }
+    }
 
-/// Run a binary with given arguments and return the output
-pub async fn run_binary<P: AsRef<Path>>(
-    binary_path: P,
-    args: &[&str],
-    binary_name: &str,
-) -> BinResult<std::process::Output> {
-    let binary_path = binary_path.as_ref();
+    Ok(())
+}
 
-    if !binary_path.exists() {
-        return Err(format!(
-            "{} binary does not exist at: {}",
-            binary_name,
-            binary_path.display()
-        )
-        .into());
+/// Formats an install duration, with tenths of a second under ten seconds
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs_f64();
+    if secs < 10.0 {
+        format!("{:.1}s", secs)
+    } else {
+        format!("{:.0}s", secs)
     }
+}
 
-    if !is_executable(binary_path)? {
-        return Err(format!(
-            "{} binary is not executable: {}",
-            binary_name,
-            binary_path.display()
-        )
-        .into());
-    }
+/// Summary of per-binary install times, e.g. `ClickHouse: 42s, agt: 3.1s`
+fn format_timings(timings: &[(&str, Duration)]) -> String {
+    timings
+        .iter()
+        .map(|(name, elapsed)| format!("{}: {}", name, format_elapsed(*elapsed)))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
 
-    let output = std::process::Command::new(binary_path)
-        .args(args)
-        .output()?;
+/// Records an install in the manifest of `manifest_dir`, warning when it cannot be updated
+fn record_in_manifest(
+    options: &InstallOptions,
+    manifest_dir: &Path,
+    provider: &dyn BinaryInfoProvider,
+    url: &str,
+    target: &SystemTarget,
+    validators: CacheValidators,
+) {
+    let version = options.version_for(provider);
+    if let Err(e) = BinManifest::record_install(
+        manifest_dir,
+        provider.name(),
+        url,
+        target,
+        version,
+        validators,
+    ) {
+        options.warn(format_args!(
+            "Warning: Could not update the binary manifest: {}",
+            e
+        ));
+    }
+}
 
-    Ok(output)
+/// Appends `record` to the install log, if any, warning when it cannot be written
+fn log_install(options: &InstallOptions, record: InstallRecord) {
+    if let Some(log_path) = &options.install_log
+        && let Err(e) = record.append(log_path)
+    {
+        eprintln!(
+            "Warning: Could not write the install log {}: {}",
+            log_path.display(),
+            e
+        );
+    }
 }
 
-/// Run a binary using provider information
-pub async fn run_binary_with_provider<P: AsRef<Path>>(
+/// Runs the provider's smoke test against an installed binary
+///
+/// Succeeds when the provider defines no smoke test. Fails when the binary
+/// exits unsuccessfully or its output is rejected by
+/// [`BinaryInfoProvider::validate_smoke_output`].
+#[allow(dead_code)]
+pub async fn smoke_test_binary<P: AsRef<Path>>(
+    provider: &dyn BinaryInfoProvider,
+    bin_dir: P,
+) -> BinResult<()> {
+    smoke_test_binary_at(provider, &get_provider_binary_path(provider, bin_dir)).await
+}
+
+/// Runs the provider's smoke test against the binary at `binary_path`
+async fn smoke_test_binary_at(
+    provider: &dyn BinaryInfoProvider,
+    binary_path: &Path,
+) -> BinResult<()> {
+    let Some(args) = provider
+        .smoke_test_args()
+        .filter(|_| !provider.is_long_running())
+    else {
+        return Ok(());
+    };
+
+    let output = run_binary(binary_path, args, provider.name()).await?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        return Err(format!(
+            "`{} {}` exited with {}",
+            provider.local_name(),
+            args.join(" "),
+            output.status
+        )
+        .into());
+    }
+    if !provider.validate_smoke_output(&stdout) {
+        return Err(format!(
+            "unexpected output from `{} {}`: {}",
+            provider.local_name(),
+            args.join(" "),
+            stdout.trim()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Check that a binary exists and is executable before running it
+fn ensure_runnable(binary_path: &Path, binary_name: &str) -> BinResult<()> {
+    if !binary_path.exists() {
+        return Err(format!(
+            "{} binary does not exist at: {}",
+            binary_name,
+            binary_path.display()
+        )
+        .into());
+    }
+
+    if !is_executable(binary_path)? {
+        return Err(format!(
+            "{} binary is not executable: {}",
+            binary_name,
+            binary_path.display()
+        )
+        .into());
+    }
+
+    Ok(())
+}
+
+/// Run a binary with given arguments and return the output
+pub async fn run_binary<P: AsRef<Path>>(
+    binary_path: P,
+    args: &[&str],
+    binary_name: &str,
+) -> BinResult<std::process::Output> {
+    let binary_path = binary_path.as_ref();
+    ensure_runnable(binary_path, binary_name)?;
+
+    let output = tokio::process::Command::new(binary_path)
+        .args(args)
+        .output()
+        .await?;
+
+    Ok(output)
+}
+
+/// Run a binary with given arguments, streaming its output as it is produced
+///
+/// Unlike [`run_binary`], stdout and stderr are inherited from the CLI so
+/// long-running binaries (e.g. an s3fs or ClickHouse server) show their logs
+/// live. Returns the exit status once the process terminates.
+#[allow(dead_code)]
+pub async fn run_binary_streaming<P: AsRef<Path>>(
+    binary_path: P,
+    args: &[&str],
+    binary_name: &str,
+) -> BinResult<ExitStatus> {
+    let mut child = spawn_binary_streaming(binary_path, args, binary_name, |_| {})?;
+    Ok(child.wait().await?)
+}
+
+/// Start a binary like [`run_binary_streaming`] without waiting for it to exit
+///
+/// `configure` runs just before the process is spawned, so callers can add
+/// environment variables or redirect a stream (e.g. for server-style runs such
+/// as `pipeline spawn`).
+pub fn spawn_binary_streaming<P: AsRef<Path>>(
+    binary_path: P,
+    args: &[&str],
+    binary_name: &str,
+    configure: impl FnOnce(&mut tokio::process::Command),
+) -> BinResult<tokio::process::Child> {
+    let binary_path = binary_path.as_ref();
+    ensure_runnable(binary_path, binary_name)?;
+
+    let mut command = tokio::process::Command::new(binary_path);
+    command
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit());
+    configure(&mut command);
+
+    Ok(command.spawn()?)
+}
+
+/// Run a binary using provider information
+pub async fn run_binary_with_provider<P: AsRef<Path>>(
     provider: &dyn BinaryInfoProvider,
     bin_dir: P,
     args: &[&str],
@@ -407,14 +1481,60 @@ pub async fn run_binary_with_provider<P: AsRef<Path>>(
     run_binary(&binary_path, args, provider.name()).await
 }
 
+/// Run a binary using provider information, streaming its output
+pub async fn run_binary_streaming_with_provider<P: AsRef<Path>>(
+    provider: &dyn BinaryInfoProvider,
+    bin_dir: P,
+    args: &[&str],
+) -> BinResult<ExitStatus> {
+    let mut child = spawn_binary_streaming_with_provider(provider, bin_dir, args, |_| {})?;
+    Ok(child.wait().await?)
+}
+
+/// Start a binary using provider information, see [`spawn_binary_streaming`]
+pub fn spawn_binary_streaming_with_provider<P: AsRef<Path>>(
+    provider: &dyn BinaryInfoProvider,
+    bin_dir: P,
+    args: &[&str],
+    configure: impl FnOnce(&mut tokio::process::Command),
+) -> BinResult<tokio::process::Child> {
+    let binary_path = get_provider_binary_path(provider, &bin_dir);
+
+    if !is_binary_ready(provider, &bin_dir) {
+        return Err(format!(
+            "{} binary is not installed or not executable, run `system install` first",
+            provider.name()
+        )
+        .into());
+    }
+
+    spawn_binary_streaming(&binary_path, args, provider.name(), configure)
+}
+
 /// Get the version of a binary using provider information
 pub async fn get_binary_version<P: AsRef<Path>>(
     provider: &dyn BinaryInfoProvider,
     bin_dir: P,
 ) -> BinResult<String> {
     let output = run_binary_with_provider(provider, &bin_dir, provider.version_args()).await?;
+    parse_version_output(provider, &output)
+}
+
+/// Get the version of the provider's binary installed at `binary_path`
+pub async fn get_binary_version_at(
+    provider: &dyn BinaryInfoProvider,
+    binary_path: &Path,
+) -> BinResult<String> {
+    let output = run_binary(binary_path, provider.version_args(), provider.name()).await?;
+    parse_version_output(provider, &output)
+}
 
-    if output.status.success() {
+/// Extracts the version from the output of the provider's version command
+fn parse_version_output(
+    provider: &dyn BinaryInfoProvider,
+    output: &std::process::Output,
+) -> BinResult<String> {
+    if output.status.success() || !provider.has_version_flag() {
         let output_text = String::from_utf8_lossy(&output.stdout);
 
         if let Some(version) = provider.parse_version_output(&output_text) {
@@ -427,16 +1547,218 @@ pub async fn get_binary_version<P: AsRef<Path>>(
     }
 }
 
+/// Outcome of an integrity check on an installed binary
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    /// The binary runs, and matches its expected digest when `digest_checked`
+    ///
+    /// `digest_checked` is false when no digest is published for the installed
+    /// build, so only the version command vouches for it.
+    Ok {
+        version: String,
+        digest_checked: bool,
+    },
+    /// The binary is not installed or not executable
+    Missing,
+    /// The binary's version command failed
+    VersionCommandFailed { error: String },
+    /// The binary content differs from the published digest
+    HashMismatch { expected: String, actual: String },
+}
+
+impl VerifyOutcome {
+    /// Whether the binary passed verification
+    pub fn is_ok(&self) -> bool {
+        matches!(self, VerifyOutcome::Ok { .. })
+    }
+}
+
+/// Digest published for the build installed in `bin_dir`, if any
+///
+/// The target and pinned version come from the bin manifest, so a binary
+/// installed with `--target` or `--bin-version` is not checked against the
+/// digest of the host's default build. Binaries without a recorded target
+/// (installed before it was tracked) are assumed to be built for the host.
+fn installed_digest(provider: &dyn BinaryInfoProvider, bin_dir: &Path) -> Option<&'static str> {
+    let entry = BinManifest::load(bin_dir)
+        .ok()
+        .and_then(|manifest| manifest.binaries.get(provider.name()).cloned());
+    let target = match entry {
+        // Digests are only published for the default version
+        Some(entry) if entry.version.is_some() => return None,
+        Some(ManifestEntry {
+            target: Some(target),
+            ..
+        }) => target.parse().ok()?,
+        _ => SystemTarget::detect().ok()?,
+    };
+    provider.expected_sha256(&target)
+}
+
+/// Re-checks an installed binary: runs its version command and compares its
+/// SHA-256 digest against the one published for the installed build, if any
+pub async fn verify_binary<P: AsRef<Path>>(
+    provider: &dyn BinaryInfoProvider,
+    bin_dir: P,
+) -> VerifyOutcome {
+    let bin_dir = bin_dir.as_ref();
+
+    if !is_binary_ready(provider, bin_dir) {
+        return VerifyOutcome::Missing;
+    }
+
+    let expected = installed_digest(provider, bin_dir);
+    if let Some(expected) = expected {
+        let path = get_provider_binary_path(provider, bin_dir);
+        match sha256_file(&path) {
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => {}
+            Ok(actual) => {
+                return VerifyOutcome::HashMismatch {
+                    expected: expected.to_string(),
+                    actual,
+                };
+            }
+            Err(e) => {
+                return VerifyOutcome::HashMismatch {
+                    expected: expected.to_string(),
+                    actual: format!("unreadable ({})", e),
+                };
+            }
+        }
+    }
+
+    match get_binary_version(provider, bin_dir).await {
+        Ok(version) => VerifyOutcome::Ok {
+            version,
+            digest_checked: expected.is_some(),
+        },
+        Err(e) => VerifyOutcome::VersionCommandFailed {
+            error: e.to_string(),
+        },
+    }
+}
+
+/// High-level handle on a directory of managed binaries
+///
+/// Wraps the global [`registry`] and a bin directory so that programs embedding
+/// this crate can install, run and inspect binaries by name. The free functions
+/// below delegate to it.
+///
+/// # Examples
+///
+/// ```no_run
+/// use cli::utils::bin::BinaryManager;
+///
+/// # async fn demo() -> cli::utils::bin::BinResult<()> {
+/// let manager = BinaryManager::new("/home/me/.agnostic/bin");
+/// manager.ensure("agt").await?;
+/// println!("agt {}", manager.version("agt").await?);
+/// let output = manager.run("agt", &["--help"]).await?;
+/// assert!(output.status.success());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct BinaryManager {
+    bin_dir: PathBuf,
+    options: InstallOptions,
+}
+
+impl BinaryManager {
+    /// Creates a manager for binaries kept in `bin_dir`
+    pub fn new(bin_dir: impl Into<PathBuf>) -> Self {
+        Self {
+            bin_dir: bin_dir.into(),
+            options: InstallOptions::default(),
+        }
+    }
+
+    /// Sets the options used when [`ensure`](Self::ensure) has to download
+    pub fn with_options(mut self, options: InstallOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Directory holding the managed binaries
+    #[allow(dead_code)]
+    pub fn bin_dir(&self) -> &Path {
+        &self.bin_dir
+    }
+
+    /// Looks up a provider by display name or local filename
+    pub fn provider(&self, name: &str) -> BinResult<&'static dyn BinaryInfoProvider> {
+        registry()
+            .get_provider(name)
+            .ok_or_else(|| format!("Unknown binary provider: {}", name).into())
+    }
+
+    /// Installs a binary unless it is already present, returning its path
+    pub async fn ensure(&self, name: &str) -> BinResult<PathBuf> {
+        install_binary(self.provider(name)?, &self.bin_dir, &self.options).await
+    }
+
+    /// Downloads a binary to `path` instead of the bin directory, returning where it landed
+    pub async fn install_to(&self, name: &str, path: &Path) -> BinResult<PathBuf> {
+        install_binary_to(self.provider(name)?, path, &self.options).await
+    }
+
+    /// Installs every managed binary that is missing
+    pub async fn ensure_all(&self) -> BinResult<Vec<PathBuf>> {
+        registry()
+            .ensure_all_binaries(&self.bin_dir, &self.options)
+            .await
+    }
+
+    /// Runs an installed binary with the given arguments and captures its output
+    ///
+    /// Long-running binaries are refused since capturing would hold their
+    /// output until they stop; use [`run_streaming`](Self::run_streaming).
+    #[allow(dead_code)]
+    pub async fn run(&self, name: &str, args: &[&str]) -> BinResult<std::process::Output> {
+        let provider = self.provider(name)?;
+        if provider.is_long_running() {
+            return Err(format!(
+                "{} is a long-running server, its output cannot be captured",
+                provider.name()
+            )
+            .into());
+        }
+        run_binary_with_provider(provider, &self.bin_dir, args).await
+    }
+
+    /// Runs an installed binary with the given arguments, streaming its output
+    #[allow(dead_code)]
+    pub async fn run_streaming(&self, name: &str, args: &[&str]) -> BinResult<ExitStatus> {
+        run_binary_streaming_with_provider(self.provider(name)?, &self.bin_dir, args).await
+    }
+
+    /// Reports the version of an installed binary
+    pub async fn version(&self, name: &str) -> BinResult<String> {
+        get_binary_version(self.provider(name)?, &self.bin_dir).await
+    }
+
+    /// Status of every managed binary
+    pub fn status(&self) -> Vec<BinaryInfo> {
+        registry().get_all_status(&self.bin_dir)
+    }
+}
+
 // Public API functions
 
 /// Get status of all binary providers
 pub fn get_all_status<P: AsRef<Path>>(bin_dir: P) -> Vec<BinaryInfo> {
-    registry().get_all_status(bin_dir)
+    BinaryManager::new(bin_dir.as_ref()).status()
 }
 
 /// Ensures all required binaries are installed
-pub async fn ensure_required_binaries<P: AsRef<Path>>(bin_dir: P) -> BinResult<Vec<PathBuf>> {
-    registry().ensure_all_binaries(bin_dir).await
+pub async fn ensure_required_binaries<P: AsRef<Path>>(
+    bin_dir: P,
+    options: &InstallOptions,
+) -> BinResult<Vec<PathBuf>> {
+    BinaryManager::new(bin_dir.as_ref())
+        .with_options(options.clone())
+        .ensure_all()
+        .await
 }
 
 /// Returns status information for all managed binaries
@@ -449,16 +1771,15 @@ pub async fn get_binary_version_by_name<P: AsRef<Path>>(
     name: &str,
     bin_dir: P,
 ) -> BinResult<String> {
-    if let Some(provider) = registry().get_provider(name) {
-        get_binary_version(provider, bin_dir).await
-    } else {
-        Err(format!("Unknown binary provider: {}", name).into())
-    }
+    BinaryManager::new(bin_dir.as_ref()).version(name).await
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::fs::sha256_hex;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use tempfile::TempDir;
 
     struct TestProvider;
@@ -472,8 +1793,8 @@ mod tests {
             "testbin"
         }
 
-        fn get_download_url(&self, _target: &SystemTarget) -> String {
-            "https://example.com/testbin".to_string()
+        fn get_download_url(&self, _target: &SystemTarget) -> BinResult<String> {
+            Ok("https://example.com/testbin".to_string())
         }
 
         fn version_args(&self) -> &[&str] {
@@ -489,12 +1810,164 @@ mod tests {
         }
     }
 
+    /// Provider reusing TestProvider's local filename under a different name
+    struct ClashingProvider;
+
+    impl BinaryInfoProvider for ClashingProvider {
+        fn name(&self) -> &'static str {
+            "other-binary"
+        }
+
+        fn local_name(&self) -> &'static str {
+            "testbin"
+        }
+
+        fn get_download_url(&self, _target: &SystemTarget) -> BinResult<String> {
+            Ok("https://example.com/other".to_string())
+        }
+
+        fn version_args(&self) -> &[&str] {
+            &["--version"]
+        }
+
+        fn parse_version_output(&self, _output: &str) -> Option<String> {
+            None
+        }
+    }
+
     #[test]
     fn test_system_target_detection() {
         let target = SystemTarget::detect();
         assert!(target.is_ok());
     }
 
+    #[test]
+    fn test_system_target_under_rosetta() {
+        let resolve = SystemTarget::resolve;
+        assert_eq!(
+            resolve("macos", "x86_64", true, true).unwrap(),
+            SystemTarget::MacOsAarch64
+        );
+        assert_eq!(
+            resolve("macos", "x86_64", true, false).unwrap(),
+            SystemTarget::MacOsX86_64
+        );
+        assert_eq!(
+            resolve("macos", "x86_64", false, true).unwrap(),
+            SystemTarget::MacOsX86_64
+        );
+        assert_eq!(
+            resolve("linux", "x86_64", true, true).unwrap(),
+            SystemTarget::LinuxX86_64
+        );
+        assert!(resolve("windows", "x86_64", false, true).is_err());
+    }
+
+    #[test]
+    fn test_registry_rejects_duplicate_local_names() {
+        let result = registry_try_new(vec![Box::new(TestProvider), Box::new(ClashingProvider)]);
+        let err = result.err().unwrap();
+        assert!(err.to_string().contains("testbin"));
+
+        let result = registry_try_new(vec![Box::new(TestProvider), Box::new(TestProvider)]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_registry_accepts_unique_providers() {
+        let registry = registry_try_new(vec![Box::new(TestProvider), Box::new(agt())]).unwrap();
+        assert!(registry.get_provider("testbin").is_some());
+        assert!(registry.get_provider("agt").is_some());
+    }
+
+    #[test]
+    fn test_system_target_parsing() {
+        for target in SystemTarget::all() {
+            let parsed: SystemTarget = target.to_string().parse().unwrap();
+            assert_eq!(&parsed, target);
+        }
+
+        assert_eq!(
+            "darwin-arm64".parse::<SystemTarget>().unwrap(),
+            SystemTarget::MacOsAarch64
+        );
+        assert!("windows-x86_64".parse::<SystemTarget>().is_err());
+    }
+
+    #[test]
+    fn test_install_options_target_override() {
+        let options = InstallOptions {
+            target: Some(SystemTarget::LinuxX86_64),
+            ..Default::default()
+        };
+        let target = options.resolve_target().unwrap();
+        assert_eq!(target, SystemTarget::LinuxX86_64);
+
+        let url = agt().get_download_url(&target).unwrap();
+        assert!(url.contains("agt_0.0.23_linux_amd64_v1"));
+
+        let detected = InstallOptions::default().resolve_target().unwrap();
+        assert_eq!(detected, SystemTarget::detect().unwrap());
+        assert!(!InstallOptions::default().is_cross_install());
+    }
+
+    #[test]
+    fn test_parse_version_override() {
+        let pin: VersionOverride = "AGT=0.0.24".parse().unwrap();
+        assert_eq!(pin.name, "agt");
+        assert_eq!(pin.version, "0.0.24");
+
+        let pin: VersionOverride = "clickhouse=24.1.0.0".parse().unwrap();
+        assert_eq!(pin.name, "ClickHouse");
+
+        assert!(
+            "agt"
+                .parse::<VersionOverride>()
+                .unwrap_err()
+                .contains("NAME=VERSION")
+        );
+        assert!("agt=".parse::<VersionOverride>().is_err());
+        assert!("=1.0".parse::<VersionOverride>().is_err());
+        assert!("agt=../1.0".parse::<VersionOverride>().is_err());
+
+        let err = "duckdb=1.0".parse::<VersionOverride>().unwrap_err();
+        assert!(err.contains("Unknown binary 'duckdb'"));
+        assert!(err.contains("clickhouse"));
+    }
+
+    #[test]
+    fn test_version_override_download_url() {
+        let options = InstallOptions {
+            versions: vec![
+                "agt=0.0.20".parse().unwrap(),
+                "agt=v0.0.24".parse().unwrap(),
+            ],
+            ..Default::default()
+        };
+        let target = SystemTarget::LinuxX86_64;
+
+        // The last pin wins and flows into the generated URL
+        assert_eq!(options.version_for(&agt()), Some("v0.0.24"));
+        let urls = options.download_urls(&agt(), &target).unwrap();
+        assert!(urls[0].contains("releases/download/v0.0.24/agt_0.0.24_linux_amd64_v1"));
+        assert!(options.forces_download(&agt()));
+
+        // Providers without a pin keep their default URL
+        assert_eq!(
+            options.download_urls(&s3fs(), &target).unwrap(),
+            s3fs().get_download_urls(&target).unwrap()
+        );
+        assert!(!options.forces_download(&s3fs()));
+
+        // Nightly-only providers reject pins
+        let options = InstallOptions {
+            versions: vec!["clickhouse=24.1.0.0".parse().unwrap()],
+            ..Default::default()
+        };
+        let err = options.download_urls(&clickhouse(), &target).unwrap_err();
+        assert!(err.to_string().contains("does not support pinning"));
+    }
+
     #[test]
     fn test_binary_info_creation() {
         let temp_dir = TempDir::new().unwrap();
@@ -580,27 +2053,1181 @@ mod tests {
         assert!(!info.exists);
         assert!(!info.executable);
 
+        let clickhouse_provider = registry().get_provider("clickhouse").unwrap();
+        assert_eq!(clickhouse_provider.name(), "ClickHouse");
+
         let unknown_provider = registry().get_provider("unknown");
         assert!(unknown_provider.is_none());
     }
 
-    #[tokio::test]
-    async fn test_binary_version_by_name() {
-        let temp_dir = TempDir::new().unwrap();
-        let bin_dir = temp_dir.path();
+    const TEST_SCRIPT: &[u8] = b"#!/bin/sh\necho test-binary 1.0\n";
 
-        // Should fail for non-existent binary
-        let result = get_binary_version_by_name("s3fs", bin_dir).await;
-        assert!(result.is_err());
+    /// TestProvider variant publishing the digest of TEST_SCRIPT
+    struct HashedProvider;
 
-        // Should fail for unknown binary
-        let result = get_binary_version_by_name("unknown", bin_dir).await;
-        assert!(result.is_err());
-        assert!(
-            result
-                .unwrap_err()
-                .to_string()
-                .contains("Unknown binary provider")
-        );
+    impl BinaryInfoProvider for HashedProvider {
+        fn name(&self) -> &'static str {
+            TestProvider.name()
+        }
+
+        fn local_name(&self) -> &'static str {
+            TestProvider.local_name()
+        }
+
+        fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+            TestProvider.get_download_url(target)
+        }
+
+        fn version_args(&self) -> &[&str] {
+            &["--version"]
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            TestProvider.parse_version_output(output)
+        }
+
+        fn expected_sha256(&self, _target: &SystemTarget) -> Option<&'static str> {
+            Some("c082598f177e480431110e5e0c9cb3c9bce853dbcfe55b6f11b316863bedc44a")
+        }
+    }
+
+    /// TestProvider variant whose smoke test expects `expected` on stdout
+    struct SmokeProvider {
+        expected: &'static str,
+    }
+
+    impl BinaryInfoProvider for SmokeProvider {
+        fn name(&self) -> &'static str {
+            TestProvider.name()
+        }
+
+        fn local_name(&self) -> &'static str {
+            TestProvider.local_name()
+        }
+
+        fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+            TestProvider.get_download_url(target)
+        }
+
+        fn version_args(&self) -> &[&str] {
+            &["--version"]
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            TestProvider.parse_version_output(output)
+        }
+
+        fn smoke_test_args(&self) -> Option<&[&str]> {
+            Some(&["--smoke"])
+        }
+
+        fn validate_smoke_output(&self, output: &str) -> bool {
+            output.trim() == self.expected
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_smoke_test_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        write_and_make_executable(temp_dir.path().join("testbin"), TEST_SCRIPT).unwrap();
+
+        let passing = SmokeProvider {
+            expected: "test-binary 1.0",
+        };
+        smoke_test_binary(&passing, temp_dir.path()).await.unwrap();
+
+        let rejecting = SmokeProvider { expected: "1" };
+        let err = smoke_test_binary(&rejecting, temp_dir.path())
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unexpected output from `testbin --smoke`: test-binary 1.0"));
+
+        // Providers without a smoke test always pass
+        smoke_test_binary(&TestProvider, temp_dir.path())
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_verify_missing_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let outcome = verify_binary(&TestProvider, temp_dir.path()).await;
+        assert_eq!(outcome, VerifyOutcome::Missing);
+        assert!(!outcome.is_ok());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_binary_with_correct_content() {
+        let temp_dir = TempDir::new().unwrap();
+        write_and_make_executable(temp_dir.path().join("testbin"), TEST_SCRIPT).unwrap();
+
+        let outcome = verify_binary(&HashedProvider, temp_dir.path()).await;
+        assert_eq!(
+            outcome,
+            VerifyOutcome::Ok {
+                version: "test-binary 1.0".to_string(),
+                digest_checked: true,
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_with_mode_sets_executable_bit() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("nested").join("testbin");
+        write_with_mode(&path, TEST_SCRIPT, 0o750).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o750);
+        assert!(is_executable(&path).unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_with_mode_replaces_existing_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("testbin");
+        fs::write(&path, b"old contents").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        write_with_mode(&path, TEST_SCRIPT, DEFAULT_BINARY_MODE).unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+        assert!(is_executable(&path).unwrap());
+        // No temporary file is left behind
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_write_with_mode_keeps_binary_on_failure() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("testbin");
+        write_with_mode(&path, TEST_SCRIPT, DEFAULT_BINARY_MODE).unwrap();
+
+        // A mode without the owner execute bit fails before the rename
+        assert!(write_with_mode(&path, b"new", 0o644).is_err());
+
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+        assert_eq!(fs::read_dir(temp_dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_check_executable_mode() {
+        let path = Path::new("/mnt/noexec/bin/clickhouse");
+        assert!(check_executable_mode(path, 0o100755).is_ok());
+        assert!(check_executable_mode(path, 0o700).is_ok());
+
+        let err = check_executable_mode(path, 0o100644)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("/mnt/noexec/bin/clickhouse"));
+        assert!(err.contains("mode 644"));
+        assert!(err.contains("noexec"));
+        assert!(err.contains("--bin-dir"));
+    }
+
+    #[test]
+    fn test_parse_binary_mode() {
+        assert_eq!(parse_binary_mode("755").unwrap(), 0o755);
+        assert_eq!(parse_binary_mode("0o700").unwrap(), 0o700);
+        assert_eq!(parse_binary_mode("0750").unwrap(), 0o750);
+        assert!(parse_binary_mode("644").unwrap_err().contains("executable"));
+        assert!(parse_binary_mode("1755").is_err());
+        assert!(parse_binary_mode("rwx").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_binary_with_tampered_content() {
+        let temp_dir = TempDir::new().unwrap();
+        write_and_make_executable(
+            temp_dir.path().join("testbin"),
+            b"#!/bin/sh\necho test-binary 6.6.6\n",
+        )
+        .unwrap();
+
+        let outcome = verify_binary(&HashedProvider, temp_dir.path()).await;
+        assert!(matches!(outcome, VerifyOutcome::HashMismatch { .. }));
+    }
+
+    /// HashedProvider variant publishing its digest for macOS on Apple silicon only
+    struct MacOnlyHashedProvider;
+
+    impl BinaryInfoProvider for MacOnlyHashedProvider {
+        fn name(&self) -> &'static str {
+            HashedProvider.name()
+        }
+
+        fn local_name(&self) -> &'static str {
+            HashedProvider.local_name()
+        }
+
+        fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+            HashedProvider.get_download_url(target)
+        }
+
+        fn version_args(&self) -> &[&str] {
+            HashedProvider.version_args()
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            HashedProvider.parse_version_output(output)
+        }
+
+        fn expected_sha256(&self, target: &SystemTarget) -> Option<&'static str> {
+            (target == &SystemTarget::MacOsAarch64)
+                .then(|| HashedProvider.expected_sha256(target))
+                .flatten()
+        }
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_binary_uses_installed_target_and_version() {
+        let temp_dir = TempDir::new().unwrap();
+        write_and_make_executable(temp_dir.path().join("testbin"), TEST_SCRIPT).unwrap();
+        let record = |target: &SystemTarget, version: Option<&str>| {
+            BinManifest::record_install(
+                temp_dir.path(),
+                TestProvider.name(),
+                "https://example.com/testbin",
+                target,
+                version,
+                CacheValidators::default(),
+            )
+            .unwrap()
+        };
+        let digest_checked = |outcome: VerifyOutcome| match outcome {
+            VerifyOutcome::Ok { digest_checked, .. } => digest_checked,
+            other => panic!("unexpected outcome {:?}", other),
+        };
+
+        // The digest of the recorded target is used, whatever the host
+        record(&SystemTarget::MacOsAarch64, None);
+        let outcome = verify_binary(&MacOnlyHashedProvider, temp_dir.path()).await;
+        assert!(digest_checked(outcome));
+
+        record(&SystemTarget::LinuxX86_64, None);
+        let outcome = verify_binary(&MacOnlyHashedProvider, temp_dir.path()).await;
+        assert!(!digest_checked(outcome));
+
+        // A pinned version has no published digest, even for a tampered binary
+        write_and_make_executable(
+            temp_dir.path().join("testbin"),
+            b"#!/bin/sh\necho test-binary 6.6.6\n",
+        )
+        .unwrap();
+        record(&SystemTarget::MacOsAarch64, Some("6.6.6"));
+        let outcome = verify_binary(&HashedProvider, temp_dir.path()).await;
+        assert!(!digest_checked(outcome));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_verify_binary_failing_version() {
+        let temp_dir = TempDir::new().unwrap();
+        write_and_make_executable(temp_dir.path().join("testbin"), b"#!/bin/sh\nexit 1\n").unwrap();
+
+        let outcome = verify_binary(&TestProvider, temp_dir.path()).await;
+        assert!(matches!(
+            outcome,
+            VerifyOutcome::VersionCommandFailed { .. }
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_binary_streaming_exit_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = temp_dir.path().join("counter");
+        write_and_make_executable(
+            &script_path,
+            b"#!/bin/sh\nfor i in 1 2 3; do echo \"line $i\"; sleep 0.05; done\nexit 3\n",
+        )
+        .unwrap();
+
+        let status = run_binary_streaming(&script_path, &[], "counter")
+            .await
+            .unwrap();
+        assert!(!status.success());
+        assert_eq!(status.code(), Some(3));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_spawn_binary_streaming_configures_command() {
+        let temp_dir = TempDir::new().unwrap();
+        let script_path = temp_dir.path().join("env-check");
+        write_and_make_executable(
+            &script_path,
+            b"#!/bin/sh\n[ \"$PIPELINE_NAME\" = \"$1\" ]\n",
+        )
+        .unwrap();
+
+        let mut child = spawn_binary_streaming(&script_path, &["demo"], "env-check", |command| {
+            command.env("PIPELINE_NAME", "demo");
+        })
+        .unwrap();
+        assert!(child.id().is_some());
+        assert!(child.wait().await.unwrap().success());
+    }
+
+    #[tokio::test]
+    async fn test_run_binary_streaming_missing_binary() {
+        let temp_dir = TempDir::new().unwrap();
+        let result =
+            run_binary_streaming(temp_dir.path().join("missing"), &["--version"], "missing").await;
+        assert!(result.unwrap_err().to_string().contains("does not exist"));
+    }
+
+    /// Serves the given chunks as a streamed body without a Content-Length header
+    async fn serve_chunked(chunks: Vec<&'static [u8]>) -> String {
+        use axum::{Router, body::Body, routing::get};
+
+        let app = Router::new().route(
+            "/binary",
+            get(move || async move {
+                let stream =
+                    futures_util::stream::iter(chunks.into_iter().map(Ok::<_, std::io::Error>));
+                Body::from_stream(stream)
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}/binary", addr)
+    }
+
+    #[tokio::test]
+    async fn test_download_without_content_length() {
+        let url = serve_chunked(vec![b"first-", b"second-", b"third"]).await;
+
+        let response = Client::new().get(&url).send().await.unwrap();
+        assert!(response.content_length().is_none());
+
+        let download = download_binary_with_progress(&url, "chunked")
+            .await
+            .unwrap();
+        assert_eq!(download.bytes, b"first-second-third");
+        assert_eq!(download.len, 18);
+        // Hashed chunk by chunk, the digest must match one over the whole content
+        assert_eq!(download.sha256, sha256_hex(b"first-second-third"));
+    }
+
+    /// Provider downloading [`TEST_SCRIPT`] from a local mock server
+    struct ServedProvider {
+        name: &'static str,
+        urls: Vec<String>,
+    }
+
+    impl BinaryInfoProvider for ServedProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        fn local_name(&self) -> &'static str {
+            self.name
+        }
+
+        fn get_download_url(&self, _target: &SystemTarget) -> BinResult<String> {
+            Ok(self.urls[0].clone())
+        }
+
+        fn get_download_urls(&self, _target: &SystemTarget) -> BinResult<Vec<String>> {
+            Ok(self.urls.clone())
+        }
+
+        fn version_args(&self) -> &[&str] {
+            TestProvider.version_args()
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            TestProvider.parse_version_output(output)
+        }
+    }
+
+    /// ServedProvider publishing the digest of [`TEST_SCRIPT`]
+    struct HashedServedProvider(ServedProvider);
+
+    impl BinaryInfoProvider for HashedServedProvider {
+        fn name(&self) -> &'static str {
+            self.0.name()
+        }
+
+        fn local_name(&self) -> &'static str {
+            self.0.local_name()
+        }
+
+        fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+            self.0.get_download_url(target)
+        }
+
+        fn get_download_urls(&self, target: &SystemTarget) -> BinResult<Vec<String>> {
+            self.0.get_download_urls(target)
+        }
+
+        fn version_args(&self) -> &[&str] {
+            self.0.version_args()
+        }
+
+        fn parse_version_output(&self, output: &str) -> Option<String> {
+            self.0.parse_version_output(output)
+        }
+
+        fn expected_sha256(&self, target: &SystemTarget) -> Option<&'static str> {
+            HashedProvider.expected_sha256(target)
+        }
+    }
+
+    /// Serves `body` at `/testbin` and returns a provider expecting [`TEST_SCRIPT`] there
+    async fn serve_hashed(body: &'static [u8]) -> (wiremock::MockServer, HashedServedProvider) {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(body))
+            .mount(&server)
+            .await;
+        let provider = HashedServedProvider(ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        });
+        (server, provider)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_checks_streamed_digest() {
+        let temp_dir = TempDir::new().unwrap();
+        let options = InstallOptions {
+            skip_verify: true,
+            ..Default::default()
+        };
+
+        let (_server, provider) = serve_hashed(TEST_SCRIPT).await;
+        let path = install_binary(&provider, temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+
+        let (_server, provider) = serve_hashed(b"#!/bin/sh\necho tampered\n").await;
+        let options = InstallOptions {
+            force_download: true,
+            ..options
+        };
+        let error = install_binary(&provider, temp_dir.path(), &options)
+            .await
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Checksum mismatch"), "{}", error);
+        // The previous, valid binary is kept
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_emits_events() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(TEST_SCRIPT))
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let provider = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        let options = InstallOptions {
+            events: EventEmitter::recording(),
+            ..Default::default()
+        };
+
+        let path = install_binary(&provider, temp_dir.path(), &options)
+            .await
+            .unwrap();
+
+        let total = TEST_SCRIPT.len() as u64;
+        let recorded = options.events.recorded();
+        let Some(Event::InstallComplete { elapsed_ms, .. }) = recorded.last() else {
+            panic!("install must end with install_complete");
+        };
+        assert_eq!(
+            recorded,
+            vec![
+                Event::DownloadStarted {
+                    name: "test-binary".to_string(),
+                    url: provider.urls[0].clone(),
+                    total_bytes: Some(total),
+                },
+                Event::DownloadProgress {
+                    name: "test-binary".to_string(),
+                    downloaded_bytes: total,
+                    total_bytes: Some(total),
+                },
+                Event::DownloadComplete {
+                    name: "test-binary".to_string(),
+                    bytes: total,
+                },
+                Event::InstallComplete {
+                    name: "test-binary".to_string(),
+                    path,
+                    url: provider.urls[0].clone(),
+                    elapsed_ms: *elapsed_ms,
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_binary_version_by_name() {
+        let temp_dir = TempDir::new().unwrap();
+        let bin_dir = temp_dir.path();
+
+        // Should fail for non-existent binary
+        let result = get_binary_version_by_name("s3fs", bin_dir).await;
+        assert!(result.is_err());
+
+        // Should fail for unknown binary
+        let result = get_binary_version_by_name("unknown", bin_dir).await;
+        assert!(result.is_err());
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Unknown binary provider")
+        );
+    }
+
+    /// Writes an executable fake `agt` that prints its version and echoes its arguments
+    #[cfg(unix)]
+    fn write_fake_agt(bin_dir: &Path) -> PathBuf {
+        let path = bin_dir.join("agt");
+        write_with_mode(
+            &path,
+            b"#!/bin/sh\nif [ \"$1\" = --version ]; then echo agt v0.0.23; else echo \"args: $*\"; fi\n",
+            DEFAULT_BINARY_MODE,
+        )
+        .unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_binary_manager_facade() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = BinaryManager::new(temp_dir.path());
+        let agt_path = write_fake_agt(temp_dir.path());
+
+        // Already installed, so ensure returns without downloading
+        assert_eq!(manager.ensure("AGT").await.unwrap(), agt_path);
+        assert_eq!(manager.version("agt").await.unwrap(), "agt v0.0.23");
+
+        let output = manager.run("agt", &["run", "demo"]).await.unwrap();
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "args: run demo\n");
+
+        let status = manager.status();
+        assert_eq!(status.len(), 3);
+        let agt = status.iter().find(|b| b.name == "agt").unwrap();
+        assert!(agt.exists && agt.executable);
+        assert!(status.iter().filter(|b| b.name != "agt").all(|b| !b.exists));
+    }
+
+    #[tokio::test]
+    async fn test_binary_manager_errors() {
+        let temp_dir = TempDir::new().unwrap();
+        let manager = BinaryManager::new(temp_dir.path());
+
+        let err = manager.version("nope").await.unwrap_err();
+        assert_eq!(err.to_string(), "Unknown binary provider: nope");
+
+        let err = manager.run("agt", &[]).await.unwrap_err();
+        assert!(err.to_string().contains("not installed"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_transient_download_errors() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/agt", listener.local_addr().unwrap());
+        drop(listener);
+
+        let error =
+            download_binary_with_events(&Client::new(), &url, "agt", &EventEmitter::default())
+                .await
+                .unwrap_err();
+        assert!(is_transient_network_error(error.as_ref()));
+
+        let error: Box<dyn std::error::Error> = "Failed to download agt binary: HTTP 404".into();
+        assert!(!is_transient_network_error(error.as_ref()));
+    }
+
+    #[tokio::test]
+    async fn test_install_falls_back_to_next_url() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/primary/testbin"))
+            .respond_with(ResponseTemplate::new(404))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/mirror/testbin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(TEST_SCRIPT))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let provider = ServedProvider {
+            name: "test-binary",
+            urls: vec![
+                format!("{}/primary/testbin", server.uri()),
+                format!("{}/mirror/testbin", server.uri()),
+            ],
+        };
+        let options = InstallOptions {
+            events: EventEmitter::recording(),
+            ..Default::default()
+        };
+
+        let path = install_binary(&provider, temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+        assert!(matches!(
+            options.events.recorded().last(),
+            Some(Event::InstallComplete { url, .. }) if url == &provider.urls[1]
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_appends_to_install_log() {
+        use crate::utils::bin::install_log::read_install_log;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(TEST_SCRIPT))
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let log_path = temp_dir.path().join("logs").join("install.log");
+        let options = InstallOptions {
+            install_log: Some(log_path.clone()),
+            ..Default::default()
+        };
+        let served = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        let missing = ServedProvider {
+            name: "missing-binary",
+            urls: vec![format!("{}/missing", server.uri())],
+        };
+
+        install_binary(&served, temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert!(
+            install_binary(&missing, temp_dir.path(), &options)
+                .await
+                .is_err()
+        );
+
+        let records = read_install_log(&log_path).unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].binary, "test-binary");
+        assert_eq!(records[0].url.as_ref(), Some(&served.urls[0]));
+        assert_eq!(records[0].size, Some(TEST_SCRIPT.len() as u64));
+        assert_eq!(records[0].sha256, Some(sha256_hex(TEST_SCRIPT)));
+        assert!(records[0].duration_ms.is_some());
+        assert_eq!(records[0].outcome, InstallOutcome::Success);
+        assert_eq!(records[1].binary, "missing-binary");
+        assert_eq!(records[1].url.as_ref(), Some(&missing.urls[0]));
+        assert!(matches!(records[1].outcome, InstallOutcome::Failed(_)));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_skip_verify_does_not_run_binary() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let temp_dir = TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let script = format!("#!/bin/sh\ntouch '{}'\n", marker.display());
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(script.as_bytes()))
+            .mount(&server)
+            .await;
+
+        let provider = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        let options = InstallOptions {
+            skip_verify: true,
+            ..Default::default()
+        };
+        let bin_dir = temp_dir.path().join("bin");
+        fs::create_dir(&bin_dir).unwrap();
+        let path = install_binary(&provider, &bin_dir, &options).await.unwrap();
+
+        assert!(is_executable(&path).unwrap());
+        assert!(!marker.exists(), "the binary must not be run");
+    }
+
+    #[test]
+    fn test_format_timings() {
+        let timings = [
+            ("ClickHouse", Duration::from_millis(42_300)),
+            ("agt", Duration::from_millis(3_140)),
+        ];
+        assert_eq!(format_timings(&timings), "ClickHouse: 42s, agt: 3.1s");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_forced_install_keeps_binary_when_not_modified() {
+        const LAST_MODIFIED: &str = "Wed, 14 Oct 2026 08:00:00 GMT";
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304).insert_header("last-modified", LAST_MODIFIED))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("etag", "\"v1\"")
+                    .set_body_bytes(TEST_SCRIPT),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let served = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        let binary_path = install_binary(&served, temp_dir.path(), &InstallOptions::default())
+            .await
+            .unwrap();
+        let manifest = BinManifest::load(temp_dir.path()).unwrap();
+        assert_eq!(
+            manifest.binaries["test-binary"].validators.etag.as_deref(),
+            Some("\"v1\"")
+        );
+
+        // Mark the installed copy so a re-download would be noticed
+        let kept = format!("{}# kept\n", std::str::from_utf8(TEST_SCRIPT).unwrap());
+        write_with_mode(&binary_path, kept.as_bytes(), DEFAULT_BINARY_MODE).unwrap();
+
+        let options = InstallOptions {
+            force_download: true,
+            ..Default::default()
+        };
+        install_binary(&served, temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert_eq!(fs::read_to_string(&binary_path).unwrap(), kept);
+
+        // The 304 refreshes the stored validators, keeping the ETag it didn't repeat
+        let validators =
+            &BinManifest::load(temp_dir.path()).unwrap().binaries["test-binary"].validators;
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+        assert_eq!(validators.last_modified.as_deref(), Some(LAST_MODIFIED));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_not_modified_records_current_cli_version() {
+        use crate::utils::bin::manifest::ManifestEntry;
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/testbin"))
+            .and(header("if-none-match", "\"v1\""))
+            .respond_with(ResponseTemplate::new(304))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let served = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        write_with_mode(
+            temp_dir.path().join("test-binary"),
+            TEST_SCRIPT,
+            DEFAULT_BINARY_MODE,
+        )
+        .unwrap();
+        let mut manifest = BinManifest::default();
+        manifest.binaries.insert(
+            "test-binary".to_string(),
+            ManifestEntry {
+                installed_by_cli_version: "0.0.1".to_string(),
+                url: served.urls[0].clone(),
+                target: None,
+                version: None,
+                validators: CacheValidators {
+                    etag: Some("\"v1\"".to_string()),
+                    last_modified: None,
+                },
+            },
+        );
+        manifest.save(temp_dir.path()).unwrap();
+
+        let options = InstallOptions {
+            force_download: true,
+            ..Default::default()
+        };
+        install_binary(&served, temp_dir.path(), &options)
+            .await
+            .unwrap();
+
+        // `system install --force` clears the "installed by an older CLI" warning
+        let manifest = BinManifest::load(temp_dir.path()).unwrap();
+        assert_eq!(
+            manifest.binaries["test-binary"].installed_by_cli_version,
+            env!("CARGO_PKG_VERSION")
+        );
+        assert!(
+            manifest
+                .installed_by_older_cli(env!("CARGO_PKG_VERSION"))
+                .is_empty()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_binary_to_custom_path() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(TEST_SCRIPT))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let provider = ServedProvider {
+            name: "test-binary",
+            urls: vec![format!("{}/testbin", server.uri())],
+        };
+        let options = InstallOptions::default();
+
+        // A file path is used as is
+        let target = temp_dir.path().join("my-tool");
+        let path = install_binary_to(&provider, &target, &options)
+            .await
+            .unwrap();
+        assert_eq!(path, target);
+        assert_eq!(fs::read(&path).unwrap(), TEST_SCRIPT);
+        assert!(is_executable(&path).unwrap());
+        assert!(!BinManifest::path(temp_dir.path()).exists());
+
+        // A directory receives the binary under its local name
+        let path = install_binary_to(&provider, temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert_eq!(path, temp_dir.path().join(provider.local_name()));
+        assert!(is_executable(&path).unwrap());
+
+        let missing = temp_dir.path().join("missing/my-tool");
+        let error = install_binary_to(&provider, &missing, &options)
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("does not exist"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_binary_to_rejects_tampered_download() {
+        let (_server, provider) = serve_hashed(b"#!/bin/sh\necho tampered\n").await;
+        let temp_dir = TempDir::new().unwrap();
+        let target = temp_dir.path().join("my-tool");
+
+        let error = install_binary_to(&provider, &target, &InstallOptions::default())
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(error.contains("Checksum mismatch"), "{}", error);
+        assert!(
+            error.contains("c082598f177e480431110e5e0c9cb3c9bce853dbcfe55b6f11b316863bedc44a"),
+            "{}",
+            error
+        );
+        assert!(!target.exists());
+    }
+
+    #[test]
+    fn test_mirror_url() {
+        let url = "https://github.com/agnosticeng/agt/releases/download/v0.0.23/agt_0.0.23_linux_amd64_v1";
+        assert_eq!(
+            mirror_url(url, "https://mirror.example.com/gh/").unwrap(),
+            "https://mirror.example.com/gh/agnosticeng/agt/releases/download/v0.0.23/agt_0.0.23_linux_amd64_v1"
+        );
+        assert!(mirror_url(url, "  ").is_none());
+        assert!(
+            mirror_url(
+                "https://builds.clickhouse.com/master/amd64/clickhouse",
+                "https://m"
+            )
+            .is_none()
+        );
+    }
+
+    /// Serves [`TEST_SCRIPT`] slowly, recording the peak number of in-flight downloads
+    async fn serve_tracking_downloads(peak: Arc<AtomicUsize>) -> String {
+        use axum::{Router, routing::get};
+
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let app = Router::new().route(
+            "/{name}",
+            get(move || {
+                let in_flight = in_flight.clone();
+                let peak = peak.clone();
+                async move {
+                    let current = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(current, Ordering::SeqCst);
+                    tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    TEST_SCRIPT
+                }
+            }),
+        );
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        format!("http://{}", addr)
+    }
+
+    async fn peak_concurrent_installs(concurrency: Option<NonZeroUsize>) -> usize {
+        let peak = Arc::new(AtomicUsize::new(0));
+        let base_url = serve_tracking_downloads(peak.clone()).await;
+
+        let registry = ProviderRegistry::try_new(
+            ["one", "two", "three"]
+                .into_iter()
+                .map(|name| {
+                    Box::new(ServedProvider {
+                        name,
+                        urls: vec![format!("{}/{}", base_url, name)],
+                    }) as Box<dyn BinaryInfoProvider>
+                })
+                .collect(),
+        )
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = InstallOptions {
+            concurrency,
+            ..Default::default()
+        };
+        let paths = registry
+            .ensure_all_binaries(temp_dir.path(), &options)
+            .await
+            .unwrap();
+        assert_eq!(paths.len(), 3);
+        assert!(paths[0].ends_with("one"));
+        assert!(paths[2].ends_with("three"));
+
+        peak.load(Ordering::SeqCst)
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_ensure_all_binaries_reports_install_timings() {
+        let base_url = serve_tracking_downloads(Arc::new(AtomicUsize::new(0))).await;
+        let registry = ProviderRegistry::try_new(
+            ["one", "two"]
+                .into_iter()
+                .map(|name| {
+                    Box::new(ServedProvider {
+                        name,
+                        urls: vec![format!("{}/{}", base_url, name)],
+                    }) as Box<dyn BinaryInfoProvider>
+                })
+                .collect(),
+        )
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = InstallOptions {
+            events: EventEmitter::recording(),
+            ..Default::default()
+        };
+        registry
+            .ensure_all_binaries(temp_dir.path(), &options)
+            .await
+            .unwrap();
+
+        // Each mock download takes 100ms, which the reported timing must include
+        let timings: Vec<(String, u64)> = options
+            .events
+            .recorded()
+            .into_iter()
+            .filter_map(|event| match event {
+                Event::InstallComplete {
+                    name, elapsed_ms, ..
+                } => Some((name, elapsed_ms)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(timings.len(), 2);
+        assert!(timings.iter().all(|(_, elapsed_ms)| *elapsed_ms >= 100));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_ensure_all_binaries_only_selected() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        for (name, expected) in [("s3fs", 1), ("clickhouse", 0), ("agt", 0)] {
+            Mock::given(method("GET"))
+                .and(path(format!("/{}", name)))
+                .respond_with(ResponseTemplate::new(200).set_body_bytes(TEST_SCRIPT))
+                .expect(expected)
+                .mount(&server)
+                .await;
+        }
+        let registry = ProviderRegistry::try_new(
+            ["s3fs", "clickhouse", "agt"]
+                .into_iter()
+                .map(|name| {
+                    Box::new(ServedProvider {
+                        name,
+                        urls: vec![format!("{}/{}", server.uri(), name)],
+                    }) as Box<dyn BinaryInfoProvider>
+                })
+                .collect(),
+        )
+        .unwrap();
+
+        let temp_dir = TempDir::new().unwrap();
+        let options = InstallOptions {
+            selection: BinarySelection {
+                only: vec!["s3fs".to_string()],
+                skip: Vec::new(),
+            },
+            ..Default::default()
+        };
+        let paths = registry
+            .ensure_all_binaries(temp_dir.path(), &options)
+            .await
+            .unwrap();
+
+        assert_eq!(paths, [temp_dir.path().join("s3fs")]);
+        assert!(!temp_dir.path().join("clickhouse").exists());
+        assert!(!temp_dir.path().join("agt").exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_capabilities_drive_version_and_run() {
+        let temp_dir = TempDir::new().unwrap();
+        // `s3fs --help` exiting non-zero still counts as a started binary
+        write_with_mode(
+            temp_dir.path().join("s3fs"),
+            b"#!/bin/sh\nexit 2\n",
+            DEFAULT_BINARY_MODE,
+        )
+        .unwrap();
+
+        let version = get_binary_version(&s3fs(), temp_dir.path()).await.unwrap();
+        assert_eq!(version, "v0.0.1 (from agnosticeng/s3fs)");
+
+        let manager = BinaryManager::new(temp_dir.path());
+        let error = manager.run("s3fs", &["--addr", "127.0.0.1:0"]).await;
+        assert!(error.unwrap_err().to_string().contains("long-running"));
+    }
+
+    #[test]
+    fn test_registry_select() {
+        let registry = registry();
+        let names = |selection: BinarySelection| -> Vec<&str> {
+            registry
+                .select(&selection)
+                .unwrap()
+                .iter()
+                .map(|p| p.name())
+                .collect()
+        };
+
+        assert_eq!(
+            names(BinarySelection::default()),
+            ["s3fs", "ClickHouse", "agt"]
+        );
+        assert_eq!(
+            names(BinarySelection {
+                only: Vec::new(),
+                skip: vec!["clickhouse".to_string()],
+            }),
+            ["s3fs", "agt"]
+        );
+        assert!(
+            registry
+                .select(&BinarySelection {
+                    only: vec!["nope".to_string()],
+                    skip: Vec::new(),
+                })
+                .is_err()
+        );
+        assert_eq!(parse_binary_name("clickhouse").unwrap(), "ClickHouse");
+        assert!(parse_binary_name("nope").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_concurrency_of_one_is_sequential() {
+        assert_eq!(peak_concurrent_installs(NonZeroUsize::new(1)).await, 1);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_install_concurrency_defaults_to_all_binaries() {
+        assert!(peak_concurrent_installs(None).await > 1);
     }
 }
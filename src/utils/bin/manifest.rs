@@ -0,0 +1,226 @@
+//! Record of how each managed binary was installed
+//!
+//! `install_binary` adds an entry to `manifest.json` in the bin directory after
+//! every successful install, so later commands can tell where a binary came
+//! from and which CLI version placed it. The HTTP validators of the download
+//! are kept too, so a forced reinstall can skip an unchanged binary.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use reqwest::header::{ETAG, HeaderMap, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+use super::manager::{BinResult, SystemTarget};
+use crate::utils::fs::write_atomic;
+
+/// Name of the manifest file inside the bin directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Install record of one binary
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    /// Version of the CLI that installed the binary
+    pub installed_by_cli_version: String,
+    /// URL the binary was downloaded from
+    pub url: String,
+    /// Platform the binary was built for (`--target`), e.g. `linux-x86_64`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Upstream version pinned with `--bin-version`, `None` for the default build
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(flatten)]
+    pub validators: CacheValidators,
+}
+
+/// `ETag` and `Last-Modified` of a downloaded binary, sent back on refresh
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheValidators {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
+}
+
+impl CacheValidators {
+    /// Validators of a download response
+    pub fn from_headers(headers: &HeaderMap) -> Self {
+        let value = |name| {
+            headers
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string)
+        };
+        Self {
+            etag: value(ETAG),
+            last_modified: value(LAST_MODIFIED),
+        }
+    }
+
+    /// Whether the server sent nothing to revalidate against
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// Contents of `manifest.json`, keyed by provider name
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinManifest {
+    #[serde(default)]
+    pub binaries: BTreeMap<String, ManifestEntry>,
+}
+
+impl BinManifest {
+    /// Path of the manifest in `bin_dir`
+    pub fn path(bin_dir: &Path) -> PathBuf {
+        bin_dir.join(MANIFEST_FILE)
+    }
+
+    /// Loads the manifest, empty when none has been written yet
+    pub fn load(bin_dir: &Path) -> BinResult<Self> {
+        let path = Self::path(bin_dir);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path)?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid binary manifest {}: {}", path.display(), e).into())
+    }
+
+    /// Writes the manifest atomically
+    pub fn save(&self, bin_dir: &Path) -> BinResult<()> {
+        write_atomic(
+            Self::path(bin_dir),
+            serde_json::to_string_pretty(self)?.as_bytes(),
+        )
+    }
+
+    /// Records that the current CLI installed `name` from `url`, built for `target`
+    pub fn record_install(
+        bin_dir: &Path,
+        name: &str,
+        url: &str,
+        target: &SystemTarget,
+        version: Option<&str>,
+        validators: CacheValidators,
+    ) -> BinResult<()> {
+        let mut manifest = Self::load(bin_dir)?;
+        manifest.binaries.insert(
+            name.to_string(),
+            ManifestEntry {
+                installed_by_cli_version: env!("CARGO_PKG_VERSION").to_string(),
+                url: url.to_string(),
+                target: Some(target.to_string()),
+                version: version.map(str::to_string),
+                validators,
+            },
+        );
+        manifest.save(bin_dir)
+    }
+
+    /// Validators to revalidate `name` with, when it was last downloaded from `url`
+    pub fn validators_for(&self, name: &str, url: &str) -> Option<&CacheValidators> {
+        self.binaries
+            .get(name)
+            .filter(|entry| entry.url == url && !entry.validators.is_empty())
+            .map(|entry| &entry.validators)
+    }
+
+    /// Binaries installed by a CLI older than `cli_version`, with the version that placed them
+    pub fn installed_by_older_cli(&self, cli_version: &str) -> Vec<(&str, &str)> {
+        self.binaries
+            .iter()
+            .filter(|(_, entry)| {
+                compare_versions(&entry.installed_by_cli_version, cli_version) == Ordering::Less
+            })
+            .map(|(name, entry)| (name.as_str(), entry.installed_by_cli_version.as_str()))
+            .collect()
+    }
+}
+
+/// Compares dotted numeric versions (`0.1.10` > `0.1.9`), ignoring a leading `v`
+///
+/// Non-numeric components compare as 0.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parts = |version: &str| -> Vec<u64> {
+        version
+            .trim_start_matches('v')
+            .split(['.', '-', '+'])
+            .map(|part| part.parse().unwrap_or(0))
+            .collect()
+    };
+    let (a, b) = (parts(a), parts(b));
+
+    (0..a.len().max(b.len()))
+        .map(|i| {
+            a.get(i)
+                .copied()
+                .unwrap_or(0)
+                .cmp(&b.get(i).copied().unwrap_or(0))
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("0.1.9", "0.1.10"), Ordering::Less);
+        assert_eq!(compare_versions("v1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("2.0.0", "1.9.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_record_install_round_trip() {
+        let temp_dir = TempDir::new().unwrap();
+        assert_eq!(
+            BinManifest::load(temp_dir.path()).unwrap(),
+            BinManifest::default()
+        );
+
+        let validators = CacheValidators {
+            etag: Some("\"abc\"".to_string()),
+            last_modified: None,
+        };
+        BinManifest::record_install(
+            temp_dir.path(),
+            "agt",
+            "https://example.com/agt",
+            &SystemTarget::MacOsAarch64,
+            Some("0.0.24"),
+            validators.clone(),
+        )
+        .unwrap();
+        let manifest = BinManifest::load(temp_dir.path()).unwrap();
+        assert_eq!(
+            manifest.binaries["agt"],
+            ManifestEntry {
+                installed_by_cli_version: env!("CARGO_PKG_VERSION").to_string(),
+                url: "https://example.com/agt".to_string(),
+                target: Some("macos-aarch64".to_string()),
+                version: Some("0.0.24".to_string()),
+                validators: validators.clone(),
+            }
+        );
+        assert_eq!(
+            manifest.validators_for("agt", "https://example.com/agt"),
+            Some(&validators)
+        );
+        assert_eq!(
+            manifest.validators_for("agt", "https://example.com/agt-v2"),
+            None
+        );
+        assert!(
+            manifest
+                .installed_by_older_cli(env!("CARGO_PKG_VERSION"))
+                .is_empty()
+        );
+    }
+}
@@ -10,6 +10,10 @@
 //!
 //! All functionality is consolidated in:
 //! - `manager`: Complete binary management system with types, providers, and operations
+//! - `manifest`: Record of which URL and CLI version installed each binary
+//! - `install_log`: Append-only audit log of every install attempt
+//! - `kind`: Typed [`BinaryKind`] names for internal lookups
+//! - `bandwidth`: Bandwidth probe behind `--auto-concurrency`
 
 // Binary provider modules
 pub mod agt;
@@ -17,10 +21,17 @@ pub mod clickhouse;
 pub mod s3fs;
 
 // Consolidated management module
+pub mod bandwidth;
+pub mod install_log;
+pub mod kind;
 pub mod manager;
+pub mod manifest;
 
 // Re-export commonly used types and functions
-pub use manager::{BinResult, BinaryInfo, SystemTarget};
+pub use manager::{
+    BinResult, BinaryInfo, BinaryManager, BinarySelection, InstallOptions, SystemTarget,
+    VersionOverride,
+};
 
 // Re-export provider system and management functions
 pub use manager::{
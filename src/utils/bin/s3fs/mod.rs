@@ -3,10 +3,13 @@
 //! This module provides configuration and information for the S3FS binary.
 //! All actual operations (install, run, check) are handled by the common manager.
 
-use crate::utils::bin::manager::{BinaryInfoProvider, SystemTarget};
+use crate::utils::bin::manager::{BinResult, BinaryInfoProvider, SystemTarget, github_mirror_url};
+
+/// Release of s3fs installed by default
+const S3FS_VERSION: &str = "0.0.1";
 
 /// S3FS binary information provider
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct S3fsProvider;
 
 impl S3fsProvider {
@@ -25,17 +28,18 @@ impl BinaryInfoProvider for S3fsProvider {
         "s3fs"
     }
 
-    fn get_download_url(&self, target: &SystemTarget) -> String {
-        let asset_name = match target {
-            SystemTarget::MacOsAarch64 => "s3fs_aarch64-apple-darwin",
-            SystemTarget::MacOsX86_64 => "s3fs_x86_64-apple-darwin",
-            SystemTarget::LinuxX86_64 => "s3fs_x86_64-unknown-linux-gnu",
-        };
-
-        format!(
-            "https://github.com/agnosticeng/s3fs/releases/download/v0.0.1/{}",
-            asset_name
-        )
+    fn get_download_url(&self, target: &SystemTarget) -> BinResult<String> {
+        Ok(release_url(S3FS_VERSION, target))
+    }
+
+    fn get_download_urls(&self, target: &SystemTarget) -> BinResult<Vec<String>> {
+        let primary = self.get_download_url(target)?;
+        let mirror = github_mirror_url(&primary);
+        Ok(std::iter::once(primary).chain(mirror).collect())
+    }
+
+    fn get_versioned_download_url(&self, target: &SystemTarget, version: &str) -> Option<String> {
+        Some(release_url(version.trim_start_matches('v'), target))
     }
 
     fn version_args(&self) -> &[&str] {
@@ -46,6 +50,28 @@ impl BinaryInfoProvider for S3fsProvider {
         // s3fs doesn't provide version info, so we return a static version
         Some("v0.0.1 (from agnosticeng/s3fs)".to_string())
     }
+
+    fn has_version_flag(&self) -> bool {
+        false
+    }
+
+    fn is_long_running(&self) -> bool {
+        true
+    }
+}
+
+/// Builds the GitHub release asset URL of an s3fs version
+fn release_url(version: &str, target: &SystemTarget) -> String {
+    let asset_name = match target {
+        SystemTarget::MacOsAarch64 => "s3fs_aarch64-apple-darwin",
+        SystemTarget::MacOsX86_64 => "s3fs_x86_64-apple-darwin",
+        SystemTarget::LinuxX86_64 => "s3fs_x86_64-unknown-linux-gnu",
+    };
+
+    format!(
+        "https://github.com/agnosticeng/s3fs/releases/download/v{}/{}",
+        version, asset_name
+    )
 }
 
 /// Create a new S3FS provider instance
@@ -65,11 +91,18 @@ mod tests {
         assert_eq!(provider.version_args(), &["--help"]);
 
         let target = SystemTarget::MacOsAarch64;
-        let url = provider.get_download_url(&target);
+        let url = provider.get_download_url(&target).unwrap();
         assert!(url.contains("s3fs_aarch64-apple-darwin"));
         assert!(url.contains("github.com/agnosticeng/s3fs"));
     }
 
+    #[test]
+    fn test_s3fs_capabilities() {
+        let provider = S3fsProvider::new();
+        assert!(!provider.has_version_flag());
+        assert!(provider.is_long_running());
+    }
+
     #[test]
     fn test_s3fs_version_parsing() {
         let provider = S3fsProvider::new();
@@ -81,13 +114,19 @@ mod tests {
     fn test_s3fs_download_urls() {
         let provider = S3fsProvider::new();
 
-        let macos_arm_url = provider.get_download_url(&SystemTarget::MacOsAarch64);
+        let macos_arm_url = provider
+            .get_download_url(&SystemTarget::MacOsAarch64)
+            .unwrap();
         assert!(macos_arm_url.contains("s3fs_aarch64-apple-darwin"));
 
-        let macos_x86_url = provider.get_download_url(&SystemTarget::MacOsX86_64);
+        let macos_x86_url = provider
+            .get_download_url(&SystemTarget::MacOsX86_64)
+            .unwrap();
         assert!(macos_x86_url.contains("s3fs_x86_64-apple-darwin"));
 
-        let linux_url = provider.get_download_url(&SystemTarget::LinuxX86_64);
+        let linux_url = provider
+            .get_download_url(&SystemTarget::LinuxX86_64)
+            .unwrap();
         assert!(linux_url.contains("s3fs_x86_64-unknown-linux-gnu"));
     }
 }
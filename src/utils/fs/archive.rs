@@ -1,19 +1,68 @@
 use std::fs::{self, File};
 use std::io;
-use std::path::Path;
+use std::path::{Component, Path, PathBuf};
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
 use zip::ZipArchive;
 
+/// Include/exclude glob patterns selecting which ZIP entries are extracted
+///
+/// Patterns are matched against the entry path after root folder stripping,
+/// e.g. `queries/**/*.sql`. An entry is extracted when it matches an include
+/// pattern (or none are given) and no exclude pattern. The default filter
+/// extracts everything.
+#[derive(Debug, Clone, Default)]
+pub struct ExtractFilter {
+    include: Option<GlobSet>,
+    exclude: Option<GlobSet>,
+}
+
+impl ExtractFilter {
+    /// Builds a filter, failing on an invalid glob pattern
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self, globset::Error> {
+        Ok(Self {
+            include: build_glob_set(include)?,
+            exclude: build_glob_set(exclude)?,
+        })
+    }
+
+    /// Whether an entry at `relative_path` should be extracted
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        self.include
+            .as_ref()
+            .is_none_or(|set| set.is_match(relative_path))
+            && !self
+                .exclude
+                .as_ref()
+                .is_some_and(|set| set.is_match(relative_path))
+    }
+}
+
+fn build_glob_set(patterns: &[String]) -> Result<Option<GlobSet>, globset::Error> {
+    if patterns.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        builder.add(Glob::new(pattern)?);
+    }
+    builder.build().map(Some)
+}
+
 /// Extracts a ZIP file to the destination directory with root folder stripping
 ///
 /// This function automatically strips the root folder from ZIP archives (common with
 /// GitHub repository downloads) and extracts the contents directly to the destination.
 /// For example, if a ZIP contains "project-main/file.txt", it will be extracted as
-/// "dest/file.txt" instead of "dest/project-main/file.txt".
+/// "dest/file.txt" instead of "dest/project-main/file.txt". Archives whose entries
+/// are not all nested in one folder are extracted as they are.
 ///
 /// # Arguments
 ///
 /// * `zip_path` - Path to the ZIP file to extract
 /// * `dest` - The destination directory to extract the contents to
+/// * `filter` - Which entries to extract, see [`ExtractFilter`]
 ///
 /// # Returns
 ///
@@ -22,13 +71,42 @@ use zip::ZipArchive;
 /// # Examples
 ///
 /// ```no_run
-/// use cli::utils::fs::archive::extract_zip_with_root_stripping;
+/// use cli::utils::fs::archive::{ExtractFilter, extract_zip_with_root_stripping};
 ///
-/// extract_zip_with_root_stripping("./archive.zip", "./extracted").unwrap();
+/// extract_zip_with_root_stripping("./archive.zip", "./extracted", &ExtractFilter::default()).unwrap();
 /// ```
+#[allow(dead_code)]
 pub fn extract_zip_with_root_stripping<P: AsRef<Path>, Q: AsRef<Path>>(
     zip_path: P,
     dest: Q,
+    filter: &ExtractFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    extract_zip_stripping(zip_path, dest, None, filter)
+}
+
+/// Extracts a ZIP file, removing the first `strip_components` path components of each entry
+///
+/// Like `tar --strip-components`: `Some(0)` extracts entries as they are,
+/// `Some(1)` drops the root folder of GitHub archives. `None` strips the root
+/// folder only when every entry is nested in one (see
+/// [`extract_zip_with_root_stripping`]). Entries with no components left, such
+/// as the stripped folders themselves, are skipped, with a warning for files.
+///
+/// # Arguments
+///
+/// * `zip_path` - Path to the ZIP file to extract
+/// * `dest` - The destination directory to extract the contents to
+/// * `strip_components` - Number of leading path components to remove, or `None` to detect a root folder
+/// * `filter` - Which entries to extract, matched after stripping
+///
+/// # Returns
+///
+/// Returns `Ok(())` if successful, or an error if the extraction fails
+pub fn extract_zip_stripping<P: AsRef<Path>, Q: AsRef<Path>>(
+    zip_path: P,
+    dest: Q,
+    strip_components: Option<usize>,
+    filter: &ExtractFilter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let dest_path = dest.as_ref();
 
@@ -41,18 +119,15 @@ pub fn extract_zip_with_root_stripping<P: AsRef<Path>, Q: AsRef<Path>>(
     let zip_file = File::open(&zip_path)?;
     let mut archive = ZipArchive::new(zip_file)?;
 
-    // Find the root folder name to strip it
-    let root_folder = find_root_folder(&mut archive)?;
-
     // Extract all files
-    let file_count = extract_files(&mut archive, dest_path, root_folder.as_deref())?;
+    let file_count = extract_files(&mut archive, dest_path, strip_components, filter)?;
 
     println!("Successfully extracted {} files", file_count);
 
     Ok(())
 }
 
-/// Finds the common root folder in a ZIP archive
+/// Finds the folder every entry of a ZIP archive is nested in
 ///
 /// # Arguments
 ///
@@ -60,25 +135,34 @@ pub fn extract_zip_with_root_stripping<P: AsRef<Path>, Q: AsRef<Path>>(
 ///
 /// # Returns
 ///
-/// Returns the root folder name if one exists, None otherwise
+/// Returns the root folder name if one exists, None when entries sit at the
+/// top level or under different folders
 fn find_root_folder(
     archive: &mut ZipArchive<File>,
-) -> Result<Option<String>, Box<dyn std::error::Error>> {
-    let mut root_folder = None;
+) -> Result<Option<PathBuf>, Box<dyn std::error::Error>> {
+    let mut root_folder: Option<PathBuf> = None;
+    let mut nested = false;
 
     for i in 0..archive.len() {
         let file = archive.by_index(i)?;
-        if let Some(path) = file.enclosed_name() {
-            if let Some(first_component) = path.components().next() {
-                if root_folder.is_none() {
-                    root_folder = Some(first_component.as_os_str().to_string_lossy().to_string());
-                }
-                break;
-            }
+        let Ok(path) = entry_path(file.name_raw()) else {
+            continue;
+        };
+        let mut components = path.components();
+        let Some(first_component) = components.next() else {
+            continue;
+        };
+
+        match &root_folder {
+            None => root_folder = Some(PathBuf::from(first_component.as_os_str())),
+            Some(root) if root.as_os_str() != first_component.as_os_str() => return Ok(None),
+            Some(_) => {}
         }
+        nested |= file.is_dir() || components.next().is_some();
     }
 
-    Ok(root_folder)
+    // A lone top-level file is not a folder to strip
+    Ok(root_folder.filter(|_| nested))
 }
 
 /// Extracts all files from a ZIP archive to the destination
@@ -87,40 +171,61 @@ fn find_root_folder(
 ///
 /// * `archive` - The ZIP archive to extract from
 /// * `dest_path` - The destination directory
-/// * `root_folder` - Optional root folder to strip from paths
+/// * `strip_components` - Number of leading path components to remove from each entry, or `None` to detect a root folder
+/// * `filter` - Which entries to extract
 ///
 /// # Returns
 ///
-/// Returns the number of files extracted
+/// Returns the number of entries written
 fn extract_files(
     archive: &mut ZipArchive<File>,
     dest_path: &Path,
-    root_folder: Option<&str>,
+    strip_components: Option<usize>,
+    filter: &ExtractFilter,
 ) -> Result<usize, Box<dyn std::error::Error>> {
+    let explicit = strip_components.is_some();
+    let strip_components = match strip_components {
+        Some(count) => count,
+        None => usize::from(find_root_folder(archive)?.is_some()),
+    };
     let mut extracted_count = 0;
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
-        let original_path = match file.enclosed_name() {
-            Some(path) => path,
-            None => continue,
+        let original_path = match entry_path(file.name_raw()) {
+            Ok(path) => path,
+            Err(reason) => {
+                eprintln!(
+                    "Warning: skipping archive entry '{}': {}",
+                    file.name_raw().escape_ascii(),
+                    reason
+                );
+                continue;
+            }
         };
 
-        // Strip the root folder if it exists
-        let relative_path = if let Some(root) = root_folder {
-            original_path.strip_prefix(root).unwrap_or(original_path)
-        } else {
-            original_path
-        };
+        // Drop the leading components
+        let relative_path: PathBuf = original_path.components().skip(strip_components).collect();
 
         // Skip if the path becomes empty after stripping
         if relative_path.as_os_str().is_empty() {
+            if explicit && !file.name_raw().ends_with(b"/") {
+                eprintln!(
+                    "Warning: skipping archive entry '{}': stripping {} leading components leaves no path",
+                    file.name_raw().escape_ascii(),
+                    strip_components
+                );
+            }
+            continue;
+        }
+
+        if !filter.matches(&relative_path) {
             continue;
         }
 
-        let outpath = dest_path.join(relative_path);
+        let outpath = dest_path.join(&relative_path);
 
-        if file.name().ends_with('/') {
+        if file.name_raw().ends_with(b"/") {
             // Directory
             fs::create_dir_all(&outpath)?;
         } else {
@@ -147,6 +252,49 @@ fn extract_files(
     Ok(extracted_count)
 }
 
+/// Output path of a ZIP entry, relative to the destination
+///
+/// The name is read from the raw bytes rather than the decoded name, which
+/// the `zip` crate reinterprets as CP437 when the UTF-8 flag is missing. On
+/// Unix, names in a legacy encoding are kept byte for byte. Fails with the
+/// reason to report when the name is unsafe (absolute, escaping the
+/// destination) or cannot be represented on this platform.
+fn entry_path(name_raw: &[u8]) -> Result<PathBuf, String> {
+    if name_raw.contains(&0) {
+        return Err("name contains a NUL byte".to_string());
+    }
+
+    let path = match std::str::from_utf8(name_raw) {
+        Ok(name) => PathBuf::from(name),
+        #[cfg(unix)]
+        Err(_) => {
+            use std::os::unix::ffi::OsStrExt;
+            PathBuf::from(std::ffi::OsStr::from_bytes(name_raw))
+        }
+        #[cfg(not(unix))]
+        Err(_) => {
+            return Err("name is not valid UTF-8 and cannot be represented here".to_string());
+        }
+    };
+
+    let mut depth = 0usize;
+    for component in path.components() {
+        match component {
+            Component::Prefix(_) | Component::RootDir => {
+                return Err("absolute paths are not allowed".to_string());
+            }
+            Component::ParentDir => {
+                depth = depth
+                    .checked_sub(1)
+                    .ok_or("path escapes the destination directory")?;
+            }
+            Component::Normal(_) => depth += 1,
+            Component::CurDir => {}
+        }
+    }
+    Ok(path)
+}
+
 /// Extracts a ZIP file to the destination directory without root folder stripping
 ///
 /// # Arguments
@@ -162,27 +310,13 @@ pub fn extract_zip<P: AsRef<Path>, Q: AsRef<Path>>(
     zip_path: P,
     dest: Q,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let dest_path = dest.as_ref();
-
-    // Create destination directory if it doesn't exist
-    fs::create_dir_all(dest_path)?;
-
-    println!("Extracting to: {}", dest_path.display());
-
-    // Open the ZIP file
-    let zip_file = File::open(&zip_path)?;
-    let mut archive = ZipArchive::new(zip_file)?;
-
     // Extract all files without stripping root folder
-    let file_count = extract_files(&mut archive, dest_path, None)?;
-
-    println!("Successfully extracted {} files", file_count);
-
-    Ok(())
+    extract_zip_stripping(zip_path, dest, Some(0), &ExtractFilter::default())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use std::fs;
     use tempfile::TempDir;
 
@@ -197,9 +331,210 @@ mod tests {
     }
 
     #[test]
-    fn test_find_root_folder_with_empty_archive() {
-        // This would require creating a test ZIP file
-        // For now, just ensure the function signature is correct
-        assert!(true);
+    fn test_extract_empty_archive() {
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("empty.zip");
+
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        writer.finish().unwrap();
+
+        let mut archive = ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let written = extract_files(
+            &mut archive,
+            &temp_dir.path().join("out"),
+            Some(1),
+            &ExtractFilter::default(),
+        )
+        .unwrap();
+        assert_eq!(written, 0);
+    }
+
+    #[test]
+    fn test_strip_components() {
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("nested.zip");
+
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        writer.add_directory("release-1.0/", options).unwrap();
+        writer
+            .add_directory("release-1.0/project/", options)
+            .unwrap();
+        for name in [
+            "release-1.0/NOTICE",
+            "release-1.0/project/README.md",
+            "release-1.0/project/queries/a.sql",
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(name.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let extract = |strip_components: usize| {
+            let dest = temp_dir.path().join(format!("strip-{}", strip_components));
+            extract_zip_stripping(
+                &zip_path,
+                &dest,
+                Some(strip_components),
+                &ExtractFilter::default(),
+            )
+            .unwrap();
+            dest
+        };
+
+        let dest = extract(0);
+        assert!(dest.join("release-1.0/NOTICE").is_file());
+        assert!(dest.join("release-1.0/project/queries/a.sql").is_file());
+
+        let dest = extract(1);
+        assert!(dest.join("NOTICE").is_file());
+        assert!(dest.join("project/README.md").is_file());
+        assert!(dest.join("project/queries/a.sql").is_file());
+
+        // Entries shallower than the strip count are dropped
+        let dest = extract(2);
+        assert!(!dest.join("NOTICE").exists());
+        assert!(dest.join("README.md").is_file());
+        assert!(dest.join("queries/a.sql").is_file());
+    }
+
+    #[test]
+    fn test_root_folder_detection() {
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let write_zip = |name: &str, entries: &[&str]| {
+            let zip_path = temp_dir.path().join(name);
+            let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+            let options = zip::write::FileOptions::default();
+            for entry in entries {
+                writer.start_file(*entry, options).unwrap();
+                writer.write_all(entry.as_bytes()).unwrap();
+            }
+            writer.finish().unwrap();
+            zip_path
+        };
+        let extract = |zip_path: &Path| {
+            let dest = temp_dir.path().join(zip_path.file_stem().unwrap());
+            extract_zip_stripping(zip_path, &dest, None, &ExtractFilter::default()).unwrap();
+            dest
+        };
+
+        // GitHub archives lose their single root folder
+        let zip_path = write_zip("github.zip", &["repo-main/README.md", "repo-main/q/a.sql"]);
+        let dest = extract(&zip_path);
+        assert!(dest.join("README.md").is_file());
+        assert!(dest.join("q/a.sql").is_file());
+
+        // Top-level files are kept as they are
+        let zip_path = write_zip("flat.zip", &["README.md", "q/a.sql"]);
+        let dest = extract(&zip_path);
+        assert!(dest.join("README.md").is_file());
+        assert!(dest.join("q/a.sql").is_file());
+
+        let zip_path = write_zip("single.zip", &["README.md"]);
+        assert!(extract(&zip_path).join("README.md").is_file());
+    }
+
+    #[test]
+    fn test_extract_with_filter() {
+        use std::io::Write;
+
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("template.zip");
+
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        writer.add_directory("template-main/", options).unwrap();
+        for name in [
+            "template-main/README.md",
+            "template-main/queries/a.sql",
+            "template-main/queries/nested/b.sql",
+            "template-main/queries/draft.sql",
+            "template-main/data/big.csv",
+        ] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(name.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let filter = ExtractFilter::new(
+            &["queries/**".to_string(), "README.md".to_string()],
+            &["**/draft.sql".to_string()],
+        )
+        .unwrap();
+        let dest = temp_dir.path().join("out");
+        extract_zip_with_root_stripping(&zip_path, &dest, &filter).unwrap();
+
+        assert!(dest.join("README.md").is_file());
+        assert!(dest.join("queries/a.sql").is_file());
+        assert!(dest.join("queries/nested/b.sql").is_file());
+        assert!(!dest.join("queries/draft.sql").exists());
+        assert!(!dest.join("data").exists());
+
+        let mut archive = ZipArchive::new(File::open(&zip_path).unwrap()).unwrap();
+        let written = extract_files(
+            &mut archive,
+            &temp_dir.path().join("again"),
+            Some(1),
+            &filter,
+        )
+        .unwrap();
+        assert_eq!(written, 3);
+    }
+
+    #[test]
+    fn test_entry_path_rejects_unsafe_names() {
+        assert_eq!(entry_path(b"a/b.txt").unwrap(), Path::new("a/b.txt"));
+        assert_eq!(entry_path(b"a/../b.txt").unwrap(), Path::new("a/../b.txt"));
+        assert!(entry_path(b"../b.txt").unwrap_err().contains("escapes"));
+        assert!(entry_path(b"/etc/passwd").unwrap_err().contains("absolute"));
+        assert!(entry_path(b"a\0b").unwrap_err().contains("NUL"));
+        assert!(entry_path(b"../caf\xe9").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_extract_non_utf8_entry_names() {
+        use std::io::Write;
+        use std::os::unix::ffi::OsStrExt;
+
+        let temp_dir = TempDir::new().unwrap();
+        let zip_path = temp_dir.path().join("latin1.zip");
+
+        // The writer only takes UTF-8 names, so write ASCII placeholders of the
+        // same length and swap in Latin-1 bytes afterwards
+        let mut writer = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        let options = zip::write::FileOptions::default();
+        for name in ["root/caf?.sql", "root/ok.sql"] {
+            writer.start_file(name, options).unwrap();
+            writer.write_all(b"select 1").unwrap();
+        }
+        writer.finish().unwrap();
+        let mut bytes = fs::read(&zip_path).unwrap();
+        for start in 0..bytes.len() - 4 {
+            if &bytes[start..start + 4] == b"caf?" {
+                bytes[start + 3] = 0xe9;
+            }
+        }
+        fs::write(&zip_path, bytes).unwrap();
+
+        let dest = temp_dir.path().join("out");
+        extract_zip_with_root_stripping(&zip_path, &dest, &ExtractFilter::default()).unwrap();
+
+        let mut names: Vec<Vec<u8>> = fs::read_dir(&dest)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().as_bytes().to_vec())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec![b"caf\xe9.sql".to_vec(), b"ok.sql".to_vec()]);
+    }
+
+    #[test]
+    fn test_extract_filter_rejects_invalid_glob() {
+        assert!(ExtractFilter::new(&["[".to_string()], &[]).is_err());
+        assert!(ExtractFilter::default().matches(Path::new("anything")));
     }
 }
@@ -0,0 +1,72 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use sha2::{Digest, Sha256};
+
+/// Computes the hex-encoded SHA-256 digest of the given bytes
+///
+/// # Examples
+///
+/// ```
+/// use cli::utils::fs::checksum::sha256_hex;
+///
+/// assert_eq!(
+///     sha256_hex(b"abc"),
+///     "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+/// );
+/// ```
+#[allow(dead_code)]
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Computes the hex-encoded SHA-256 digest of a file without loading it in memory
+///
+/// # Arguments
+///
+/// * `path` - The path to the file to hash
+///
+/// # Returns
+///
+/// Returns the lowercase hex digest, or an error if the file cannot be read
+pub fn sha256_file<P: AsRef<Path>>(path: P) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+
+    loop {
+        let read = file.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_sha256_file_matches_bytes() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join("data.bin");
+        std::fs::write(&path, b"hello world").unwrap();
+
+        let digest = sha256_file(&path).unwrap();
+        assert_eq!(digest, sha256_hex(b"hello world"));
+        assert_eq!(
+            digest,
+            "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9"
+        );
+    }
+
+    #[test]
+    fn test_sha256_file_missing() {
+        assert!(sha256_file("/nonexistent/file").is_err());
+    }
+}
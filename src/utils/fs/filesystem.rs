@@ -10,17 +10,78 @@ use std::path::{Path, PathBuf};
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if successful, or an error if the creation fails
+/// Returns `Ok(true)` if the directory was created, `Ok(false)` if it was
+/// already present, or an error if the creation fails
 ///
 /// # Examples
 ///
 /// ```no_run
 /// use cli::utils::fs::filesystem::ensure_dir_exists;
 ///
-/// ensure_dir_exists("./some/nested/directory").unwrap();
+/// let created = ensure_dir_exists("./some/nested/directory").unwrap();
 /// ```
-pub fn ensure_dir_exists<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+pub fn ensure_dir_exists<P: AsRef<Path>>(path: P) -> Result<bool, Box<dyn std::error::Error>> {
+    let existed = path.as_ref().is_dir();
     fs::create_dir_all(&path)?;
+    Ok(!existed)
+}
+
+/// Checks that a path is either absent or usable as a directory
+///
+/// Returns a descriptive error when the path is a regular file, or a symlink
+/// that is dangling or points to something other than a directory, so callers
+/// can tell the user exactly which path to fix instead of surfacing a raw I/O error.
+///
+/// # Arguments
+///
+/// * `path` - The directory path to check
+///
+/// # Examples
+///
+/// ```no_run
+/// use cli::utils::fs::filesystem::check_dir_path;
+///
+/// check_dir_path("/home/user/.agnostic/bin").unwrap();
+/// ```
+pub fn check_dir_path<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+
+    let metadata = match fs::symlink_metadata(path) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(format!("Cannot access {}: {}", path.display(), e).into()),
+    };
+
+    if metadata.file_type().is_symlink() {
+        let target = fs::read_link(path)
+            .map(|t| t.display().to_string())
+            .unwrap_or_else(|_| "an unreadable target".to_string());
+
+        return match fs::metadata(path) {
+            Ok(resolved) if resolved.is_dir() => Ok(()),
+            Ok(_) => Err(format!(
+                "{} is a symlink to {}, which is not a directory. Point it at a directory or remove it.",
+                path.display(),
+                target
+            )
+            .into()),
+            Err(_) => Err(format!(
+                "{} is a symlink to {}, which does not exist. Create the target directory or remove the symlink.",
+                path.display(),
+                target
+            )
+            .into()),
+        };
+    }
+
+    if !metadata.is_dir() {
+        return Err(format!(
+            "{} exists but is not a directory. Move or remove it so it can be recreated as a directory.",
+            path.display()
+        )
+        .into());
+    }
+
     Ok(())
 }
 
@@ -45,6 +106,61 @@ pub fn remove_path<P: AsRef<Path>>(path: P) -> Result<(), Box<dyn std::error::Er
     Ok(())
 }
 
+/// Removes a file or directory, refusing anything outside `allowed_root`
+///
+/// Both paths are canonicalized first, so `..` components and symlinks cannot
+/// escape the root. The root itself, `$HOME` and `/` are never removed. Use
+/// this instead of [`remove_path`] whenever the path is user-supplied or derived.
+///
+/// # Arguments
+///
+/// * `path` - The path to remove
+/// * `allowed_root` - Directory the path must be strictly inside
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the path was removed or did not exist, or an error if
+/// the removal was refused or failed
+///
+/// # Examples
+///
+/// ```no_run
+/// use cli::utils::fs::filesystem::remove_path_guarded;
+///
+/// remove_path_guarded("/home/user/.agnostic/temp/download", "/home/user/.agnostic").unwrap();
+/// ```
+pub fn remove_path_guarded<P: AsRef<Path>, R: AsRef<Path>>(
+    path: P,
+    allowed_root: R,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let allowed_root = allowed_root.as_ref();
+
+    if fs::symlink_metadata(path).is_err() {
+        return Ok(());
+    }
+
+    let root = fs::canonicalize(allowed_root)
+        .map_err(|e| format!("Cannot resolve {}: {}", allowed_root.display(), e))?;
+    let target =
+        fs::canonicalize(path).map_err(|e| format!("Cannot resolve {}: {}", path.display(), e))?;
+
+    let is_home = env::var_os("HOME")
+        .and_then(|home| fs::canonicalize(home).ok())
+        .is_some_and(|home| home == target);
+
+    if target == root || !target.starts_with(&root) || is_home || target.parent().is_none() {
+        return Err(format!(
+            "Refusing to remove {}: only paths inside {} may be removed",
+            path.display(),
+            root.display()
+        )
+        .into());
+    }
+
+    remove_path(target)
+}
+
 /// Checks if a path exists and is a directory
 ///
 /// # Arguments
@@ -90,6 +206,9 @@ pub fn file_size<P: AsRef<Path>>(path: P) -> Result<u64, Box<dyn std::error::Err
 
 /// Creates a temporary file path in the given directory
 ///
+/// The name combines a nanosecond timestamp with random bytes, so concurrent
+/// callers (e.g. parallel downloads) never receive the same path.
+///
 /// # Arguments
 ///
 /// * `dir` - The directory to create the temporary file in
@@ -110,14 +229,80 @@ pub fn temp_file_path<P: AsRef<Path>>(
         .duration_since(UNIX_EPOCH)
         .unwrap_or_default()
         .as_nanos();
+    let random = uuid::Uuid::new_v4().simple().to_string();
+
+    let unique = format!("{}_{}", timestamp, &random[..8]);
+    temp_file_path_with_key(dir, prefix, suffix, &unique)
+}
+
+/// Creates a temporary file path named after a caller-provided unique key
+///
+/// Unlike [`temp_file_path`], the result is deterministic: retrying an operation
+/// with the same key gets the same path back, so a previous partial file can be
+/// found and resumed or cleaned up. Characters that are not safe in a file name
+/// are replaced with `_`.
+///
+/// # Examples
+///
+/// ```
+/// use cli::utils::fs::filesystem::temp_file_path_with_key;
+///
+/// let path = temp_file_path_with_key("/tmp", Some("download"), Some(".zip"), "agt/v0.0.23");
+/// assert_eq!(path.to_str(), Some("/tmp/download_agt_v0.0.23.zip"));
+/// ```
+#[allow(dead_code)]
+pub fn temp_file_path_with_key<P: AsRef<Path>>(
+    dir: P,
+    prefix: Option<&str>,
+    suffix: Option<&str>,
+    key: &str,
+) -> std::path::PathBuf {
+    let key: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
 
     let prefix = prefix.unwrap_or("temp");
     let suffix = suffix.unwrap_or("");
 
-    let filename = format!("{}_{}{}", prefix, timestamp, suffix);
+    let filename = format!("{}_{}{}", prefix, key, suffix);
     dir.as_ref().join(filename)
 }
 
+/// Writes a file atomically, creating its parent directories as needed
+///
+/// The content is written to a temporary file next to `path` and renamed over
+/// it, so readers never observe a partially written file.
+pub fn write_atomic<P: AsRef<Path>>(
+    path: P,
+    contents: &[u8],
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(Path::new("."));
+    fs::create_dir_all(parent)?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let part_path = temp_file_path(parent, Some(&format!(".{}", file_name)), Some(".part"));
+
+    let result = fs::write(&part_path, contents).and_then(|()| fs::rename(&part_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&part_path);
+    }
+    Ok(result?)
+}
+
 /// Gets the current working directory
 ///
 /// # Returns
@@ -164,8 +349,14 @@ pub fn get_current_working_dir() -> Result<PathBuf, Box<dyn std::error::Error>>
 /// ```
 pub fn create_agnostic_working_dir() -> Result<PathBuf, Box<dyn std::error::Error>> {
     let home_dir = env::var("HOME").map_err(|_| "Could not determine home directory")?;
-    let agnostic_dir = PathBuf::from(home_dir).join(".agnostic");
+    create_agnostic_working_dir_in(Path::new(&home_dir))
+}
+
+/// Creates and returns the `.agnostic` directory under `home` instead of `$HOME`
+pub fn create_agnostic_working_dir_in(home: &Path) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    let agnostic_dir = home.join(".agnostic");
 
+    check_dir_path(&agnostic_dir)?;
     ensure_dir_exists(&agnostic_dir)?;
     Ok(agnostic_dir)
 }
@@ -188,6 +379,97 @@ mod tests {
         assert!(is_directory(&test_path));
     }
 
+    #[test]
+    fn test_check_dir_path() {
+        let temp_dir = TempDir::new().unwrap();
+
+        assert!(check_dir_path(temp_dir.path()).is_ok());
+        assert!(check_dir_path(temp_dir.path().join("missing")).is_ok());
+
+        let file_path = temp_dir.path().join("user");
+        File::create(&file_path).unwrap();
+        let err = check_dir_path(&file_path).unwrap_err().to_string();
+        assert!(err.contains(&file_path.display().to_string()));
+        assert!(err.contains("is not a directory"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_dir_path_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let temp_dir = TempDir::new().unwrap();
+        let target_dir = temp_dir.path().join("real");
+        fs::create_dir(&target_dir).unwrap();
+
+        let good_link = temp_dir.path().join("good");
+        symlink(&target_dir, &good_link).unwrap();
+        assert!(check_dir_path(&good_link).is_ok());
+
+        let dangling = temp_dir.path().join("dangling");
+        symlink(temp_dir.path().join("nowhere"), &dangling).unwrap();
+        let err = check_dir_path(&dangling).unwrap_err().to_string();
+        assert!(err.contains("does not exist"));
+        assert!(err.contains("nowhere"));
+
+        let file_target = temp_dir.path().join("file");
+        File::create(&file_target).unwrap();
+        let file_link = temp_dir.path().join("file-link");
+        symlink(&file_target, &file_link).unwrap();
+        let err = check_dir_path(&file_link).unwrap_err().to_string();
+        assert!(err.contains("which is not a directory"));
+    }
+
+    #[test]
+    fn test_remove_path_guarded() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let inside = root.join("temp").join("download");
+        fs::create_dir_all(&inside).unwrap();
+        File::create(inside.join("file.bin")).unwrap();
+
+        remove_path_guarded(&inside, &root).unwrap();
+        assert!(!inside.exists());
+        assert!(root.join("temp").exists());
+
+        // Missing paths are a no-op
+        remove_path_guarded(root.join("missing"), &root).unwrap();
+    }
+
+    #[test]
+    fn test_remove_path_guarded_refuses_root_and_outside() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+
+        let err = remove_path_guarded(&root, &root).unwrap_err().to_string();
+        assert!(err.contains("Refusing to remove"));
+        assert!(root.exists());
+
+        assert!(remove_path_guarded(&outside, &root).is_err());
+        assert!(remove_path_guarded(root.join("..").join("outside"), &root).is_err());
+        assert!(remove_path_guarded(root.join("."), &root).is_err());
+        assert!(outside.exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_remove_path_guarded_refuses_symlink_escape() {
+        let temp_dir = TempDir::new().unwrap();
+        let root = temp_dir.path().join("root");
+        let outside = temp_dir.path().join("outside");
+        fs::create_dir_all(&root).unwrap();
+        fs::create_dir_all(&outside).unwrap();
+
+        let link = root.join("escape");
+        std::os::unix::fs::symlink(&outside, &link).unwrap();
+
+        assert!(remove_path_guarded(&link, &root).is_err());
+        assert!(outside.exists());
+    }
+
     #[test]
     fn test_remove_path_file() {
         let temp_dir = TempDir::new().unwrap();
@@ -261,6 +543,22 @@ mod tests {
         assert!(filename2.ends_with(".tmp"));
     }
 
+    #[test]
+    fn test_temp_file_paths_are_unique() {
+        let paths: std::collections::HashSet<_> = (0..10_000)
+            .map(|_| temp_file_path("/tmp", Some("download"), Some(".zip")))
+            .collect();
+        assert_eq!(paths.len(), 10_000);
+    }
+
+    #[test]
+    fn test_temp_file_path_with_key() {
+        let first = temp_file_path_with_key("/tmp", None, Some(".part"), "https://x/a b");
+        let retry = temp_file_path_with_key("/tmp", None, Some(".part"), "https://x/a b");
+        assert_eq!(first, retry);
+        assert_eq!(first, PathBuf::from("/tmp/temp_https___x_a_b.part"));
+    }
+
     #[test]
     fn test_get_current_working_dir() {
         let cwd = get_current_working_dir().unwrap();
@@ -1,11 +1,21 @@
 pub mod archive;
+pub mod checksum;
 pub mod filesystem;
+pub mod provider;
 
 // Re-export commonly used filesystem functions
 #[allow(unused_imports)]
-pub use archive::{extract_zip, extract_zip_with_root_stripping};
+pub use archive::{
+    ExtractFilter, extract_zip, extract_zip_stripping, extract_zip_with_root_stripping,
+};
+#[allow(unused_imports)]
+pub use checksum::{sha256_file, sha256_hex};
 #[allow(unused_imports)]
 pub use filesystem::{
-    create_agnostic_working_dir, ensure_dir_exists, file_size, get_current_working_dir,
-    is_directory, is_file, remove_path, temp_file_path,
+    check_dir_path, create_agnostic_working_dir, ensure_dir_exists, file_size,
+    get_current_working_dir, is_directory, is_file, remove_path, remove_path_guarded,
+    temp_file_path, temp_file_path_with_key, write_atomic,
 };
+#[cfg(test)]
+pub use provider::MemoryFs;
+pub use provider::{FsProvider, RealFs};
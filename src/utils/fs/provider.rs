@@ -0,0 +1,163 @@
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+#[cfg(test)]
+use std::{collections::HashMap, path::PathBuf, sync::Mutex};
+
+use super::filesystem::remove_path_guarded;
+
+/// File access used by command logic, so it can run against memory in tests
+///
+/// Only the small set of operations commands need on their state files
+/// (`auth.json`, the cached profile, ...) is covered; bulk work such as
+/// downloads and extraction still goes through `std::fs`.
+pub trait FsProvider: fmt::Debug + Send + Sync {
+    /// Reads a whole file as UTF-8
+    fn read_to_string(&self, path: &Path) -> io::Result<String>;
+
+    /// Writes a file, replacing any previous contents
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Writes a file only the current user can read (mode 0600 on Unix)
+    ///
+    /// An existing file is restricted as well before its contents are replaced.
+    fn write_private(&self, path: &Path, contents: &[u8]) -> io::Result<()>;
+
+    /// Whether a file exists at `path`
+    fn exists(&self, path: &Path) -> io::Result<bool>;
+
+    /// Removes a file, refusing anything outside `allowed_root`
+    ///
+    /// Removing a missing file succeeds. See [`remove_path_guarded`].
+    fn remove(&self, path: &Path, allowed_root: &Path) -> io::Result<()>;
+}
+
+/// [`FsProvider`] backed by the real filesystem
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RealFs;
+
+impl FsProvider for RealFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        fs::read_to_string(path)
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        fs::write(path, contents)
+    }
+
+    fn write_private(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // The mode only applies to new files, so tighten an existing one first
+            if path.try_exists()? {
+                fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+            }
+        }
+        options.open(path)?.write_all(contents)
+    }
+
+    fn exists(&self, path: &Path) -> io::Result<bool> {
+        path.try_exists()
+    }
+
+    fn remove(&self, path: &Path, allowed_root: &Path) -> io::Result<()> {
+        remove_path_guarded(path, allowed_root).map_err(|e| io::Error::other(e.to_string()))
+    }
+}
+
+/// [`FsProvider`] keeping files in memory, for unit tests
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryFs {
+    files: Mutex<HashMap<PathBuf, Vec<u8>>>,
+}
+
+#[cfg(test)]
+impl MemoryFs {
+    /// Paths of all files currently stored
+    pub fn paths(&self) -> Vec<PathBuf> {
+        self.files.lock().unwrap().keys().cloned().collect()
+    }
+}
+
+#[cfg(test)]
+impl FsProvider for MemoryFs {
+    fn read_to_string(&self, path: &Path) -> io::Result<String> {
+        let files = self.files.lock().unwrap();
+        let content = files
+            .get(path)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, path.display().to_string()))?;
+        String::from_utf8(content.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn write(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.files
+            .lock()
+            .unwrap()
+            .insert(path.to_path_buf(), contents.to_vec());
+        Ok(())
+    }
+
+    fn write_private(&self, path: &Path, contents: &[u8]) -> io::Result<()> {
+        self.write(path, contents)
+    }
+
+    fn exists(&self, path: &Path) -> io::Result<bool> {
+        Ok(self.files.lock().unwrap().contains_key(path))
+    }
+
+    fn remove(&self, path: &Path, allowed_root: &Path) -> io::Result<()> {
+        if path == allowed_root || !path.starts_with(allowed_root) {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "Refusing to remove {}: only paths inside {} may be removed",
+                    path.display(),
+                    allowed_root.display()
+                ),
+            ));
+        }
+        self.files.lock().unwrap().remove(path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    /// Runs the same operations against any provider
+    fn exercise(provider: &dyn FsProvider, root: &Path) {
+        let file = root.join("state.json");
+        assert!(!provider.exists(&file).unwrap());
+
+        provider.write(&file, b"{}").unwrap();
+        assert!(provider.exists(&file).unwrap());
+        assert_eq!(provider.read_to_string(&file).unwrap(), "{}");
+
+        assert!(provider.remove(root, root).is_err());
+        provider.remove(&file, root).unwrap();
+        assert!(!provider.exists(&file).unwrap());
+        provider.remove(&file, root).unwrap();
+    }
+
+    #[test]
+    fn test_real_fs() {
+        let temp_dir = TempDir::new().unwrap();
+        exercise(&RealFs, temp_dir.path());
+    }
+
+    #[test]
+    fn test_memory_fs() {
+        let memory = MemoryFs::default();
+        exercise(&memory, Path::new("/home/user/.agnostic"));
+        assert!(memory.paths().is_empty());
+    }
+}
@@ -21,18 +21,22 @@ pub use fs::{
 
 // Network utilities
 #[allow(unused_imports)]
-pub use net::{dl_unzip, download_file, download_to_temp_file};
+pub use net::{
+    ApiError, CliError, api_get, api_post, dl_unzip, download_file, download_to_temp_file,
+    send_api_request,
+};
 
 // Application utilities
 #[allow(unused_imports)]
 pub use app::{
-    AppConfig, AuthTokens, cleanup_app, ensure_valid_tokens, get_agnostic_subdir, initialize_app,
+    AppConfig, AuthTokens, cleanup_app, confirm, ensure_valid_tokens, get_agnostic_subdir,
+    initialize_app,
 };
 
 // Binary utilities
 #[allow(unused_imports)]
 pub use bin::{
-    BinResult, BinaryInfo, BinaryInfoProvider, SystemTarget, agt, clickhouse,
-    ensure_required_binaries, get_binaries_status, get_binary_path, get_binary_version_by_name,
-    registry, s3fs,
+    BinResult, BinaryInfo, BinaryInfoProvider, BinaryManager, BinarySelection, InstallOptions,
+    SystemTarget, VersionOverride, agt, clickhouse, ensure_required_binaries, get_binaries_status,
+    get_binary_path, get_binary_version_by_name, registry, s3fs,
 };
@@ -0,0 +1,307 @@
+use std::time::Duration;
+
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
+use serde::de::DeserializeOwned;
+
+use super::client::{ApiError, send_with_request_id};
+
+/// Largest API response body read before giving up
+pub const MAX_RESPONSE_BYTES: usize = 4 * 1024 * 1024;
+
+/// Time allowed for an API request, from sending it to reading the whole body
+pub const API_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Error returned by the API helpers ([`api_get`], [`api_post`], [`api_get_text`])
+///
+/// Every variant carries the request id to report to support.
+#[derive(Debug, thiserror::Error)]
+pub enum CliError {
+    /// The server rejected the token (HTTP 401)
+    #[error("Authentication failed, please run `user login` again (request id: {request_id})")]
+    Auth { request_id: String },
+    /// Any other non-success response, with the body the server sent back
+    #[error("API request failed with HTTP {status}: {body} (request id: {request_id})")]
+    Api {
+        status: StatusCode,
+        body: String,
+        request_id: String,
+    },
+    /// The request never got a response (connection refused, timeout, ...)
+    #[error(transparent)]
+    Transport(ApiError),
+    /// The response body was not the JSON the command expected
+    #[error("Invalid response from the API: {message} (request id: {request_id})")]
+    Decode { message: String, request_id: String },
+    /// The response body was larger than [`MAX_RESPONSE_BYTES`]
+    #[error("API response is larger than {limit} bytes (request id: {request_id})")]
+    TooLarge { limit: usize, request_id: String },
+}
+
+impl CliError {
+    /// The HTTP status returned by the server, if a response was received
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Auth { .. } => Some(StatusCode::UNAUTHORIZED),
+            Self::Api { status, .. } => Some(*status),
+            Self::Transport(_) | Self::Decode { .. } | Self::TooLarge { .. } => None,
+        }
+    }
+
+    /// The request id to report, as echoed by the server when available
+    pub fn request_id(&self) -> &str {
+        match self {
+            Self::Auth { request_id }
+            | Self::Api { request_id, .. }
+            | Self::Decode { request_id, .. }
+            | Self::TooLarge { request_id, .. } => request_id,
+            Self::Transport(e) => e.request_id(),
+        }
+    }
+}
+
+/// GETs `url` with the id token and deserializes the JSON response
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use cli::utils::net::api::api_get;
+///
+/// let client = reqwest::Client::new();
+/// let user: serde_json::Value =
+///     api_get(&client, "https://app.agnostic.tech/api/user", "id-token").await?;
+/// println!("{}", user["email"]);
+/// # Ok(())
+/// # }
+/// ```
+pub async fn api_get<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+) -> Result<T, CliError> {
+    send_json(client.get(url).bearer_auth(id_token)).await
+}
+
+/// POSTs to `url` with the id token and an optional JSON body
+///
+/// An empty response body deserializes like JSON `null`, so `()` can be used
+/// for endpoints answering `204 No Content`.
+pub async fn api_post<T: DeserializeOwned>(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+    body: Option<&serde_json::Value>,
+) -> Result<T, CliError> {
+    let mut request = client.post(url).bearer_auth(id_token);
+    if let Some(body) = body {
+        request = request.json(body);
+    }
+    send_json(request).await
+}
+
+/// GETs `url` with the id token and query parameters, returning the body as text
+///
+/// Meant for endpoints that answer plain text, such as pipeline logs.
+pub async fn api_get_text(
+    client: &Client,
+    url: &str,
+    id_token: &str,
+    query: &[(&str, String)],
+) -> Result<String, CliError> {
+    let (body, _) = send_checked(client.get(url).bearer_auth(id_token).query(query)).await?;
+    Ok(body)
+}
+
+/// Sends a request tagged with a request id, checks its status and decodes the body
+async fn send_json<T: DeserializeOwned>(builder: RequestBuilder) -> Result<T, CliError> {
+    let (body, request_id) = send_checked(builder).await?;
+
+    let body = if body.trim().is_empty() {
+        "null"
+    } else {
+        &body
+    };
+    serde_json::from_str(body).map_err(|e| CliError::Decode {
+        message: e.to_string(),
+        request_id,
+    })
+}
+
+/// Sends a request tagged with a request id and reads its body, failing on a
+/// non-success status
+///
+/// Returns the body with the request id to report.
+async fn send_checked(builder: RequestBuilder) -> Result<(String, String), CliError> {
+    let (response, request_id) = send_with_request_id(builder.timeout(API_TIMEOUT))
+        .await
+        .map_err(CliError::Transport)?;
+    let status = response.status();
+    let body = read_body_limited(response, MAX_RESPONSE_BYTES, &request_id).await?;
+    let body = String::from_utf8_lossy(&body).into_owned();
+
+    if status == StatusCode::UNAUTHORIZED {
+        return Err(CliError::Auth { request_id });
+    }
+    if !status.is_success() {
+        return Err(CliError::Api {
+            status,
+            body: body.trim().to_string(),
+            request_id,
+        });
+    }
+    Ok((body, request_id))
+}
+
+/// Reads a response body chunk by chunk, failing as soon as it exceeds `limit` bytes
+pub async fn read_body_limited(
+    mut response: Response,
+    limit: usize,
+    request_id: &str,
+) -> Result<Vec<u8>, CliError> {
+    let too_large = || CliError::TooLarge {
+        limit,
+        request_id: request_id.to_string(),
+    };
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|source| {
+        CliError::Transport(ApiError::Request {
+            request_id: request_id.to_string(),
+            source,
+        })
+    })? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::net::client::REQUEST_ID_HEADER;
+    use serde::Deserialize;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Pipeline {
+        name: String,
+    }
+
+    async fn get_pipeline(response: ResponseTemplate) -> Result<Pipeline, CliError> {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/pipelines/demo"))
+            .and(header("authorization", "Bearer id-token"))
+            .respond_with(response.insert_header(REQUEST_ID_HEADER, "srv-1"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let url = format!("{}/api/pipelines/demo", server.uri());
+        api_get(&Client::new(), &url, "id-token").await
+    }
+
+    #[tokio::test]
+    async fn test_api_get_success() {
+        let response =
+            ResponseTemplate::new(200).set_body_raw(r#"{"name":"demo"}"#, "application/json");
+        assert_eq!(
+            get_pipeline(response).await.unwrap(),
+            Pipeline {
+                name: "demo".to_string()
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_get_unauthorized() {
+        let err = get_pipeline(ResponseTemplate::new(401)).await.unwrap_err();
+        assert!(matches!(err, CliError::Auth { .. }));
+        assert_eq!(err.status(), Some(StatusCode::UNAUTHORIZED));
+        assert_eq!(err.request_id(), "srv-1");
+    }
+
+    #[tokio::test]
+    async fn test_api_get_not_found_keeps_body() {
+        let response = ResponseTemplate::new(404).set_body_string("no such pipeline");
+        let err = get_pipeline(response).await.unwrap_err();
+        assert_eq!(err.status(), Some(StatusCode::NOT_FOUND));
+        assert!(
+            err.to_string()
+                .contains("HTTP 404 Not Found: no such pipeline")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_api_get_malformed_json() {
+        let response = ResponseTemplate::new(200).set_body_string("<html>oops</html>");
+        let err = get_pipeline(response).await.unwrap_err();
+        assert!(matches!(err, CliError::Decode { .. }));
+        assert_eq!(err.status(), None);
+        assert_eq!(err.request_id(), "srv-1");
+    }
+
+    #[tokio::test]
+    async fn test_api_get_oversized_body() {
+        let response =
+            ResponseTemplate::new(200).set_body_bytes(vec![b' '; MAX_RESPONSE_BYTES + 1]);
+        let err = get_pipeline(response).await.unwrap_err();
+        assert!(matches!(
+            err,
+            CliError::TooLarge {
+                limit: MAX_RESPONSE_BYTES,
+                ..
+            }
+        ));
+        assert_eq!(err.request_id(), "srv-1");
+    }
+
+    #[tokio::test]
+    async fn test_read_body_limited_without_content_length() {
+        use tokio::io::AsyncWriteExt;
+
+        // A chunked response announces no length, so the cap applies while reading
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut request = [0u8; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut socket, &mut request).await;
+            let _ = socket
+                .write_all(b"HTTP/1.1 200 OK\r\ntransfer-encoding: chunked\r\n\r\n")
+                .await;
+            for _ in 0..4 {
+                let _ = socket.write_all(b"10\r\n0123456789abcdef\r\n").await;
+            }
+            let _ = socket.write_all(b"0\r\n\r\n").await;
+        });
+
+        let response = Client::new().get(&url).send().await.unwrap();
+        let err = read_body_limited(response, 40, "req-1").await.unwrap_err();
+        assert!(err.to_string().contains("larger than 40 bytes"));
+    }
+
+    #[tokio::test]
+    async fn test_api_post_empty_response() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result: Result<(), CliError> =
+            api_post(&Client::new(), &server.uri(), "id-token", None).await;
+        assert!(result.is_ok());
+    }
+}
@@ -0,0 +1,448 @@
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+
+use reqwest::{
+    Certificate, Client, ClientBuilder, Proxy, RequestBuilder, Response, StatusCode,
+    redirect::Policy,
+};
+use uuid::Uuid;
+
+/// Header used to correlate a CLI request with server-side logs
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Error returned by API calls, always carrying the request id to report to support
+#[derive(Debug, thiserror::Error)]
+pub enum ApiError {
+    #[error("Request failed: {source} (request id: {request_id})")]
+    Request {
+        request_id: String,
+        #[source]
+        source: reqwest::Error,
+    },
+    #[error("API request failed with HTTP {status} (request id: {request_id})")]
+    Status {
+        status: StatusCode,
+        request_id: String,
+    },
+}
+
+impl ApiError {
+    /// The request id to report, as echoed by the server when available
+    pub fn request_id(&self) -> &str {
+        match self {
+            Self::Request { request_id, .. } | Self::Status { request_id, .. } => request_id,
+        }
+    }
+
+    /// The HTTP status returned by the server, if a response was received
+    pub fn status(&self) -> Option<StatusCode> {
+        match self {
+            Self::Request { .. } => None,
+            Self::Status { status, .. } => Some(*status),
+        }
+    }
+}
+
+/// Default `User-Agent` sent with every request, e.g. `agnostic-cli/0.1.0 (linux; x86_64)`
+pub fn default_user_agent() -> String {
+    format!(
+        "agnostic-cli/{} ({}; {})",
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS,
+        std::env::consts::ARCH
+    )
+}
+
+/// Redirects followed per request unless `--max-redirects` says otherwise
+pub const DEFAULT_MAX_REDIRECTS: usize = 10;
+
+/// Which proxy a client sends its requests through
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum ProxySetting {
+    /// Follow the `HTTP_PROXY` / `HTTPS_PROXY` / `NO_PROXY` environment variables
+    #[default]
+    System,
+    /// Send every request through this proxy URL (`--proxy`)
+    Url(String),
+    /// Connect directly, ignoring the environment
+    Disabled,
+}
+
+/// Settings shared by every HTTP client the CLI builds
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    /// Extra PEM root certificate to trust (`--ca-cert`), e.g. a private CA
+    pub ca_cert: Option<PathBuf>,
+    /// Disable certificate verification entirely (`--insecure`), for local testing only
+    pub insecure: bool,
+    /// `User-Agent` override (`--user-agent`), [`default_user_agent`] otherwise
+    pub user_agent: Option<String>,
+    /// Redirects followed per request (`--max-redirects`), `0` disables following
+    pub max_redirects: usize,
+    /// Print each redirect hop to stderr (enabled with `--verbose`)
+    pub log_redirects: bool,
+    /// Proxy to go through, see [`ProxySetting`]
+    pub proxy: ProxySetting,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            ca_cert: None,
+            insecure: false,
+            user_agent: None,
+            max_redirects: DEFAULT_MAX_REDIRECTS,
+            log_redirects: false,
+            proxy: ProxySetting::default(),
+        }
+    }
+}
+
+impl ClientOptions {
+    /// Creates a client builder with these settings applied
+    pub fn builder(&self) -> Result<ClientBuilder, Box<dyn Error>> {
+        let user_agent = self.user_agent.clone().unwrap_or_else(default_user_agent);
+        let mut builder = Client::builder().user_agent(user_agent);
+
+        if let Some(path) = &self.ca_cert {
+            let pem = fs::read(path)
+                .map_err(|e| format!("Failed to read CA certificate {}: {}", path.display(), e))?;
+            let cert = Certificate::from_pem(&pem)
+                .map_err(|e| format!("Invalid CA certificate {}: {}", path.display(), e))?;
+            builder = builder.add_root_certificate(cert);
+        }
+
+        if self.insecure {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+
+        match &self.proxy {
+            ProxySetting::System => {}
+            ProxySetting::Url(url) => {
+                let proxy =
+                    Proxy::all(url).map_err(|e| format!("Invalid proxy URL {}: {}", url, e))?;
+                builder = builder.proxy(proxy);
+            }
+            ProxySetting::Disabled => builder = builder.no_proxy(),
+        }
+
+        Ok(builder.redirect(self.redirect_policy()))
+    }
+
+    /// Redirect policy enforcing `max_redirects` and logging hops when asked
+    fn redirect_policy(&self) -> Policy {
+        let max_redirects = self.max_redirects;
+        let log_redirects = self.log_redirects;
+        if max_redirects == 0 {
+            return Policy::none();
+        }
+
+        Policy::custom(move |attempt| {
+            let hops = attempt.previous().len();
+            if hops > max_redirects {
+                return attempt.error(format!(
+                    "Too many redirects (more than {}, see --max-redirects)",
+                    max_redirects
+                ));
+            }
+            if log_redirects && let Some(from) = attempt.previous().last() {
+                eprintln!(
+                    "Redirect {}/{}: {} -> {} (HTTP {})",
+                    hops,
+                    max_redirects,
+                    from,
+                    attempt.url(),
+                    attempt.status().as_u16()
+                );
+            }
+            attempt.follow()
+        })
+    }
+
+    /// Builds a client with these settings applied
+    pub fn build(&self) -> Result<Client, Box<dyn Error>> {
+        Ok(self.builder()?.build()?)
+    }
+}
+
+/// Request id attached to the extensions of responses returned by [`send_api_request`]
+#[derive(Debug, Clone)]
+struct RequestId(String);
+
+/// Returns the request id of a response obtained through [`send_api_request`]
+pub fn response_request_id(response: &Response) -> String {
+    response
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.clone())
+        .unwrap_or_default()
+}
+
+/// Generates a new unique request id
+pub fn new_request_id() -> String {
+    Uuid::new_v4().to_string()
+}
+
+/// Sends a request tagged with a generated `X-Request-Id` header, whatever its status
+///
+/// Returns the response with the request id to report: the one echoed back by
+/// the server, or the generated one otherwise.
+pub(crate) async fn send_with_request_id(
+    builder: RequestBuilder,
+) -> Result<(Response, String), ApiError> {
+    let request_id = new_request_id();
+
+    let response = builder
+        .header(REQUEST_ID_HEADER, &request_id)
+        .send()
+        .await
+        .map_err(|source| ApiError::Request {
+            request_id: request_id.clone(),
+            source,
+        })?;
+
+    let request_id = response
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or(request_id);
+    Ok((response, request_id))
+}
+
+/// Sends an API request tagged with a generated `X-Request-Id` header
+///
+/// Non-success responses are turned into an [`ApiError::Status`] carrying the
+/// request id echoed back by the server, or the generated one otherwise.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// use cli::utils::net::client::send_api_request;
+///
+/// let client = reqwest::Client::new();
+/// let response = send_api_request(client.get("https://app.agnostic.tech/api/user")).await?;
+/// println!("HTTP {}", response.status());
+/// # Ok(())
+/// # }
+/// ```
+pub async fn send_api_request(builder: RequestBuilder) -> Result<Response, ApiError> {
+    let (mut response, request_id) = send_with_request_id(builder).await?;
+
+    if response.status().is_success() {
+        response.extensions_mut().insert(RequestId(request_id));
+        return Ok(response);
+    }
+
+    Err(ApiError::Status {
+        status: response.status(),
+        request_id,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_client_options_with_ca_cert() {
+        let options = ClientOptions {
+            ca_cert: Some(
+                PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/test-ca.pem"),
+            ),
+            ..Default::default()
+        };
+        assert!(options.build().is_ok());
+
+        let options = ClientOptions {
+            ca_cert: Some(PathBuf::from("/nonexistent/ca.pem")),
+            ..Default::default()
+        };
+        let err = options.build().unwrap_err().to_string();
+        assert!(err.contains("/nonexistent/ca.pem"));
+    }
+
+    #[test]
+    fn test_client_options_insecure() {
+        let builder = ClientOptions::default().builder().unwrap();
+        assert!(!format!("{:?}", builder).contains("danger_accept_invalid_certs"));
+
+        let options = ClientOptions {
+            insecure: true,
+            ..Default::default()
+        };
+        let builder = options.builder().unwrap();
+        assert!(format!("{:?}", builder).contains("danger_accept_invalid_certs: true"));
+        assert!(options.build().is_ok());
+    }
+
+    #[test]
+    fn test_client_options_proxy() {
+        let download = ClientOptions {
+            proxy: ProxySetting::Url("http://proxy.example:3128".to_string()),
+            ..Default::default()
+        };
+        let api = ClientOptions {
+            proxy: ProxySetting::Disabled,
+            ..download.clone()
+        };
+
+        let download_builder = format!("{:?}", download.builder().unwrap());
+        let api_builder = format!("{:?}", api.builder().unwrap());
+        assert!(download_builder.contains("proxy.example:3128"));
+        assert!(!api_builder.contains("proxy.example"));
+        assert_ne!(download_builder, api_builder);
+        assert!(download.build().is_ok());
+        assert!(api.build().is_ok());
+
+        let invalid = ClientOptions {
+            proxy: ProxySetting::Url("not a url".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid.build().is_err());
+    }
+
+    #[test]
+    fn test_default_user_agent() {
+        let user_agent = default_user_agent();
+        assert!(user_agent.starts_with(&format!("agnostic-cli/{} (", env!("CARGO_PKG_VERSION"))));
+        assert!(user_agent.contains(std::env::consts::OS));
+        assert!(user_agent.ends_with(&format!("{})", std::env::consts::ARCH)));
+    }
+
+    #[tokio::test]
+    async fn test_user_agent_is_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", default_user_agent().as_str()))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(header("user-agent", "ci-bot/1.0"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ClientOptions::default().build().unwrap();
+        let response = client.get(server.uri()).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let options = ClientOptions {
+            user_agent: Some("ci-bot/1.0".to_string()),
+            ..Default::default()
+        };
+        let response = options
+            .build()
+            .unwrap()
+            .get(server.uri())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    }
+
+    #[tokio::test]
+    async fn test_max_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/mirror/agt"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/hop/agt"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/hop/agt"))
+            .respond_with(ResponseTemplate::new(301).insert_header("location", "/files/agt"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/files/agt"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"binary".to_vec()))
+            .mount(&server)
+            .await;
+        let url = format!("{}/mirror/agt", server.uri());
+
+        let options = ClientOptions {
+            log_redirects: true,
+            ..Default::default()
+        };
+        let response = options.build().unwrap().get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(response.url().path().ends_with("/files/agt"));
+        assert_eq!(response.bytes().await.unwrap().as_ref(), b"binary");
+
+        let options = ClientOptions {
+            max_redirects: 0,
+            ..Default::default()
+        };
+        let response = options.build().unwrap().get(&url).send().await.unwrap();
+        assert_eq!(response.status(), StatusCode::FOUND);
+        assert_eq!(response.headers()["location"], "/hop/agt");
+
+        let options = ClientOptions {
+            max_redirects: 1,
+            ..Default::default()
+        };
+        let err = options.build().unwrap().get(&url).send().await.unwrap_err();
+        assert!(err.is_redirect());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header_is_sent() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/api/user"))
+            .and(header_exists(REQUEST_ID_HEADER))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let url = format!("{}/api/user", server.uri());
+        let response = send_api_request(client.get(url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(Uuid::parse_str(&response_request_id(&response)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_server_request_id_in_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(500).insert_header(REQUEST_ID_HEADER, "srv-1234"))
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let error = send_api_request(client.get(server.uri()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error.status(), Some(StatusCode::INTERNAL_SERVER_ERROR));
+        assert_eq!(error.request_id(), "srv-1234");
+        assert!(error.to_string().contains("srv-1234"));
+    }
+
+    #[tokio::test]
+    async fn test_generated_request_id_in_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = Client::new();
+        let error = send_api_request(client.get(server.uri()))
+            .await
+            .unwrap_err();
+
+        assert!(Uuid::parse_str(error.request_id()).is_ok());
+        assert!(error.to_string().contains(error.request_id()));
+    }
+}
@@ -1,21 +1,39 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use reqwest::Client;
 
 use crate::utils::{
-    fs::{ensure_dir_exists, extract_zip_with_root_stripping, remove_path, temp_file_path},
-    net::download::download_to_temp_file,
+    fs::{ExtractFilter, ensure_dir_exists, extract_zip_stripping, temp_file_path},
+    net::{RetryPolicy, download::download_resumable},
 };
 
+/// Removes a temporary file when dropped
+///
+/// Cleanup runs on every exit path, including early returns on error and a
+/// cancelled download future.
+struct TempFileGuard(PathBuf);
+
+impl Drop for TempFileGuard {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
 /// Downloads a ZIP file from the given URL and extracts it to the destination directory
 ///
 /// This function automatically strips the root folder from ZIP archives (common with
 /// GitHub repository downloads) and extracts the contents directly to the destination.
 /// For example, if a ZIP contains "project-main/file.txt", it will be extracted as
-/// "dest/file.txt" instead of "dest/project-main/file.txt".
+/// "dest/file.txt" instead of "dest/project-main/file.txt". Archives whose entries
+/// are not all nested in one folder are extracted as they are.
 ///
 /// # Arguments
 ///
+/// * `client` - The HTTP client to download with
 /// * `url` - The URL to download the ZIP file from
 /// * `dest` - The destination directory to extract the contents to
+/// * `filter` - Which entries to extract, see [`ExtractFilter`]
 ///
 /// # Returns
 ///
@@ -25,33 +43,49 @@ use crate::utils::{
 ///
 /// ```no_run
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
-/// use cli::utils::dl_unzip;
+/// use cli::utils::{dl_unzip, fs::ExtractFilter};
 ///
 /// // Download and extract a ZIP file (root folder will be stripped)
-/// dl_unzip("https://github.com/user/repo/archive/main.zip", "./extracted").await?;
+/// let client = reqwest::Client::new();
+/// let url = "https://github.com/user/repo/archive/main.zip";
+/// dl_unzip(&client, url, "./extracted", &ExtractFilter::default()).await?;
 /// # Ok(())
 /// # }
 /// ```
+#[allow(dead_code)]
 pub async fn dl_unzip<P: AsRef<Path>>(
+    client: &Client,
+    url: &str,
+    dest: P,
+    filter: &ExtractFilter,
+) -> Result<(), Box<dyn std::error::Error>> {
+    dl_unzip_stripping(client, url, dest, None, filter).await
+}
+
+/// Like [`dl_unzip`], removing `strip_components` leading path components when given
+///
+/// See [`extract_zip_stripping`]; `Some(0)` extracts the archive as-is and
+/// `None` detects the root folder like [`dl_unzip`].
+pub async fn dl_unzip_stripping<P: AsRef<Path>>(
+    client: &Client,
     url: &str,
     dest: P,
+    strip_components: Option<usize>,
+    filter: &ExtractFilter,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let dest_path = dest.as_ref();
 
     // Ensure destination directory exists
     ensure_dir_exists(dest_path)?;
 
-    // Create a temporary file path for the download
-    let temp_file_path = temp_file_path(dest_path, Some("download"), Some(".zip"));
+    // Create a temporary file path for the download, removed however we return
+    let temp_file = TempFileGuard(temp_file_path(dest_path, Some("download"), Some(".zip")));
 
-    // Download the file to the temporary location
-    download_to_temp_file(url, &temp_file_path).await?;
+    // Download the file to the temporary location, resuming after dropped connections
+    download_resumable(client, url, &temp_file.0, &RetryPolicy::default()).await?;
 
-    // Extract the ZIP file with root folder stripping
-    extract_zip_with_root_stripping(&temp_file_path, dest_path)?;
-
-    // Clean up the temporary file
-    remove_path(&temp_file_path)?;
+    // Extract the ZIP file, stripping the requested leading components
+    extract_zip_stripping(&temp_file.0, dest_path, strip_components, filter)?;
 
     Ok(())
 }
@@ -59,6 +93,7 @@ pub async fn dl_unzip<P: AsRef<Path>>(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::fs::remove_path;
     use tempfile::TempDir;
 
     #[test]
@@ -84,4 +119,109 @@ mod tests {
         remove_path(&temp_path).unwrap();
         assert!(!temp_path.exists());
     }
+
+    /// Serves `zip` once truncated to half its length, then honours the `Range` retry
+    ///
+    /// Returns the server URL and the `Range` headers received.
+    async fn serve_interrupted_zip(
+        zip: Vec<u8>,
+    ) -> (String, std::sync::Arc<std::sync::Mutex<Vec<String>>>) {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let ranges = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = ranges.clone();
+
+        tokio::spawn(async move {
+            for attempt in 0.. {
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut request = vec![0; 4096];
+                let n = socket.read(&mut request).await.unwrap();
+                let request = String::from_utf8_lossy(&request[..n]).to_lowercase();
+                let range = request
+                    .lines()
+                    .find_map(|line| line.strip_prefix("range: bytes="))
+                    .map(|range| range.trim_end_matches('-').parse::<usize>().unwrap());
+                received
+                    .lock()
+                    .unwrap()
+                    .extend(range.map(|r| r.to_string()));
+
+                if attempt == 0 {
+                    // Announce the full length, send half, then drop the connection
+                    let head = format!("HTTP/1.1 200 OK\r\ncontent-length: {}\r\n\r\n", zip.len());
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&zip[..zip.len() / 2]).await.unwrap();
+                } else {
+                    let start = range.unwrap_or(0);
+                    let head = format!(
+                        "HTTP/1.1 206 Partial Content\r\ncontent-length: {}\r\ncontent-range: bytes {}-{}/{}\r\nconnection: close\r\n\r\n",
+                        zip.len() - start,
+                        start,
+                        zip.len() - 1,
+                        zip.len()
+                    );
+                    socket.write_all(head.as_bytes()).await.unwrap();
+                    socket.write_all(&zip[start..]).await.unwrap();
+                }
+                socket.shutdown().await.unwrap();
+            }
+        });
+
+        (format!("http://{}/init.zip", addr), ranges)
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_download_resumes() {
+        use std::io::Write;
+
+        let mut zip = Vec::new();
+        {
+            let mut writer = zip::ZipWriter::new(std::io::Cursor::new(&mut zip));
+            let options = zip::write::FileOptions::default()
+                .compression_method(zip::CompressionMethod::Stored);
+            writer.start_file("init-main/README.md", options).unwrap();
+            writer.write_all(&[b'x'; 4096]).unwrap();
+            writer.finish().unwrap();
+        }
+        let (url, ranges) = serve_interrupted_zip(zip.clone()).await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest_path = temp_dir.path().join("project");
+        dl_unzip(&Client::new(), &url, &dest_path, &ExtractFilter::default())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            std::fs::read(dest_path.join("README.md")).unwrap(),
+            [b'x'; 4096]
+        );
+        assert_eq!(*ranges.lock().unwrap(), [(zip.len() / 2).to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_failed_extraction_removes_temp_zip() {
+        use wiremock::matchers::method;
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"not a zip".to_vec()))
+            .mount(&server)
+            .await;
+
+        let temp_dir = TempDir::new().unwrap();
+        let dest_path = temp_dir.path().join("project");
+        let result = dl_unzip(
+            &Client::new(),
+            &format!("{}/init.zip", server.uri()),
+            &dest_path,
+            &ExtractFilter::default(),
+        )
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_dir(&dest_path).unwrap().count(), 0);
+    }
 }
@@ -1,10 +1,16 @@
-use reqwest::Client;
+use futures_util::TryStreamExt;
+use reqwest::{Client, StatusCode, header::RANGE};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
 use std::path::Path;
 
+use super::retry::{RetryPolicy, is_transient_network_error, retry};
+
 /// Downloads a file from the given URL and returns the content as bytes
 ///
 /// # Arguments
 ///
+/// * `client` - The HTTP client to download with
 /// * `url` - The URL to download the file from
 ///
 /// # Returns
@@ -17,15 +23,19 @@ use std::path::Path;
 /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
 /// use cli::utils::net::download::download_file;
 ///
-/// let content = download_file("https://example.com/file.zip").await?;
+/// let client = reqwest::Client::new();
+/// let content = download_file(&client, "https://example.com/file.zip").await?;
 /// println!("Downloaded {} bytes", content.len());
 /// # Ok(())
 /// # }
 /// ```
-pub async fn download_file(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+#[allow(dead_code)]
+pub async fn download_file(
+    client: &Client,
+    url: &str,
+) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
     println!("Downloading from: {}", url);
 
-    let client = Client::new();
     let response = client.get(url).send().await?;
 
     if !response.status().is_success() {
@@ -42,20 +52,23 @@ pub async fn download_file(url: &str) -> Result<Vec<u8>, Box<dyn std::error::Err
 ///
 /// # Arguments
 ///
+/// * `client` - The HTTP client to download with
 /// * `url` - The URL to download the file from
 /// * `temp_path` - The temporary file path to save to
 ///
 /// # Returns
 ///
 /// Returns `Ok(())` if successful, or an error if the download or save fails
+#[allow(dead_code)]
 pub async fn download_to_temp_file<P: AsRef<Path>>(
+    client: &Client,
     url: &str,
     temp_path: P,
 ) -> Result<(), Box<dyn std::error::Error>> {
     use std::fs::File;
     use std::io::Write;
 
-    let content = download_file(url).await?;
+    let content = download_file(client, url).await?;
 
     let mut temp_file = File::create(&temp_path)?;
     temp_file.write_all(&content)?;
@@ -64,6 +77,63 @@ pub async fn download_to_temp_file<P: AsRef<Path>>(
     Ok(())
 }
 
+/// Downloads a file to `path`, resuming interrupted transfers
+///
+/// A transfer cut by a network failure is retried according to `policy`,
+/// asking only for the missing bytes with a `Range` header. A server that
+/// ignores the range (answering `200 OK`) restarts the file from scratch.
+pub async fn download_resumable<P: AsRef<Path>>(
+    client: &Client,
+    url: &str,
+    path: P,
+    policy: &RetryPolicy,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = path.as_ref();
+    println!("Downloading from: {}", url);
+
+    File::create(path)?;
+    retry(
+        || download_remaining(client, url, path),
+        policy,
+        |e| is_transient_network_error(e.as_ref()),
+    )
+    .await?;
+
+    println!("Downloaded {} bytes", fs::metadata(path)?.len());
+    Ok(())
+}
+
+/// Appends the bytes of `url` not yet present in `path`
+async fn download_remaining(
+    client: &Client,
+    url: &str,
+    path: &Path,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let offset = fs::metadata(path)?.len();
+    let mut request = client.get(url);
+    if offset > 0 {
+        println!("Resuming download at byte {}", offset);
+        request = request.header(RANGE, format!("bytes={}-", offset));
+    }
+
+    let response = request.send().await?;
+    let mut file = match response.status() {
+        StatusCode::PARTIAL_CONTENT if offset > 0 => OpenOptions::new().append(true).open(path)?,
+        // Everything was already received before the connection dropped
+        StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+        status if status.is_success() => File::create(path)?,
+        status => return Err(format!("Failed to download file: HTTP {}", status).into()),
+    };
+
+    let mut body = response.bytes_stream();
+    while let Some(chunk) = body.try_next().await? {
+        file.write_all(&chunk)?;
+    }
+    file.sync_all()?;
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::fs;
@@ -1,8 +1,16 @@
+pub mod api;
+pub mod client;
 pub mod dl_unzip;
 pub mod download;
+pub mod retry;
 
 // Re-export commonly used network functions
 #[allow(unused_imports)]
-pub use dl_unzip::dl_unzip;
+pub use api::{CliError, api_get, api_post};
 #[allow(unused_imports)]
-pub use download::{download_file, download_to_temp_file};
+pub use client::{ApiError, ClientOptions, DEFAULT_MAX_REDIRECTS, ProxySetting, send_api_request};
+#[allow(unused_imports)]
+pub use dl_unzip::{dl_unzip, dl_unzip_stripping};
+#[allow(unused_imports)]
+pub use download::{download_file, download_resumable, download_to_temp_file};
+pub use retry::{RetryPolicy, is_transient_network_error, retry};
@@ -0,0 +1,188 @@
+use std::future::Future;
+use std::time::Duration;
+
+/// How [`retry`] spaces out and bounds repeated attempts
+///
+/// The delay before attempt `n + 1` is `base_delay * 2^(n - 1)`, capped at
+/// `max_delay`. With `jitter`, a random amount of up to half of that delay is
+/// taken off so concurrent clients do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one (at least 1)
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound for any single delay
+    pub max_delay: Duration,
+    /// Whether to randomize delays
+    pub jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(10),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay to wait after the given failed attempt (1-based), before jitter
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.base_delay
+            .checked_mul(factor)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay)
+    }
+
+    /// Delay to wait after the given failed attempt (1-based), jitter included
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let delay = self.backoff(attempt);
+        if !self.jitter {
+            return delay;
+        }
+
+        // Take off a random fraction (0-50%) of the delay
+        let random = (uuid::Uuid::new_v4().as_u128() % 1_000) as u32;
+        delay - delay / 2 * random / 1_000
+    }
+}
+
+/// Runs `op` until it succeeds, fails with a non-retryable error or runs out of attempts
+///
+/// `retryable` decides which errors are worth another attempt; the last error
+/// is returned when giving up.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn example() -> Result<(), reqwest::Error> {
+/// use cli::utils::net::retry::{RetryPolicy, retry};
+///
+/// let client = reqwest::Client::new();
+/// let response = retry(
+///     || client.get("https://app.agnostic.tech").send(),
+///     &RetryPolicy::default(),
+///     |e| e.is_timeout() || e.is_connect(),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub async fn retry<T, E, F, Fut>(
+    mut op: F,
+    policy: &RetryPolicy,
+    retryable: impl Fn(&E) -> bool,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < policy.max_attempts && retryable(&e) => {
+                tokio::time::sleep(policy.delay(attempt)).await;
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Whether a failed request is worth retrying against the same URL
+///
+/// Only network-level failures (timeouts, refused connections, truncated
+/// bodies) qualify; an HTTP error status is left to the caller.
+pub fn is_transient_network_error(error: &(dyn std::error::Error + 'static)) -> bool {
+    error
+        .downcast_ref::<reqwest::Error>()
+        .is_some_and(|e| e.is_timeout() || e.is_connect() || e.is_body())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    fn fast_policy(max_attempts: u32) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(5),
+            jitter: false,
+        }
+    }
+
+    /// Fails with `Err(attempt)` for the first `failures` calls
+    async fn flaky(calls: &Cell<u32>, failures: u32) -> Result<&'static str, u32> {
+        calls.set(calls.get() + 1);
+        if calls.get() <= failures {
+            Err(calls.get())
+        } else {
+            Ok("done")
+        }
+    }
+
+    #[tokio::test]
+    async fn test_succeeds_first_try() {
+        let calls = Cell::new(0);
+        let result = retry(|| flaky(&calls, 0), &fast_policy(3), |_| true).await;
+        assert_eq!(result, Ok("done"));
+        assert_eq!(calls.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_succeeds_after_failures() {
+        let calls = Cell::new(0);
+        let result = retry(|| flaky(&calls, 2), &fast_policy(3), |_| true).await;
+        assert_eq!(result, Ok("done"));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_gives_up_after_max_attempts() {
+        let calls = Cell::new(0);
+        let result = retry(|| flaky(&calls, 5), &fast_policy(3), |_| true).await;
+        assert_eq!(result, Err(3));
+        assert_eq!(calls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_respects_retryable_predicate() {
+        let calls = Cell::new(0);
+        let result = retry(|| flaky(&calls, 5), &fast_policy(5), |attempt| *attempt < 2).await;
+        assert_eq!(result, Err(2));
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn test_backoff_is_exponential_and_capped() {
+        let policy = RetryPolicy {
+            max_attempts: 10,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(1_000),
+            jitter: false,
+        };
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(4), Duration::from_millis(800));
+        assert_eq!(policy.backoff(5), Duration::from_millis(1_000));
+        assert_eq!(policy.backoff(40), Duration::from_millis(1_000));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+
+        let jittered = RetryPolicy {
+            jitter: true,
+            ..policy
+        };
+        for _ in 0..20 {
+            let delay = jittered.delay(3);
+            assert!(delay >= Duration::from_millis(200) && delay <= Duration::from_millis(400));
+        }
+    }
+}